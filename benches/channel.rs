@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use stwo::commitment_scheme::blake2_hash::Blake2sHash;
+use stwo::core::channel::{Blake2sChannel, Channel};
+
+fn draw_queries_benchmark(c: &mut Criterion) {
+    const N_QUERIES: usize = 1 << 10;
+    const LOG_DOMAIN_SIZE: u32 = 20;
+    let channel = || Blake2sChannel::new(Blake2sHash::default());
+    c.bench_function("draw_queries", |b| {
+        b.iter(|| {
+            black_box(
+                channel()
+                    .draw_queries(black_box(N_QUERIES), LOG_DOMAIN_SIZE)
+                    .unwrap(),
+            );
+        })
+    });
+    c.bench_function("draw_queries_batched", |b| {
+        b.iter(|| {
+            black_box(
+                channel()
+                    .draw_queries_batched(black_box(N_QUERIES), LOG_DOMAIN_SIZE)
+                    .unwrap(),
+            );
+        })
+    });
+}
+
+criterion_group!(benches, draw_queries_benchmark);
+criterion_main!(benches);
@@ -5,8 +5,10 @@ use criterion::{
     criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
 };
 use stwo::commitment_scheme::blake2_hash::Blake2sHasher;
+use stwo::commitment_scheme::blake2s_simd_hash::Blake2sSimdHasher;
 use stwo::commitment_scheme::blake3_hash::Blake3Hasher;
-use stwo::commitment_scheme::hasher::{Hasher, Name};
+use stwo::commitment_scheme::hasher::Hasher;
+use stwo::commitment_scheme::hasher_pool::HasherPool;
 use stwo::commitment_scheme::merkle_tree::MerkleTree;
 use stwo::core::fields::m31::M31;
 
@@ -22,7 +24,7 @@ fn merkle_bench<T: Hasher>(group: &mut BenchmarkGroup<'_, WallTime>, elems: &[M3
     group.sample_size(10);
     group.throughput(Throughput::Bytes((size * N_BYTES_U32) as u64));
     group.bench_with_input(
-        BenchmarkId::new(T::Hash::NAME, size),
+        BenchmarkId::new(T::NAME, size),
         &size,
         |b: &mut criterion::Bencher<'_>, &_size| {
             b.iter_batched(
@@ -90,6 +92,72 @@ fn single_blake2s_hash_benchmark(c: &mut Criterion) {
     });
 }
 
+fn hash_many_blake2s_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Blake2s_hash_many");
+    for exp in 15u32..20u32 {
+        // Set up.
+        let size = 2usize.pow(exp);
+        let inputs: Vec<[u8; 64]> = (0..size).map(|i| [i as u8; 64]).collect();
+        let input_refs: Vec<&[u8]> = inputs.iter().map(|input| &input[..]).collect();
+
+        // Benchmark Loop.
+        group.throughput(Throughput::Bytes((size * 64) as u64));
+        group.bench_with_input(BenchmarkId::new("scalar", size), &size, |b, &_size| {
+            b.iter(|| Blake2sHasher::hash_many(&input_refs))
+        });
+        group.bench_with_input(BenchmarkId::new("simd", size), &size, |b, &_size| {
+            b.iter(|| Blake2sSimdHasher::hash_many(&input_refs))
+        });
+    }
+    group.finish();
+}
+
+fn hasher_pool_benchmark(c: &mut Criterion) {
+    let inputs: Vec<[u8; 64]> = (0..1024).map(|i| [i as u8; 64]).collect();
+    let mut group = c.benchmark_group("Blake2s_hasher_reuse");
+    group.throughput(Throughput::Bytes((inputs.len() * 64) as u64));
+    group.bench_function("fresh", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                let mut hasher = Blake2sHasher::new();
+                hasher.update(input);
+                hasher.finalize();
+            }
+        })
+    });
+    let pool = HasherPool::<Blake2sHasher>::new();
+    group.bench_function("pool", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                pool.hash(input);
+            }
+        })
+    });
+    group.finish();
+}
+
+fn tls_hash_benchmark(c: &mut Criterion) {
+    let inputs: Vec<[u8; 8]> = (0..1 << 20).map(|i: u64| i.to_le_bytes()).collect();
+    let mut group = c.benchmark_group("Blake2s_thread_local_hash");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(inputs.len() as u64));
+    group.bench_function("fresh", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                Blake2sHasher::hash(input);
+            }
+        })
+    });
+    group.bench_function("thread_local", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                Blake2sHasher::hash_with_tls(input);
+            }
+        })
+    });
+    group.finish();
+}
+
 fn single_blake3_hash_benchmark(c: &mut Criterion) {
     let input = [0u8; 1];
     c.bench_function("Single blake3 hash", |b| b.iter(|| blake3::hash(&input)));
@@ -109,4 +177,11 @@ criterion_group!(
     single_blake3_hash_benchmark,
 );
 
-criterion_main!(comparisons);
+criterion_group!(
+    hash_many,
+    hash_many_blake2s_benchmark,
+    hasher_pool_benchmark,
+    tls_hash_benchmark,
+);
+
+criterion_main!(comparisons, hash_many);
@@ -0,0 +1,104 @@
+use tokio::task::{self, JoinHandle};
+
+use super::hasher::Hasher;
+
+/// Chunks of at least this many bytes are hashed on the blocking pool of the runtime.
+pub const BLOCKING_CHUNK_SIZE: usize = 1 << 16;
+
+/// A wrapper of a byte [Hasher] for hashing data as it arrives from async IO, e.g. trace data
+/// uploaded over the network. Large chunks are hashed with [task::spawn_blocking], so that the
+/// event loop stays responsive, while small chunks are hashed in place. The digest is the same as
+/// hashing the concatenated chunks with the wrapped hasher.
+///
+/// Dropping an [AsyncHasher::update] future before completion doesn't lose data: the chunk is
+/// still absorbed, and the next call waits for it.
+pub struct AsyncHasher<H: Hasher<NativeType = u8> + Send + 'static> {
+    /// The hasher, unless it's on the blocking pool.
+    hasher: Option<H>,
+    /// The hashing of a large chunk, returning the hasher.
+    pending: Option<JoinHandle<H>>,
+}
+
+impl<H: Hasher<NativeType = u8> + Send + 'static> AsyncHasher<H> {
+    pub fn new() -> Self {
+        Self::from_hasher(H::new())
+    }
+
+    pub fn from_hasher(hasher: H) -> Self {
+        Self {
+            hasher: Some(hasher),
+            pending: None,
+        }
+    }
+
+    /// Must be called from within a tokio runtime. Chunks of [BLOCKING_CHUNK_SIZE] bytes or more
+    /// are copied to be moved to the blocking pool.
+    pub async fn update(&mut self, data: &[u8]) {
+        if data.len() < BLOCKING_CHUNK_SIZE {
+            self.hasher().await.update(data);
+            return;
+        }
+        self.hasher().await;
+        let mut hasher = self.hasher.take().unwrap();
+        let data = data.to_vec();
+        self.pending = Some(task::spawn_blocking(move || {
+            hasher.update(&data);
+            hasher
+        }));
+        self.hasher().await;
+    }
+
+    pub async fn finalize(mut self) -> H::Hash {
+        self.hasher().await;
+        self.hasher.unwrap().finalize()
+    }
+
+    /// Waits for the pending chunk, if any, and returns the hasher.
+    async fn hasher(&mut self) -> &mut H {
+        if let Some(pending) = &mut self.pending {
+            let hasher = pending
+                .await
+                .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()));
+            self.pending = None;
+            self.hasher = Some(hasher);
+        }
+        self.hasher.as_mut().unwrap()
+    }
+}
+
+impl<H: Hasher<NativeType = u8> + Send + 'static> Default for AsyncHasher<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncHasher, BLOCKING_CHUNK_SIZE};
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::hasher::Hasher;
+
+    #[tokio::test]
+    async fn async_hasher_test() {
+        let data = (0..5 * BLOCKING_CHUNK_SIZE)
+            .map(|i| i as u8)
+            .collect::<Vec<u8>>();
+        // Small chunks, then chunks large enough for the blocking pool.
+        let chunk_ends = [
+            0,
+            1,
+            100,
+            1000,
+            BLOCKING_CHUNK_SIZE,
+            3 * BLOCKING_CHUNK_SIZE,
+        ];
+        let mut hasher = AsyncHasher::<Blake2sHasher>::new();
+
+        for (start, end) in chunk_ends.iter().zip(&chunk_ends[1..]) {
+            hasher.update(&data[*start..*end]).await;
+        }
+        hasher.update(&data[3 * BLOCKING_CHUNK_SIZE..]).await;
+
+        assert_eq!(hasher.finalize().await, Blake2sHasher::hash(&data));
+    }
+}
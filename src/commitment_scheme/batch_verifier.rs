@@ -0,0 +1,224 @@
+use alloc::collections::BTreeMap;
+
+use super::error::Error;
+use super::hasher::{Hash, Hasher};
+use super::merkle_decommitment::MerkleVerifyError;
+use super::utils::hash_root;
+
+/// Verifies many authentication paths of a binary tree hashed with [Hasher::hash_nodes] against
+/// the same root, e.g. the openings of the FRI queries of a layer. Nodes recomputed by earlier
+/// paths are memoized, so every interior node is hashed at most once: a path stops as soon as it
+/// reaches a node that is already known, after checking that both agree.
+///
+/// Errors are reported by [BatchVerifier::finalize].
+pub struct BatchVerifier<H: Hasher> {
+    root: H::Hash,
+    n_layers: usize,
+    n_rows: usize,
+    /// Known nodes, by layer and index. Layer 0 holds the leaf hashes.
+    nodes: BTreeMap<(usize, usize), H::Hash>,
+    n_cache_hits: usize,
+    error: Option<MerkleVerifyError>,
+}
+
+impl<H: Hasher> BatchVerifier<H>
+where
+    H::NativeType: From<u8>,
+{
+    /// Creates a verifier for a tree of `2^n_layers` leaves over a trace of `n_rows` rows,
+    /// committed to by `root`, see [super::merkle_tree::MerkleTree::root].
+    pub fn new(root: H::Hash, n_layers: usize, n_rows: usize) -> Self {
+        Self {
+            root,
+            n_layers,
+            n_rows,
+            nodes: BTreeMap::new(),
+            n_cache_hits: 0,
+            error: None,
+        }
+    }
+
+    /// Adds the path from the leaf at `index` with hash `leaf_hash`, given the siblings along the
+    /// path, bottom first.
+    pub fn add_opening(&mut self, index: usize, leaf_hash: H::Hash, siblings: &[H::Hash]) {
+        if self.error.is_some() {
+            return;
+        }
+        let in_range = u32::try_from(self.n_layers)
+            .ok()
+            .and_then(|n_layers| index.checked_shr(n_layers))
+            .map_or(true, |high_bits| high_bits == 0);
+        if !in_range {
+            self.error = Some(MerkleVerifyError::QueryOutOfRange { query: index });
+            return;
+        }
+        self.error = match siblings.len().cmp(&self.n_layers) {
+            core::cmp::Ordering::Less => Some(MerkleVerifyError::MissingLayers),
+            core::cmp::Ordering::Greater => Some(MerkleVerifyError::UnusedWitness {
+                layer: self.n_layers,
+            }),
+            core::cmp::Ordering::Equal => self.walk_path(index, leaf_hash, siblings).err(),
+        };
+    }
+
+    /// Walks up the path until a known node, memoizing the nodes and siblings on the way.
+    fn walk_path(
+        &mut self,
+        mut index: usize,
+        mut node: H::Hash,
+        siblings: &[H::Hash],
+    ) -> Result<(), MerkleVerifyError> {
+        for (layer, sibling) in siblings.iter().enumerate() {
+            if self.insert_node(layer, index, node)? {
+                self.n_cache_hits += 1;
+                return Ok(());
+            }
+            self.insert_node(layer, index ^ 1, *sibling)?;
+            node = match index & 1 {
+                0 => H::hash_nodes(&node, sibling),
+                _ => H::hash_nodes(sibling, &node),
+            };
+            index >>= 1;
+        }
+        if self.insert_node(self.n_layers, 0, node)? {
+            self.n_cache_hits += 1;
+        }
+        Ok(())
+    }
+
+    /// Records a node, returning whether it was already known.
+    fn insert_node(
+        &mut self,
+        layer: usize,
+        index: usize,
+        hash: H::Hash,
+    ) -> Result<bool, MerkleVerifyError> {
+        match self.nodes.insert((layer, index), hash) {
+            Some(known) if !bool::from(known.ct_eq(&hash)) => {
+                Err(MerkleVerifyError::NodeMismatch { layer, index })
+            }
+            known => Ok(known.is_some()),
+        }
+    }
+
+    /// Number of paths that stopped at an already known node.
+    pub fn n_cache_hits(&self) -> usize {
+        self.n_cache_hits
+    }
+
+    /// Checks that all the added paths were consistent and lead to the root. Succeeds if no path
+    /// was added.
+    pub fn finalize(self) -> Result<(), Error> {
+        if let Some(error) = self.error {
+            return Err(error.into());
+        }
+        match self.nodes.get(&(self.n_layers, 0)) {
+            Some(top_node)
+                if !bool::from(hash_root::<H>(top_node, self.n_rows).ct_eq(&self.root)) =>
+            {
+                Err(MerkleVerifyError::RootMismatch.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BatchVerifier;
+    use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+    use crate::commitment_scheme::error::Error;
+    use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::merkle_decommitment::MerkleVerifyError;
+    use crate::commitment_scheme::merkle_tree::MerkleTree;
+    use crate::core::fields::m31::M31;
+
+    fn path(layers: &[&[Blake2sHash]], index: usize) -> Vec<Blake2sHash> {
+        layers[..layers.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(layer, hashes)| hashes[(index >> layer) ^ 1])
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_paths_test() {
+        let trace = vec![(0..1 << 10).map(M31::from_u32_unchecked).collect()];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let layers = tree.layers().collect::<Vec<_>>();
+        let n_layers = layers.len() - 1;
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), n_layers, 1 << 10);
+
+        // Leaf 1 is the sibling of leaf 0, and leaf 2 meets their path one layer up.
+        for index in [0, 1, 2, 7] {
+            verifier.add_opening(index, layers[0][index], &path(&layers, index));
+        }
+
+        assert_eq!(verifier.n_cache_hits(), 3);
+        assert_eq!(verifier.finalize(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_opening_test() {
+        let trace = vec![(0..1 << 10).map(M31::from_u32_unchecked).collect()];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let layers = tree.layers().collect::<Vec<_>>();
+        let n_layers = layers.len() - 1;
+        let fake_leaf = Blake2sHasher::hash(b"fake");
+
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), n_layers, 1 << 10);
+        verifier.add_opening(0, layers[0][0], &path(&layers, 0));
+        verifier.add_opening(1, fake_leaf, &path(&layers, 1));
+        assert_eq!(
+            verifier.finalize(),
+            Err(Error::VerificationFailed(MerkleVerifyError::NodeMismatch {
+                layer: 0,
+                index: 1
+            }))
+        );
+
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), n_layers, 1 << 10);
+        verifier.add_opening(5, fake_leaf, &path(&layers, 5));
+        assert_eq!(
+            verifier.finalize(),
+            Err(Error::VerificationFailed(MerkleVerifyError::RootMismatch))
+        );
+
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), n_layers, 1 << 10);
+        verifier.add_opening(5, layers[0][5], &path(&layers, 5)[1..]);
+        assert_eq!(
+            verifier.finalize(),
+            Err(Error::VerificationFailed(MerkleVerifyError::MissingLayers))
+        );
+
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), n_layers, 1 << 10);
+        let mut long_path = path(&layers, 5);
+        long_path.push(fake_leaf);
+        verifier.add_opening(5, layers[0][5], &long_path);
+        assert_eq!(
+            verifier.finalize(),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::UnusedWitness { layer: n_layers }
+            ))
+        );
+
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), n_layers, 1 << 10);
+        verifier.add_opening(1 << n_layers, layers[0][5], &path(&layers, 5));
+        assert_eq!(
+            verifier.finalize(),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::QueryOutOfRange {
+                    query: 1 << n_layers
+                }
+            ))
+        );
+
+        // A claimed depth beyond the width of the index must not overflow the range check.
+        let mut verifier = BatchVerifier::<Blake2sHasher>::new(tree.root(), 100, 1 << 10);
+        verifier.add_opening(usize::MAX, fake_leaf, &[fake_leaf; 100]);
+        assert_eq!(
+            verifier.finalize(),
+            Err(Error::VerificationFailed(MerkleVerifyError::RootMismatch))
+        );
+    }
+}
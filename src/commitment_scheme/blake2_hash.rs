@@ -1,100 +1,648 @@
-use std::fmt;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::io;
 
-use blake2::digest::{Update, VariableOutput};
-use blake2::{Blake2s256, Blake2sVar, Digest};
+use subtle::{Choice, ConditionallySelectable};
 
-// Wrapper for the blake2s hash type.
-#[derive(Clone, Copy, PartialEq, Default, Eq)]
-pub struct Blake2sHash([u8; 32]);
+use super::error;
+use super::hasher::{write_output, HashConversionError};
+use super::serialize::Endianness;
+use crate::core::fields::m31::BaseField;
+use crate::core::fields::qm31::SecureField;
 
-impl From<Blake2sHash> for Vec<u8> {
-    fn from(value: Blake2sHash) -> Self {
+// Wrapper for the blake2s hash type, with an output of `N` bytes.
+// Hashes are ordered lexicographically by their bytes, i.e. as big-endian numbers.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Blake2sHashN<const N: usize>([u8; N]);
+
+/// The standard, 32 byte, blake2s hash.
+pub type Blake2sHash = Blake2sHashN<32>;
+
+impl<const N: usize> Default for Blake2sHashN<N> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a Blake2sHashN<N> {
+    type Item = u8;
+    type IntoIter = core::iter::Copied<core::slice::Iter<'a, u8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().copied()
+    }
+}
+
+impl<const N: usize> From<Blake2sHashN<N>> for Vec<u8> {
+    fn from(value: Blake2sHashN<N>) -> Self {
         Vec::from(value.0)
     }
 }
 
-impl From<Vec<u8>> for Blake2sHash {
-    fn from(value: Vec<u8>) -> Self {
-        Self(
-            value
-                .try_into()
-                .expect("Failed converting Vec<u8> to Blake2Hash type"),
-        )
+impl<const N: usize> Blake2sHashN<N> {
+    /// The all-zero hash value, e.g. for padding. This is not the hash of the empty input, see
+    /// [super::hasher::Hasher::empty_hash].
+    pub const ZERO: Self = Self([0; N]);
+
+    /// Borrows the bytes of the hash, keeping their length in the type.
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, u8> {
+        self.0.iter()
+    }
+
+    /// XORs the bytes of two hashes, e.g. to aggregate several roots into a single digest. The
+    /// aggregate doesn't depend on the order of the hashes, and equal hashes cancel out.
+    pub fn xor(&self, other: &Self) -> Self {
+        Self(core::array::from_fn(|i| self.0[i] ^ other.0[i]))
+    }
+
+    /// Interprets `value` as a hash, failing if its length is not `N`. Same as the
+    /// `TryFrom<&[u8]>` conversion.
+    pub fn try_from_bytes(value: &[u8]) -> Result<Self, error::Error> {
+        Ok(Self(value.try_into().map_err(|_| HashConversionError {
+            expected: N,
+            actual: value.len(),
+        })?))
+    }
+
+    /// Parses a hash from its hex representation, as printed by `Display`, optionally prefixed
+    /// with `0x` or `0X`, as passed by many CLIs and JSON-RPC APIs. Both lowercase and uppercase
+    /// digits are accepted. The length is that of the digits, without the prefix, and invalid
+    /// characters are reported at their index in `s`.
+    pub fn from_hex(s: &str) -> Result<Self, error::Error> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        if digits.len() != 2 * N {
+            return Err(HexError::InvalidLength {
+                expected: 2 * N,
+                actual: digits.len(),
+            }
+            .into());
+        }
+        let prefix_len = s.len() - digits.len();
+        let mut bytes = [0; N];
+        hex::decode_to_slice(digits, &mut bytes).map_err(|err| match err {
+            hex::FromHexError::InvalidHexCharacter { c, index } => HexError::InvalidCharacter {
+                c,
+                index: prefix_len + index,
+            },
+            hex::FromHexError::OddLength | hex::FromHexError::InvalidStringLength => {
+                unreachable!("The length was checked above.")
+            }
+        })?;
+        Ok(Self(bytes))
     }
 }
 
-impl From<&[u8]> for Blake2sHash {
-    fn from(value: &[u8]) -> Self {
-        Self(
-            value
-                .try_into()
-                .expect("Failed converting &[u8] to Blake2sHash Type!"),
-        )
+impl Blake2sHash {
+    /// Splits the hash into 8 [BaseField] elements, each the reduction of a 4-byte little-endian
+    /// chunk modulo P. The reduction is slightly biased, as 2^32 = 2P + 2: every element has two
+    /// preimages, except 0 and 1 which have three. Use rejection sampling where that's not
+    /// acceptable, see
+    /// [crate::core::channel::HashChannel::draw_base_felts].
+    pub fn to_m31_array(&self) -> [BaseField; 8] {
+        core::array::from_fn(|i| {
+            let chunk = u32::from_le_bytes(self.0[4 * i..4 * (i + 1)].try_into().unwrap());
+            BaseField::reduce(chunk as u64)
+        })
+    }
+
+    /// Returns the [SecureField] element of the first 4 elements of [Self::to_m31_array], i.e. of
+    /// the first 16 bytes of the hash.
+    pub fn to_qm31(&self) -> SecureField {
+        let felts = self.to_m31_array();
+        SecureField::from_m31_array(felts[..4].try_into().unwrap())
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for Blake2sHashN<N> {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Slices of a length other than `N` are unequal to any hash.
+impl<const N: usize> PartialEq<&[u8]> for Blake2sHashN<N> {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.0[..] == **other
+    }
+}
+
+impl<const N: usize> FromStr for Blake2sHashN<N> {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for Blake2sHashN<N> {
+    type Error = error::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_hex(s)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    InvalidLength { expected: usize, actual: usize },
+    InvalidCharacter { c: char, index: usize },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength { expected, actual } => write!(
+                f,
+                "Expected a hex string of {expected} characters, got {actual}."
+            ),
+            Self::InvalidCharacter { c, index } => {
+                write!(f, "Invalid hex character {c:?} at index {index}.")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+impl<const N: usize> TryFrom<&[u8]> for Blake2sHashN<N> {
+    type Error = error::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(value)
+    }
+}
+
+impl<const N: usize> TryFrom<Vec<u8>> for Blake2sHashN<N> {
+    type Error = error::Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(&value)
+    }
+}
+
+/// Interprets the little-endian bytes of `N / 4` [BaseField] elements as a hash.
+impl<const N: usize> TryFrom<&[BaseField]> for Blake2sHashN<N> {
+    type Error = error::Error;
+
+    fn try_from(value: &[BaseField]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(&Endianness::Little.encode_felts::<_, u8>(value))
+    }
+}
+
+/// Interprets the little-endian bytes of `N / 16` [SecureField] elements as a hash.
+impl<const N: usize> TryFrom<&[SecureField]> for Blake2sHashN<N> {
+    type Error = error::Error;
+
+    fn try_from(value: &[SecureField]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(&Endianness::Little.encode_felts::<_, u8>(value))
     }
 }
 
-impl AsRef<[u8]> for Blake2sHash {
+impl<const N: usize> AsRef<[u8]> for Blake2sHashN<N> {
     fn as_ref(&self) -> &[u8] {
         &self.0
     }
 }
 
-impl From<Blake2sHash> for [u8; 32] {
-    fn from(val: Blake2sHash) -> Self {
+/// Allows writing a hash in place, e.g. as a [super::hasher::Hasher::Digest] buffer.
+impl<const N: usize> AsMut<[u8]> for Blake2sHashN<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> From<Blake2sHashN<N>> for [u8; N] {
+    fn from(val: Blake2sHashN<N>) -> Self {
         val.0
     }
 }
 
-impl fmt::Display for Blake2sHash {
+impl<const N: usize> From<[u8; N]> for Blake2sHashN<N> {
+    fn from(value: [u8; N]) -> Self {
+        Self(value)
+    }
+}
+
+impl<const N: usize> fmt::Display for Blake2sHashN<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&hex::encode(self.0))
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
     }
 }
 
-impl fmt::Debug for Blake2sHash {
+impl<const N: usize> fmt::Debug for Blake2sHashN<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        <Blake2sHash as fmt::Display>::fmt(self, f)
+        <Blake2sHashN<N> as fmt::Display>::fmt(self, f)
+    }
+}
+
+/// Algorithm ids of the blake2s hashes by output size, `BLAKE2s-<bits>` for an output of
+/// `N = bits / 8` bytes.
+const ALGORITHM_IDS: [&str; 32] = [
+    "BLAKE2s-8",
+    "BLAKE2s-16",
+    "BLAKE2s-24",
+    "BLAKE2s-32",
+    "BLAKE2s-40",
+    "BLAKE2s-48",
+    "BLAKE2s-56",
+    "BLAKE2s-64",
+    "BLAKE2s-72",
+    "BLAKE2s-80",
+    "BLAKE2s-88",
+    "BLAKE2s-96",
+    "BLAKE2s-104",
+    "BLAKE2s-112",
+    "BLAKE2s-120",
+    "BLAKE2s-128",
+    "BLAKE2s-136",
+    "BLAKE2s-144",
+    "BLAKE2s-152",
+    "BLAKE2s-160",
+    "BLAKE2s-168",
+    "BLAKE2s-176",
+    "BLAKE2s-184",
+    "BLAKE2s-192",
+    "BLAKE2s-200",
+    "BLAKE2s-208",
+    "BLAKE2s-216",
+    "BLAKE2s-224",
+    "BLAKE2s-232",
+    "BLAKE2s-240",
+    "BLAKE2s-248",
+    "BLAKE2s-256",
+];
+
+impl<const N: usize> super::hasher::Name for Blake2sHashN<N> {
+    const NAME: Cow<'static, str> = Cow::Borrowed("BLAKE2");
+    // Fails to compile for an `N` outside [1, 32].
+    const ALGORITHM_ID: Cow<'static, str> = Cow::Borrowed(ALGORITHM_IDS[N - 1]);
+}
+
+/// As the hash is [Copy], it can't be wiped on drop. Call `zeroize` explicitly on hashes of
+/// sensitive data once they are no longer needed.
+#[cfg(feature = "zeroize")]
+impl<const N: usize> zeroize::Zeroize for Blake2sHashN<N> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, const N: usize> arbitrary::Arbitrary<'a> for Blake2sHashN<N> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.arbitrary()?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[u8; N] as arbitrary::Arbitrary<'a>>::size_hint(depth)
+    }
+}
+
+impl<const N: usize> super::hasher::Hash<u8> for Blake2sHashN<N> {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        subtle::ConstantTimeEq::ct_eq(&self.0[..], &other.0[..])
+    }
+}
+
+impl<const N: usize> ConditionallySelectable for Blake2sHashN<N> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut selected = *a;
+        selected
+            .0
+            .iter_mut()
+            .zip(&b.0)
+            .for_each(|(byte, b_byte)| byte.conditional_assign(b_byte, choice));
+        selected
+    }
+}
+
+/// Swaps `a` and `b` if `swap` is set, without branching on it. Used to order the children of a
+/// node when the side of the path is secret, see [super::utils::verify_path_constant_time].
+pub fn conditionally_swap(a: &mut Blake2sHash, b: &mut Blake2sHash, swap: Choice) {
+    Blake2sHash::conditional_swap(a, b, swap);
+}
+
+/// Serializes as a lowercase hex string in human-readable formats, and as raw bytes otherwise.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Blake2sHashN<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_string())
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Blake2sHashN<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Blake2sHashVisitor::<N>)
+        } else {
+            deserializer.deserialize_bytes(Blake2sHashVisitor::<N>)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct Blake2sHashVisitor<const N: usize>;
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::de::Visitor<'de> for Blake2sHashVisitor<N> {
+    type Value = Blake2sHashN<N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{N} bytes, or a hex string of {N} bytes")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Blake2sHashN::from_hex(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Blake2sHashN::try_from_bytes(v).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    /// Reads exactly `N` bytes, rejecting shorter and longer sequences without buffering them.
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = [0; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(serde::de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(Blake2sHashN(bytes))
     }
 }
 
-impl super::hasher::Name for Blake2sHash {
-    const NAME: std::borrow::Cow<'static, str> = std::borrow::Cow::Borrowed("BLAKE2");
+/// Maximal key size of a keyed blake2s hasher, see [Blake2sHasherN::new_keyed].
+pub const MAX_KEY_SIZE: usize = blake2s_simd::KEYBYTES;
+
+/// Wrapper for the blake2s Hashing functionalities, with an output of `N` bytes.
+///
+/// `N` must be a valid blake2s output size, i.e. in the range [1, 32], which is checked at compile
+/// time:
+/// ```compile_fail
+/// use stwo::commitment_scheme::blake2_hash::Blake2sHasherN;
+/// use stwo::commitment_scheme::hasher::Hasher;
+///
+/// Blake2sHasherN::<33>::hash(b"a");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Blake2sHasherN<const N: usize> {
+    state: blake2s_simd::State,
+    /// The state before any input was fed, restored on reset. Holds the key of keyed hashers.
+    initial_state: blake2s_simd::State,
+    /// The input fed since creation or the last reset, only recorded by hashers built with
+    /// [Blake2sHasherN::new_resumable].
+    recorded_input: Option<Vec<u8>>,
 }
 
-impl super::hasher::Hash<u8> for Blake2sHash {}
+/// The standard, 32 byte, blake2s hasher.
+pub type Blake2sHasher = Blake2sHasherN<32>;
 
-// Wrapper for the blake2s Hashing functionalities.
+/// The hashing progress of a [Blake2sHasherN], see [Blake2sHasherN::save_state].
+///
+/// With the `serde` feature, checkpoints of hashers built with [Blake2sHasherN::new_resumable] can
+/// be serialized, e.g. to resume hashing in another process. As `blake2s_simd` doesn't expose the
+/// internals of its state, they are serialized as the input fed so far, which is fed again on
+/// deserialization. Serializing the checkpoint of any other hasher fails.
 #[derive(Clone, Debug)]
-pub struct Blake2sHasher {
-    state: Blake2s256,
+pub struct HasherCheckpoint<const N: usize>(Blake2sHasherN<N>);
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for HasherCheckpoint<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let recorded_input = self.0.recorded_input.as_ref().ok_or_else(|| {
+            serde::ser::Error::custom("Only checkpoints of resumable hashers can be serialized.")
+        })?;
+        serializer.serialize_bytes(recorded_input)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for HasherCheckpoint<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let recorded_input = <Vec<u8> as serde::Deserialize>::deserialize(deserializer)?;
+        let mut hasher = Blake2sHasherN::new_resumable();
+        hasher.absorb(&recorded_input);
+        Ok(HasherCheckpoint(hasher))
+    }
+}
+
+/// Equal to [super::hasher::Hasher::new], for generic code bounded by `Default`.
+impl<const N: usize> Default for Blake2sHasherN<N> {
+    fn default() -> Self {
+        <Self as super::hasher::Hasher>::new()
+    }
+}
+
+impl<const N: usize> Blake2sHasherN<N> {
+    /// Constructs a keyed (MAC mode) hasher, e.g. for domain separation between independent
+    /// commitments. Other than the key, it behaves exactly like [super::hasher::Hasher::new]; in
+    /// particular `reset` restores the keyed state.
+    ///
+    /// With the `zeroize` feature, the key is wiped along with the rest of the state on drop or
+    /// `zeroize`. The caller's copy of `key` is left to the caller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is longer than [MAX_KEY_SIZE] bytes.
+    pub fn new_keyed(key: &[u8]) -> Self {
+        assert!(
+            key.len() <= MAX_KEY_SIZE,
+            "Blake2s keys are at most {MAX_KEY_SIZE} bytes, got {}.",
+            key.len()
+        );
+        Self::from_initial_state(Self::params().key(key).to_state())
+    }
+
+    /// Constructs a hasher with the Blake2s personalization parameter set to `personal`, e.g. to
+    /// separate the trees of a protocol without keying them. Hashers of different personalizations
+    /// are independent functions, and an all-zero personalization is the default hasher. `reset`
+    /// keeps the personalization.
+    pub fn new_personalized(personal: &[u8; 8]) -> Self {
+        Self::from_initial_state(Self::params().personal(personal).to_state())
+    }
+
+    /// Constructs a hasher like [super::hasher::Hasher::new] that records its input, so that its
+    /// checkpoints can be serialized, see [HasherCheckpoint]. The input is buffered until the
+    /// hasher is reset or finalized.
+    pub fn new_resumable() -> Self {
+        let mut hasher = <Self as super::hasher::Hasher>::new();
+        hasher.recorded_input = Some(Vec::new());
+        hasher
+    }
+
+    /// Snapshots the hashing progress, to later resume it with [Self::restore_state].
+    pub fn save_state(&self) -> HasherCheckpoint<N> {
+        HasherCheckpoint(self.clone())
+    }
+
+    /// Resumes hashing from a checkpoint taken by [Self::save_state].
+    pub fn restore_state(checkpoint: HasherCheckpoint<N>) -> Self {
+        checkpoint.0
+    }
+
+    /// Number of bytes fed since creation or the last reset, excluding the key. A debugging aid,
+    /// e.g. to compare with another implementation of a transcript.
+    pub fn bytes_fed(&self) -> u64 {
+        self.state.count()
+    }
+
+    /// Feeds whole blocks of [super::hasher::Hasher::BLOCK_SIZE] bytes, e.g. rows of a trace
+    /// serialized to a block each. Equivalent to `update` on the concatenated bytes.
+    pub fn update_blocks(&mut self, blocks: &[[u8; 64]]) {
+        self.absorb(bytemuck::cast_slice(blocks));
+    }
+
+    /// Feeds the little-endian bytes of the given elements, see [Endianness::encode_felts],
+    /// without an intermediate allocation on little-endian targets.
+    pub fn update_felts(&mut self, felts: &[BaseField]) {
+        self.absorb(&Endianness::Little.encode_felts(felts));
+    }
+
+    /// Feeds every element as its four [BaseField] limbs `[a, b, c, d]` of `(a + bi) + (c + di)u`,
+    /// see [SecureField::to_m31_array], each in little-endian bytes. This is the same encoding
+    /// used by the channel's `mix_felts`.
+    pub fn update_qm31(&mut self, felts: &[SecureField]) {
+        self.absorb(&Endianness::Little.encode_felts(felts));
+    }
+
+    /// Hashes the given elements, encoded as in [Self::update_qm31].
+    pub fn hash_qm31(felts: &[SecureField]) -> Blake2sHashN<N> {
+        let mut hasher = <Self as super::hasher::Hasher>::new();
+        hasher.update_qm31(felts);
+        super::hasher::Hasher::finalize(hasher)
+    }
+
+    /// The parameters of an unkeyed hasher. `hash_many_in_place` constructs a state from them
+    /// once and clones it for every input.
+    ///
+    /// Every constructor goes through here, so that an invalid `N` fails to compile.
+    fn params() -> blake2s_simd::Params {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_OUTPUT_SIZE;
+        let mut params = blake2s_simd::Params::new();
+        params.hash_length(N);
+        params
+    }
+
+    /// Evaluated at compile time for every `N` a hasher is constructed for.
+    const VALID_OUTPUT_SIZE: () = assert!(1 <= N && N <= 32, "Invalid blake2s output size.");
+
+    fn from_initial_state(initial_state: blake2s_simd::State) -> Self {
+        Self {
+            state: initial_state.clone(),
+            initial_state,
+            recorded_input: None,
+        }
+    }
+
+    fn absorb(&mut self, data: &[u8]) {
+        self.state.update(data);
+        if let Some(recorded_input) = &mut self.recorded_input {
+            recorded_input.extend_from_slice(data);
+        }
+    }
+
+    /// Forgets the recorded input, wiping it with the `zeroize` feature.
+    fn clear_recorded_input(&mut self) {
+        if let Some(recorded_input) = &mut self.recorded_input {
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(recorded_input);
+            #[cfg(not(feature = "zeroize"))]
+            recorded_input.clear();
+        }
+    }
+
+    fn finalize_state(state: &blake2s_simd::State) -> Blake2sHashN<N> {
+        Blake2sHashN(
+            state
+                .finalize()
+                .as_bytes()
+                .try_into()
+                .expect("The state outputs N bytes."),
+        )
+    }
+}
+
+/// Wipes the fed state, the key and the recorded input, leaving a hasher equal to
+/// [super::hasher::Hasher::new]. Hashers are also wiped on drop, and `reset` wipes the recorded
+/// input. Copies of the input left behind by the recording buffer growing are not wiped; reserve
+/// its capacity upfront when that matters.
+#[cfg(feature = "zeroize")]
+impl<const N: usize> zeroize::Zeroize for Blake2sHasherN<N> {
+    fn zeroize(&mut self) {
+        let fresh_state = Self::params().to_state();
+        // SAFETY: The states are valid for writes and hold no heap data, so overwriting them
+        // without dropping leaks nothing. Volatile, so that the wipe isn't optimized away.
+        unsafe {
+            core::ptr::write_volatile(&mut self.state, fresh_state.clone());
+            core::ptr::write_volatile(&mut self.initial_state, fresh_state);
+        }
+        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        if let Some(recorded_input) = &mut self.recorded_input {
+            recorded_input.zeroize();
+        }
+    }
 }
 
-impl super::hasher::Hasher for Blake2sHasher {
-    type Hash = Blake2sHash;
+#[cfg(feature = "zeroize")]
+impl<const N: usize> Drop for Blake2sHasherN<N> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const N: usize> zeroize::ZeroizeOnDrop for Blake2sHasherN<N> {}
+
+impl<const N: usize> super::hasher::Hasher for Blake2sHasherN<N> {
+    type Hash = Blake2sHashN<N>;
     const BLOCK_SIZE: usize = 64;
-    const OUTPUT_SIZE: usize = 32;
+    const OUTPUT_SIZE: usize = N;
     type NativeType = u8;
+    // `[u8; N]` doesn't implement `Default` for a generic `N`.
+    type Digest = Blake2sHashN<N>;
 
     fn new() -> Self {
-        Self {
-            state: Blake2s256::new(),
-        }
+        Self::from_initial_state(Self::params().to_state())
     }
 
     fn reset(&mut self) {
-        blake2::Digest::reset(&mut self.state);
+        self.state = self.initial_state.clone();
+        self.clear_recorded_input();
     }
 
     fn update(&mut self, data: &[u8]) {
-        blake2::Digest::update(&mut self.state, data);
+        self.absorb(data);
     }
 
-    fn finalize(self) -> Blake2sHash {
-        Blake2sHash(self.state.finalize().into())
+    fn finalize(self) -> Blake2sHashN<N> {
+        Self::finalize_state(&self.state)
     }
 
-    fn finalize_reset(&mut self) -> Blake2sHash {
-        Blake2sHash(self.state.finalize_reset().into())
+    fn finalize_reset(&mut self) -> Blake2sHashN<N> {
+        self.clear_recorded_input();
+        let hash = Self::finalize_state(&self.state);
+        self.state = self.initial_state.clone();
+        hash
     }
 
     unsafe fn hash_many_in_place(
@@ -102,25 +650,92 @@ impl super::hasher::Hasher for Blake2sHasher {
         single_input_length_bytes: usize,
         dst: &[*mut u8],
     ) {
+        // Catch misuse before dereferencing, at no cost in release builds.
+        debug_assert_eq!(
+            data.len(),
+            dst.len(),
+            "Number of inputs and outputs don't match!"
+        );
+        debug_assert!(
+            data.iter().all(|src| !src.is_null()) && dst.iter().all(|dst| !dst.is_null()),
+            "Null input or output pointer!"
+        );
+        let initial_state = Self::params().to_state();
+        let hash_single = |src: *const u8, dst: *mut u8| {
+            let input = core::slice::from_raw_parts(src, single_input_length_bytes);
+            let mut state = initial_state.clone();
+            state.update(input);
+            write_output(state.finalize().as_bytes(), dst);
+        };
+
+        #[cfg(not(feature = "parallel"))]
         data.iter()
-            .map(|p| std::slice::from_raw_parts(*p, single_input_length_bytes))
-            .zip(
-                dst.iter()
-                    .map(|p| std::slice::from_raw_parts_mut(*p, Self::OUTPUT_SIZE)),
-            )
-            .for_each(|(input, out)| {
-                let mut hasher = Blake2sVar::new(Self::OUTPUT_SIZE).unwrap();
-                hasher.update(input);
-                hasher.finalize_variable(out).unwrap();
-            })
+            .zip(dst)
+            .for_each(|(src, dst)| hash_single(*src, *dst));
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            use super::utils::SyncPointers;
+
+            // Safe because every output is written to its own (distinct) destination.
+            let (data, dst) = (SyncPointers(data), SyncPointers(dst));
+            (0..data.len().min(dst.len()))
+                .into_par_iter()
+                .for_each(|i| hash_single(data.get(i), dst.get(i)));
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Blake2sHasher {
+    /// Equivalent to [super::hasher::Hasher::hash], reusing a hasher state of the current thread
+    /// instead of initializing a new one per call, see also [super::hasher_pool::HasherPool].
+    pub fn hash_with_tls(data: &[u8]) -> Blake2sHash {
+        use super::hasher::Hasher;
+
+        thread_local! {
+            static HASHER: std::cell::RefCell<Blake2sHasher> =
+                std::cell::RefCell::new(Blake2sHasher::new());
+        }
+        HASHER.with(|hasher| {
+            let mut hasher = hasher.borrow_mut();
+            hasher.update(data);
+            hasher.finalize_reset()
+        })
+    }
+}
+
+/// Allows using the hasher as an [io::Write] sink, equivalent to calling `update` on the written
+/// data.
+#[cfg(feature = "std")]
+impl<const N: usize> io::Write for Blake2sHasherN<N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        super::hasher::Hasher::update(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Blake2sHasher;
+    use std::collections::{BTreeSet, HashMap};
+
+    use itertools::Itertools;
+
+    use super::{Blake2sHash, Blake2sHashN, Blake2sHasher, Blake2sHasherN, HexError};
     use crate::commitment_scheme::blake2_hash;
-    use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::error::Error;
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
+    use crate::commitment_scheme::hasher::{Hash, Hasher};
+    use crate::commitment_scheme::utils::{verify_path_constant_time, verify_sorted_path};
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::IntoSlice;
+    use crate::{m31, qm31};
 
     #[test]
     fn single_hash_test() {
@@ -145,14 +760,1017 @@ mod tests {
     }
 
     #[test]
-    fn hash_state_test() {
-        let mut state = Blake2sHasher::new();
-        state.update(b"a");
-        state.update(b"b");
-        let hash = state.finalize_reset();
-        let hash_empty = state.finalize();
+    fn keyed_hash_test() {
+        let key = (0..32).collect::<Vec<u8>>();
+        let mut hasher = Blake2sHasher::new_keyed(&key);
+        hasher.update(b"a");
+        let keyed_hash = hasher.finalize_reset();
+        hasher.update(b"a");
 
-        assert_eq!(hash.to_string(), Blake2sHasher::hash(b"ab").to_string());
-        assert_eq!(hash_empty.to_string(), Blake2sHasher::hash(b"").to_string());
+        assert_ne!(keyed_hash, Blake2sHasher::hash(b"a"));
+        assert_eq!(hasher.finalize(), keyed_hash);
+        // First keyed test vector of the blake2s reference implementation.
+        assert_eq!(
+            Blake2sHasher::new_keyed(&key).finalize().to_string(),
+            "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49"
+        );
+    }
+
+    #[test]
+    fn personalized_hash_test() {
+        let mut trace_hasher = Blake2sHasher::new_personalized(b"trace\0\0\0");
+        let mut fri_hasher = Blake2sHasher::new_personalized(b"fri\0\0\0\0\0");
+        trace_hasher.update(b"a");
+        fri_hasher.update(b"a");
+        let trace_hash = trace_hasher.finalize_reset();
+        trace_hasher.update(b"a");
+
+        assert_ne!(trace_hash, fri_hasher.finalize());
+        assert_ne!(trace_hash, Blake2sHasher::hash(b"a"));
+        assert_eq!(trace_hasher.finalize(), trace_hash);
+        assert_eq!(
+            Blake2sHasher::new_personalized(&[0; 8])
+                .chain(b"a")
+                .finalize(),
+            Blake2sHasher::hash(b"a")
+        );
+    }
+
+    #[test]
+    fn keyed_hash_matches_blake2s_mac_test() {
+        use blake2::digest::Mac;
+
+        let mut mac = blake2::Blake2sMac256::new_from_slice(b"key").unwrap();
+        mac.update(b"data");
+        let mut hasher = Blake2sHasher::new_keyed(b"key");
+        hasher.update(b"data");
+
+        assert_eq!(hasher.finalize().as_ref(), &mac.finalize().into_bytes()[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Blake2s keys are at most 32 bytes, got 33.")]
+    fn keyed_hash_long_key_test() {
+        Blake2sHasher::new_keyed(&[0; 33]);
+    }
+
+    #[test]
+    fn truncated_hash_test() {
+        let hash = Blake2sHasherN::<16>::hash(b"a");
+        let expected = blake2s_simd::Params::new().hash_length(16).hash(b"a");
+
+        assert_eq!(hash.as_ref(), expected.as_bytes());
+        assert_eq!(hash.to_string().len(), 32);
+        assert_eq!(
+            Blake2sHashN::<16>::try_from_bytes(&[0; 32]),
+            Err(Error::InvalidHashLength {
+                expected: 16,
+                got: 32
+            })
+        );
+    }
+
+    #[test]
+    fn truncated_hash_many_test() {
+        let inputs: Vec<&[u8]> = vec![b"ab", b"cd", b"ef"];
+
+        let hashes = Blake2sHasherN::<16>::hash_many(&inputs).unwrap();
+
+        inputs
+            .iter()
+            .zip(hashes)
+            .for_each(|(input, hash)| assert_eq!(hash, Blake2sHasherN::<16>::hash(input)));
+    }
+
+    #[test]
+    fn full_size_hash_matches_blake2s256_test() {
+        use blake2::{Blake2s256, Digest};
+
+        let hash = Blake2sHasherN::<32>::hash(b"a");
+
+        assert_eq!(hash.as_ref(), &Blake2s256::digest(b"a")[..]);
+        assert_eq!(hash, Blake2sHasher::hash(b"a"));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_test() {
+        use zeroize::Zeroize;
+
+        let mut hash = Blake2sHasher::hash(b"secret");
+        assert_ne!(hash, Blake2sHash::default());
+
+        hash.zeroize();
+
+        assert_eq!(<[u8; 32]>::from(hash), [0; 32]);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn hasher_zeroize_test() {
+        use zeroize::Zeroize;
+
+        let mut hasher = Blake2sHasher::new_resumable();
+        hasher.update(b"secret");
+        hasher.reset();
+
+        let recorded_input = hasher.recorded_input.as_ref().unwrap();
+        assert!(recorded_input.is_empty());
+        // SAFETY: The wipe on reset initialized the whole capacity.
+        let buffer = unsafe {
+            core::slice::from_raw_parts(recorded_input.as_ptr(), recorded_input.capacity())
+        };
+        assert!(!buffer.is_empty());
+        assert!(buffer.iter().all(|&byte| byte == 0));
+
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(b"secret");
+        hasher.zeroize();
+        hasher.update(b"a");
+
+        assert_eq!(hasher.finalize(), Blake2sHasher::hash(b"a"));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn keyed_hasher_zeroize_test() {
+        use zeroize::Zeroize;
+
+        let mut hasher = Blake2sHasher::new_keyed(b"key");
+        hasher.update(b"secret");
+
+        hasher.zeroize();
+        hasher.update(b"a");
+        let hash_after_zeroize = hasher.finalize_reset();
+        hasher.update(b"a");
+
+        // Both the fed state and the key are gone.
+        assert_eq!(hash_after_zeroize, Blake2sHasher::hash(b"a"));
+        assert_eq!(hasher.finalize(), Blake2sHasher::hash(b"a"));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = (0..32).collect::<Vec<u8>>();
+        let mut u = Unstructured::new(&bytes);
+
+        let hash = Blake2sHash::arbitrary(&mut u).unwrap();
+
+        assert_eq!(hash.as_ref(), &bytes[..]);
+        assert!(u.is_empty());
+    }
+
+    #[test]
+    fn to_m31_array_test() {
+        let hash = Blake2sHash::from_hex(
+            "01000000ffffff7fffffffff0000008078563412000000000000000002000000",
+        )
+        .unwrap();
+
+        assert_eq!(
+            hash.to_m31_array(),
+            [1, 0, 1, 1, 0x12345678, 0, 0, 2].map(BaseField::from_u32_unchecked)
+        );
+        assert_eq!(hash.to_qm31(), qm31!(1, 0, 1, 1));
+    }
+
+    #[test]
+    fn from_array_test() {
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8);
+
+        let hash = Blake2sHash::from(bytes);
+
+        assert_eq!(<[u8; 32]>::from(hash), bytes);
+        assert_eq!(hash, Blake2sHash::try_from(&bytes[..]).unwrap());
+    }
+
+    #[test]
+    fn iter_and_xor_test() {
+        let hash = Blake2sHasher::hash(b"abc");
+        let other = Blake2sHasher::hash(b"def");
+
+        assert!(hash.iter().eq(hash.as_ref()));
+        assert_eq!((&hash).into_iter().collect::<Vec<u8>>(), Vec::from(hash));
+        assert_eq!(hash.xor(&hash), Blake2sHash::ZERO);
+        assert_eq!(hash.xor(&Blake2sHash::default()), hash);
+        assert_eq!(hash.xor(&other), other.xor(&hash));
+        assert!(hash
+            .xor(&other)
+            .into_iter()
+            .zip(hash.iter().zip(&other))
+            .all(|(x, (a, b))| x == a ^ b));
+    }
+
+    #[test]
+    fn as_bytes_test() {
+        let hash = Blake2sHasher::hash(b"abc");
+
+        let bytes: &[u8; 32] = hash.as_bytes();
+
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(&bytes[..], hash.as_ref());
+        assert_eq!(Blake2sHashN::<16>::ZERO.as_bytes(), &[0; 16]);
+    }
+
+    #[test]
+    fn checkpoint_test() {
+        let mut hasher = Blake2sHasher::new_keyed(b"key");
+        hasher.update(&[1; 100]);
+        let checkpoint = hasher.save_state();
+        hasher.update(b"more");
+
+        let mut restored = Blake2sHasher::restore_state(checkpoint.clone());
+        restored.update(b"other");
+
+        let mut expected = Blake2sHasher::new_keyed(b"key");
+        expected.update(&[1; 100]);
+        expected.update(b"other");
+        assert_eq!(restored.finalize(), expected.finalize());
+        assert_ne!(
+            Blake2sHasher::restore_state(checkpoint).finalize(),
+            hasher.finalize()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint_serde_test() {
+        let mut hasher = Blake2sHasher::new_resumable();
+        hasher.update(&[1; 100]);
+        let checkpoint = hasher.save_state();
+        hasher.update(b"more");
+
+        for restored in [
+            serde_json::from_str(&serde_json::to_string(&checkpoint).unwrap()).unwrap(),
+            bincode::deserialize(&bincode::serialize(&checkpoint).unwrap()).unwrap(),
+        ] {
+            let mut restored = Blake2sHasher::restore_state(restored);
+            restored.update(b"more");
+            assert_eq!(restored.bytes_fed(), 104);
+            assert_eq!(restored.finalize(), hasher.clone().finalize());
+        }
+        assert_eq!(
+            hasher.finalize(),
+            Blake2sHasher::hash(&[&[1; 100][..], b"more"].concat())
+        );
+        assert!(serde_json::to_string(&Blake2sHasher::new().save_state()).is_err());
+
+        // Only the input fed since the last reset is recorded.
+        let mut hasher = Blake2sHasher::new_resumable();
+        hasher.update(b"a");
+        hasher.finalize_reset();
+        hasher.update(b"b");
+        let bytes = bincode::serialize(&hasher.save_state()).unwrap();
+        let restored = Blake2sHasher::restore_state(bincode::deserialize(&bytes).unwrap());
+        assert_eq!(restored.finalize(), Blake2sHasher::hash(b"b"));
+    }
+
+    #[test]
+    fn default_test() {
+        let mut hasher = Blake2sHasher::default();
+        let mut other_hasher = Blake2sHasher::new();
+
+        hasher.update(b"abc");
+        other_hasher.update(b"abc");
+
+        assert_eq!(hasher.finalize(), other_hasher.finalize());
+    }
+
+    #[test]
+    fn dyn_hasher_test() {
+        use crate::commitment_scheme::hasher::DynHasher;
+        use crate::commitment_scheme::sha256_hash::Sha256Hasher;
+
+        let new_hasher = |algorithm_id: &str| -> Option<Box<dyn DynHasher>> {
+            match algorithm_id {
+                "BLAKE2s-256" => Some(Box::new(Blake2sHasher::new())),
+                "SHA256" => Some(Box::new(Sha256Hasher::new())),
+                _ => None,
+            }
+        };
+
+        for (algorithm_id, expected) in [
+            ("BLAKE2s-256", Vec::from(Blake2sHasher::hash(b"ab"))),
+            ("SHA256", Vec::from(Sha256Hasher::hash(b"ab"))),
+        ] {
+            let mut hasher = new_hasher(algorithm_id).unwrap();
+            hasher.update(b"a");
+            hasher.update(b"b");
+
+            assert_eq!(hasher.algorithm_id(), algorithm_id);
+            assert_eq!(hasher.finalize_boxed(), expected);
+        }
+    }
+
+    #[test]
+    fn domain_separation_test() {
+        let left = Blake2sHasher::hash(b"left");
+        let right = Blake2sHasher::hash(b"right");
+        let payload = [left.as_ref(), right.as_ref()].concat();
+
+        let leaf = Blake2sHasher::hash_leaf(&payload);
+        let node = Blake2sHasher::hash_nodes(&left, &right);
+
+        assert_ne!(leaf, node);
+        assert_ne!(leaf, Blake2sHasher::hash(&payload));
+        assert_eq!(node, Blake2sHasher::hash(&[&[1][..], &payload].concat()));
+    }
+
+    #[test]
+    fn compress_test() {
+        let left = Blake2sHasher::hash(b"left");
+        let right = Blake2sHasher::hash(b"right");
+
+        let compressed = Blake2sHasher::compress(&left, &right);
+
+        assert_eq!(
+            compressed,
+            Blake2sHasher::hash(&[left.as_ref(), right.as_ref()].concat())
+        );
+        assert_eq!(compressed, Blake2sHasher::concat_and_hash(&left, &right));
+    }
+
+    #[test]
+    fn compress_four_leaf_tree_test() {
+        let leaves = [b"a", b"b", b"c", b"d"].map(|leaf| Blake2sHasher::hash(leaf));
+        let concat_hash = |l: &Blake2sHash, r: &Blake2sHash| {
+            let mut hasher = Blake2sHasher::new();
+            hasher.update(l.as_ref());
+            hasher.update(r.as_ref());
+            hasher.finalize()
+        };
+
+        let root = Blake2sHasher::compress(
+            &Blake2sHasher::compress(&leaves[0], &leaves[1]),
+            &Blake2sHasher::compress(&leaves[2], &leaves[3]),
+        );
+
+        let expected_root = concat_hash(
+            &concat_hash(&leaves[0], &leaves[1]),
+            &concat_hash(&leaves[2], &leaves[3]),
+        );
+        assert_eq!(root, expected_root);
+    }
+
+    #[test]
+    fn eq_bytes_test() {
+        let hash = Blake2sHasher::hash(b"a");
+        let bytes: [u8; 32] = hash.into();
+
+        assert_eq!(hash, bytes);
+        assert_ne!(hash, [0; 32]);
+        assert_eq!(hash, &bytes[..]);
+        assert_ne!(hash, &[0; 32][..]);
+        assert_ne!(hash, &bytes[..31]);
+        assert_ne!(hash, &[bytes, bytes].concat()[..]);
+    }
+
+    #[test]
+    fn hash_map_key_test() {
+        let hashes = (0..10u8)
+            .map(|i| Blake2sHasher::hash(&[i]))
+            .collect::<Vec<_>>();
+
+        let map = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| (*hash, i))
+            .collect::<HashMap<_, _>>();
+        let set = hashes.iter().copied().collect::<BTreeSet<_>>();
+
+        hashes.iter().enumerate().for_each(|(i, hash)| {
+            assert_eq!(map[hash], i);
+            assert!(set.contains(hash));
+        });
+        assert!(set
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| a.as_ref() < b.as_ref()));
+    }
+
+    #[test]
+    fn ord_test() {
+        let mut small = [0xff; 32];
+        small[0] = 0;
+        let mut large = [0; 32];
+        large[0] = 1;
+
+        assert!(
+            Blake2sHash::try_from(&small[..]).unwrap() < Blake2sHash::try_from(&large[..]).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_hex_test() {
+        let hash = Blake2sHasher::hash(b"a");
+
+        assert_eq!(Blake2sHash::from_hex(&hash.to_string()), Ok(hash));
+        assert_eq!(
+            Blake2sHash::from_hex(&hash.to_string().to_uppercase()),
+            Ok(hash)
+        );
+        assert_eq!(hash.to_string().parse::<Blake2sHash>(), Ok(hash));
+    }
+
+    #[test]
+    fn from_prefixed_hex_test() {
+        let hash = Blake2sHasher::hash(b"a");
+        let digits = hash.to_string();
+
+        assert_eq!(Blake2sHash::try_from(&format!("0x{digits}")[..]), Ok(hash));
+        assert_eq!(
+            Blake2sHash::try_from(&format!("0X{}", digits.to_uppercase())[..]),
+            Ok(hash)
+        );
+        assert_eq!(Blake2sHash::try_from(&digits.to_uppercase()[..]), Ok(hash));
+        assert_eq!(
+            Blake2sHash::try_from(&format!("0x{}", &digits[2..])[..]),
+            Err(Error::InvalidHex(HexError::InvalidLength {
+                expected: 64,
+                actual: 62
+            }))
+        );
+        assert_eq!(
+            Blake2sHash::try_from(&format!("0x{}g", &digits[1..])[..]),
+            Err(Error::InvalidHex(HexError::InvalidCharacter {
+                c: 'g',
+                index: 65
+            }))
+        );
+        // Only a single prefix is stripped.
+        assert!(Blake2sHash::try_from(&format!("0x0x{}", &digits[2..])[..]).is_err());
+    }
+
+    #[test]
+    fn from_hex_invalid_test() {
+        assert_eq!(
+            Blake2sHash::from_hex("abcd"),
+            Err(Error::InvalidHex(HexError::InvalidLength {
+                expected: 64,
+                actual: 4
+            }))
+        );
+        assert_eq!(
+            Blake2sHash::from_hex(&"g".repeat(64)),
+            Err(Error::InvalidHex(HexError::InvalidCharacter {
+                c: 'g',
+                index: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn empty_hash_test() {
+        assert_eq!(Blake2sHasher::empty_hash(), Blake2sHasher::hash(b""));
+        assert_ne!(Blake2sHasher::empty_hash(), Blake2sHash::ZERO);
+        assert_eq!(Blake2sHash::ZERO, Blake2sHash::default());
+    }
+
+    #[test]
+    fn finalize_bytes_test() {
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(b"a");
+
+        let bytes = hasher.clone().finalize_bytes();
+
+        assert_eq!(bytes, Vec::<u8>::from(hasher.finalize()));
+    }
+
+    #[test]
+    fn finalize_into_digest_test() {
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(b"a");
+
+        let digest = hasher.finalize_into_digest();
+
+        assert_eq!(digest.as_ref().len(), Blake2sHasher::OUTPUT_SIZE);
+        assert_eq!(digest, Blake2sHasher::hash(b"a"));
+        assert_eq!(
+            <Blake2sHasherN<16> as Hasher>::Digest::default()
+                .as_ref()
+                .len(),
+            16
+        );
+    }
+
+    #[test]
+    fn finalize_into_test() {
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(b"a");
+        let mut dst = [0xff; 40];
+
+        let n_written = hasher.clone().finalize_into(&mut dst).unwrap();
+
+        assert_eq!(n_written, 32);
+        assert_eq!(&dst[..32], hasher.clone().finalize().as_ref());
+        assert_eq!(&dst[32..], &[0xff; 8]);
+        assert_eq!(
+            hasher.finalize_into(&mut [0; 31]),
+            Err(Error::BufferTooSmall {
+                required: 32,
+                got: 31
+            })
+        );
+    }
+
+    #[test]
+    fn display_test() {
+        let hash = Blake2sHasher::hash(b"a");
+
+        assert_eq!(hash.to_string(), hex::encode(hash.as_ref()));
+        assert_eq!(format!("{hash:?}"), hash.to_string());
+    }
+
+    #[test]
+    fn hasher_name_test() {
+        assert_eq!(Blake2sHasher::NAME, "BLAKE2");
+        assert_eq!(Blake2sHasherN::<20>::NAME, "BLAKE2");
+        assert_eq!(Blake2sHasher::ALGORITHM_ID, "BLAKE2s-256");
+        assert_eq!(Blake2sHasherN::<1>::ALGORITHM_ID, "BLAKE2s-8");
+        assert_eq!(Blake2sHasherN::<20>::ALGORITHM_ID, "BLAKE2s-160");
+    }
+
+    #[test]
+    fn ct_eq_test() {
+        let hash_a = Blake2sHasher::hash(b"a");
+        let hash_b = Blake2sHasher::hash(b"b");
+
+        assert!(bool::from(hash_a.ct_eq(&hash_a)));
+        assert!(!bool::from(hash_a.ct_eq(&hash_b)));
+    }
+
+    #[test]
+    fn try_from_bytes_test() {
+        let hash = Blake2sHasher::hash(b"a");
+
+        assert_eq!(Blake2sHash::try_from_bytes(hash.as_ref()), Ok(hash));
+        assert_eq!(Blake2sHash::try_from(Vec::from(hash)), Ok(hash));
+        for len in [0, 31, 33] {
+            assert_eq!(
+                Blake2sHash::try_from_bytes(&vec![0; len]),
+                Err(Error::InvalidHashLength {
+                    expected: 32,
+                    got: len
+                })
+            );
+            assert!(Blake2sHash::try_from(vec![0; len]).is_err());
+        }
+    }
+
+    #[test]
+    fn hash_many_in_place_many_inputs_test() {
+        const N_INPUTS: usize = 1024;
+        let inputs = (0..N_INPUTS)
+            .map(|i| (i as u32).to_le_bytes())
+            .collect::<Vec<_>>();
+        let input_ptrs = inputs
+            .iter()
+            .map(|input| input.as_ptr())
+            .collect::<Vec<_>>();
+        let mut out = vec![0_u8; N_INPUTS * Blake2sHasher::OUTPUT_SIZE];
+        let out_ptrs = (0..N_INPUTS)
+            .map(|i| unsafe { out.as_mut_ptr().add(i * Blake2sHasher::OUTPUT_SIZE) })
+            .collect::<Vec<_>>();
+
+        unsafe { Blake2sHasher::hash_many_in_place(&input_ptrs, 4, &out_ptrs) };
+
+        inputs
+            .iter()
+            .zip(out.chunks(Blake2sHasher::OUTPUT_SIZE))
+            .for_each(|(input, out)| assert_eq!(Blake2sHasher::hash(input), out));
+    }
+
+    #[test]
+    fn bytes_fed_test() {
+        let mut hasher = Blake2sHasher::new_keyed(b"key");
+        assert_eq!(hasher.bytes_fed(), 0);
+
+        hasher.update(&[0; 3]);
+        hasher.update(&[0; 100]);
+        assert_eq!(hasher.bytes_fed(), 103);
+
+        hasher.reset();
+        assert_eq!(hasher.bytes_fed(), 0);
+    }
+
+    #[test]
+    fn hash_slices_test() {
+        let (a, b) = (&b"a row"[..], &b"of two columns"[..]);
+
+        assert_eq!(
+            Blake2sHasher::hash_slices(&[a, b]),
+            Blake2sHasher::hash(&[a, b].concat())
+        );
+        assert_eq!(
+            Blake2sHasher::hash_slices(&[a, b, b"", b"!"]),
+            Blake2sHasher::hash(b"a rowof two columns!")
+        );
+    }
+
+    #[test]
+    fn chain_test() {
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        hasher.update(b"");
+        let expected = hasher.finalize();
+
+        let mut hasher = Blake2sHasher::new();
+        hasher
+            .chain_update(b"a")
+            .chain_update(b"bc")
+            .chain_update(b"");
+
+        assert_eq!(
+            Blake2sHasher::new()
+                .chain(b"a")
+                .chain(b"bc")
+                .chain(b"")
+                .finalize(),
+            expected
+        );
+        assert_eq!(hasher.finalize(), expected);
+    }
+
+    #[test]
+    fn conditionally_swap_test() {
+        let a = Blake2sHasher::hash(b"a");
+        let b = Blake2sHasher::hash(b"b");
+
+        for swap in [false, true] {
+            let (mut x, mut y) = (a, b);
+            blake2_hash::conditionally_swap(&mut x, &mut y, (swap as u8).into());
+
+            assert_eq!((x, y), if swap { (b, a) } else { (a, b) });
+        }
+    }
+
+    #[test]
+    fn verify_path_constant_time_test() {
+        let leaves = (0..4_u8)
+            .map(|i| Blake2sHasher::hash_leaf(&[i]))
+            .collect::<Vec<_>>();
+        let left = Blake2sHasher::hash_nodes(&leaves[0], &leaves[1]);
+        let right = Blake2sHasher::hash_nodes(&leaves[2], &leaves[3]);
+        let root = Blake2sHasher::hash_nodes(&left, &right);
+
+        assert!(verify_path_constant_time::<Blake2sHasher>(
+            &leaves[1],
+            1,
+            &[leaves[0], right],
+            &root
+        ));
+        assert!(verify_path_constant_time::<Blake2sHasher>(
+            &leaves[2],
+            2,
+            &[leaves[3], left],
+            &root
+        ));
+        assert!(!verify_path_constant_time::<Blake2sHasher>(
+            &leaves[2],
+            3,
+            &[leaves[3], left],
+            &root
+        ));
+        // Bits of the index above the height of the path must be rejected, not ignored.
+        assert!(!verify_path_constant_time::<Blake2sHasher>(
+            &leaves[2],
+            2 | 4,
+            &[leaves[3], left],
+            &root
+        ));
+        assert!(!verify_path_constant_time::<Blake2sHasher>(
+            &leaves[2],
+            usize::MAX,
+            &[left; 100],
+            &root
+        ));
+    }
+
+    #[test]
+    fn hash_pair_sorted_test() {
+        let leaves = (0..4_u8)
+            .map(|i| Blake2sHasher::hash_leaf(&[i]))
+            .collect::<Vec<_>>();
+        let left = Blake2sHasher::hash_pair_sorted(&leaves[0], &leaves[1]);
+        let right = Blake2sHasher::hash_pair_sorted(&leaves[2], &leaves[3]);
+        let root = Blake2sHasher::hash_pair_sorted(&left, &right);
+
+        assert_eq!(
+            Blake2sHasher::hash_pair_sorted(&leaves[1], &leaves[0]),
+            left
+        );
+        assert!(verify_sorted_path::<Blake2sHasher>(
+            &leaves[2],
+            &[leaves[3], left],
+            &root
+        ));
+        assert!(!verify_sorted_path::<Blake2sHasher>(
+            &leaves[2],
+            &[leaves[1], left],
+            &root
+        ));
+    }
+
+    #[test]
+    fn hash_many_flat_test() {
+        const N_INPUTS: usize = 100;
+        let flat_input = (0..N_INPUTS * 7).map(|i| i as u8).collect::<Vec<_>>();
+        let input_ptrs = flat_input
+            .chunks_exact(7)
+            .map(|input| input.as_ptr())
+            .collect::<Vec<_>>();
+        let mut out = vec![0_u8; N_INPUTS * Blake2sHasher::OUTPUT_SIZE];
+        let out_ptrs = (0..N_INPUTS)
+            .map(|i| unsafe { out.as_mut_ptr().add(i * Blake2sHasher::OUTPUT_SIZE) })
+            .collect::<Vec<_>>();
+
+        unsafe { Blake2sHasher::hash_many_in_place(&input_ptrs, 7, &out_ptrs) };
+
+        assert_eq!(Blake2sHasher::hash_many_flat(&flat_input, 7), out);
+        assert!(Blake2sHasher::hash_many_flat(&[], 7).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Input length is not a multiple of the stride!")]
+    fn hash_many_flat_partial_input_test() {
+        Blake2sHasher::hash_many_flat(&[0; 10], 7);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "Number of inputs and outputs don't match!")]
+    fn hash_many_in_place_length_mismatch_test() {
+        let inputs = [[0_u8; 4]; 2];
+        let input_ptrs = inputs
+            .iter()
+            .map(|input| input.as_ptr())
+            .collect::<Vec<_>>();
+        let mut out = [0_u8; Blake2sHasher::OUTPUT_SIZE];
+
+        unsafe { Blake2sHasher::hash_many_in_place(&input_ptrs, 4, &[out.as_mut_ptr()]) };
+    }
+
+    #[test]
+    fn initial_state_reuse_test() {
+        let initial_state = Blake2sHasher::params().to_state();
+
+        for input in [&b"a"[..], b"bc", b""] {
+            let mut state = initial_state.clone();
+            state.update(input);
+
+            assert_eq!(
+                Blake2sHash::try_from(state.finalize().as_bytes()).unwrap(),
+                Blake2sHasher::hash(input)
+            );
+        }
+    }
+
+    #[test]
+    fn hash_many_iter_test() {
+        let inputs: Vec<&[u8]> = vec![b"ab", b"cd", b"ef"];
+
+        let hashes = Blake2sHasher::hash_many_iter(inputs.iter().copied());
+
+        assert_eq!(
+            hashes.collect::<Vec<_>>(),
+            Blake2sHasher::hash_many(&inputs).unwrap()
+        );
+    }
+
+    #[test]
+    fn hash_many_iter_different_lengths_test() {
+        let inputs: Vec<&[u8]> = vec![b"a", b"", b"abc"];
+
+        let hashes = Blake2sHasher::hash_many_iter(inputs.iter().copied());
+
+        inputs
+            .iter()
+            .zip(hashes)
+            .for_each(|(input, hash)| assert_eq!(hash, Blake2sHasher::hash(input)));
+    }
+
+    #[test]
+    fn hash_many_test() {
+        let inputs: Vec<&[u8]> = vec![b"ab", b"cd", b"ef"];
+
+        let hashes = Blake2sHasher::hash_many(&inputs).unwrap();
+
+        assert_eq!(hashes.len(), inputs.len());
+        inputs
+            .iter()
+            .zip(hashes)
+            .for_each(|(input, hash)| assert_eq!(hash, Blake2sHasher::hash(input)));
+    }
+
+    #[test]
+    fn hash_many_empty_test() {
+        let empty_inputs: Vec<&[u8]> = vec![b"", b""];
+
+        assert_eq!(Blake2sHasher::hash_many(&[]), Ok(Vec::new()));
+        assert_eq!(
+            Blake2sHasher::hash_many(&empty_inputs),
+            Ok(vec![Blake2sHasher::hash(b""); 2])
+        );
+    }
+
+    #[test]
+    fn hash_many_different_lengths_test() {
+        assert_eq!(
+            Blake2sHasher::hash_many(&[b"a", b"bc", b"d"]),
+            Err(Error::MismatchedInputLengths {
+                index: 1,
+                length: 2,
+                expected: 1
+            })
+        );
+    }
+
+    #[test]
+    fn hash_state_test() {
+        let mut state = Blake2sHasher::new();
+        state.update(b"a");
+        state.update(b"b");
+        let hash = state.finalize_reset();
+        let hash_empty = state.finalize();
+
+        assert_eq!(hash.to_string(), Blake2sHasher::hash(b"ab").to_string());
+        assert_eq!(hash_empty.to_string(), Blake2sHasher::hash(b"").to_string());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_with_tls_test() {
+        for len in [0, 1, 64, 65, 200] {
+            let data = (0..len).map(|i| i as u8).collect::<Vec<u8>>();
+
+            assert_eq!(
+                Blake2sHasher::hash_with_tls(&data),
+                Blake2sHasher::hash(&data)
+            );
+        }
+        let hash = std::thread::spawn(|| Blake2sHasher::hash_with_tls(b"abc"))
+            .join()
+            .unwrap();
+        assert_eq!(hash, Blake2sHasher::hash(b"abc"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_large_test() {
+        let data = (0..10 * 1024).map(|i| i as u8).collect::<Vec<u8>>();
+
+        let hash = Blake2sHasher::hash_large(&mut &data[..]).unwrap();
+
+        assert_eq!(
+            Blake2sHasher::optimal_chunk_bytes(),
+            Blake2sHasher::BLOCK_SIZE
+        );
+        assert_eq!(hash, Blake2sHasher::hash(&data));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn write_test() {
+        use std::io::Write;
+
+        let data = (0..200).map(|i| i as u8).collect::<Vec<u8>>();
+        let mut hasher = Blake2sHasher::new();
+
+        for chunk in data.chunks(33) {
+            hasher.write_all(chunk).unwrap();
+        }
+        std::io::copy(&mut &data[..], &mut hasher).unwrap();
+        hasher.flush().unwrap();
+
+        assert_eq!(
+            hasher.finalize(),
+            Blake2sHasher::hash(&[data.clone(), data].concat())
+        );
+    }
+
+    #[test]
+    fn update_blocks_test() {
+        let blocks = (0..5).map(|i| [i; 64]).collect::<Vec<[u8; 64]>>();
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(b"prefix");
+
+        hasher.update_blocks(&blocks);
+
+        assert_eq!(
+            hasher.finalize(),
+            Blake2sHasher::hash(&[&b"prefix"[..], &blocks.concat()].concat())
+        );
+    }
+
+    #[test]
+    fn update_felts_test() {
+        let felts = [m31!(1), m31!(2), m31!(1 << 30)];
+        let bytes = felts
+            .iter()
+            .flat_map(|felt| felt.0.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let mut hasher = Blake2sHasher::new();
+
+        hasher.update_felts(&felts);
+
+        assert_eq!(hasher.finalize(), Blake2sHasher::hash(&bytes));
+    }
+
+    #[test]
+    fn hash_felts_test() {
+        assert_eq!(
+            Blake2sHasher::hash_felts(&[m31!(1)]),
+            Blake2sHasher::hash(&[1, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn hash_qm31_test() {
+        let felts = [qm31!(1, 2, 3, 4), qm31!(5, 6, 7, 1 << 30)];
+        let limbs = felts
+            .iter()
+            .flat_map(|felt| felt.to_m31_array())
+            .collect::<Vec<BaseField>>();
+        let mut hasher = Blake2sHasher::new();
+        hasher.update_felts(&limbs);
+
+        assert_eq!(Blake2sHasher::hash_qm31(&felts), hasher.finalize());
+        assert_eq!(
+            Blake2sHasher::hash_qm31(&felts),
+            Blake2sHasher::hash(IntoSlice::<u8>::into_slice(&felts))
+        );
+    }
+
+    #[test]
+    fn from_felts_test() {
+        let felts: Vec<BaseField> = (0..8).map(|i| m31!(i * 1000)).collect();
+        let secure_felts = [qm31!(0, 1000, 2000, 3000), qm31!(4000, 5000, 6000, 7000)];
+        let bytes = felts
+            .iter()
+            .flat_map(|felt| felt.0.to_le_bytes())
+            .collect::<Vec<u8>>();
+
+        assert_eq!(
+            Blake2sHash::try_from(&felts[..]).unwrap(),
+            Blake2sHash::try_from(&bytes[..]).unwrap()
+        );
+        assert_eq!(
+            Blake2sHash::try_from(&secure_felts[..]).unwrap(),
+            Blake2sHash::try_from(&bytes[..]).unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_test() {
+        let hash = Blake2sHasher::hash(b"a");
+
+        let serialized = serde_json::to_string(&hash).unwrap();
+        let deserialized: Blake2sHash = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(serialized, format!("\"{}\"", hash));
+        assert_eq!(deserialized, hash);
+        assert!(serde_json::from_str::<Blake2sHash>("\"4a0d12\"").is_err());
+        assert!(serde_json::from_str::<Blake2sHash>("\"not hex\"").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bincode_test() {
+        let hash = Blake2sHasher::hash(b"a");
+
+        let serialized = bincode::serialize(&hash).unwrap();
+        let deserialized: Blake2sHash = bincode::deserialize(&serialized).unwrap();
+        let short_serialized = bincode::serialize(&vec![1_u8; 31]).unwrap();
+
+        // Length prefix followed by the raw bytes.
+        assert_eq!(serialized.len(), 8 + 32);
+        assert_eq!(deserialized, hash);
+        assert!(bincode::deserialize::<Blake2sHash>(&short_serialized).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_seq_test() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+
+        let hash = Blake2sHasher::hash(b"a");
+        let deserialize_seq = |bytes: Vec<u8>| {
+            Blake2sHash::deserialize(SeqDeserializer::<_, Error>::new(bytes.into_iter()))
+        };
+
+        assert_eq!(deserialize_seq(hash.as_ref().to_vec()), Ok(hash));
+        assert!(deserialize_seq(vec![1; 31]).is_err());
+        assert!(deserialize_seq(vec![1; 33]).is_err());
+    }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<Blake2sHasher>(&(0..64).collect::<Vec<u8>>());
     }
 }
@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+
+use blake2s_simd::many::{hash_many, HashManyJob, MAX_DEGREE};
+use blake2s_simd::{Params, State};
+
+use super::blake2_hash::Blake2sHash;
+use super::hasher::write_output;
+
+// Wrapper for the SIMD accelerated blake2s Hashing functionalities.
+// Outputs are bit-identical to [super::blake2_hash::Blake2sHasher], hence the same hash type is
+// used and commitments are interchangeable.
+#[derive(Clone, Debug)]
+pub struct Blake2sSimdHasher {
+    state: State,
+}
+
+impl super::hasher::Hasher for Blake2sSimdHasher {
+    type Hash = Blake2sHash;
+    const BLOCK_SIZE: usize = 64;
+    const OUTPUT_SIZE: usize = 32;
+    type NativeType = u8;
+    type Digest = [u8; 32];
+
+    fn new() -> Self {
+        Self {
+            state: State::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = State::new();
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.state.update(data);
+    }
+
+    fn finalize(self) -> Blake2sHash {
+        Blake2sHash::try_from_bytes(self.state.finalize().as_bytes())
+            .expect("The state outputs 32 bytes.")
+    }
+
+    fn finalize_reset(&mut self) -> Blake2sHash {
+        let res = Blake2sHash::try_from_bytes(self.state.finalize().as_bytes())
+            .expect("The state outputs 32 bytes.");
+        self.reset();
+        res
+    }
+
+    /// Hashes the inputs in groups of [MAX_DEGREE], each group is hashed in parallel SIMD lanes
+    /// when supported by the CPU. The last (partial) group is hashed with fewer lanes.
+    unsafe fn hash_many_in_place(
+        data: &[*const u8],
+        single_input_length_bytes: usize,
+        dst: &[*mut u8],
+    ) {
+        let params = Params::new();
+        let mut jobs = Vec::with_capacity(MAX_DEGREE);
+        for (src_chunk, dst_chunk) in data.chunks(MAX_DEGREE).zip(dst.chunks(MAX_DEGREE)) {
+            jobs.extend(src_chunk.iter().map(|p| {
+                HashManyJob::new(
+                    &params,
+                    core::slice::from_raw_parts(*p, single_input_length_bytes),
+                )
+            }));
+            hash_many(jobs.iter_mut());
+            for (job, p) in jobs.drain(..).zip(dst_chunk) {
+                write_output(job.to_hash().as_bytes(), *p);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::Blake2sSimdHasher;
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
+    use crate::commitment_scheme::hasher::Hasher;
+
+    #[test]
+    fn single_hash_test() {
+        let hash_a = Blake2sSimdHasher::hash(b"a");
+        assert_eq!(
+            hash_a.to_string(),
+            "4a0d129873403037c2cd9b9048203687f6233fb6738956e0349bd4320fec3e90"
+        );
+    }
+
+    #[test]
+    fn hash_many_cross_check_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let inputs = (0..37).map(|_| rng.gen::<[u8; 32]>()).collect::<Vec<_>>();
+        let input_refs = inputs.iter().map(|input| &input[..]).collect::<Vec<_>>();
+
+        assert_eq!(
+            Blake2sSimdHasher::hash_many(&input_refs),
+            Blake2sHasher::hash_many(&input_refs)
+        );
+    }
+
+    #[test]
+    fn hash_state_test() {
+        let mut state = Blake2sSimdHasher::new();
+        state.update(b"a");
+        state.update(b"b");
+        let hash = state.finalize_reset();
+        let hash_empty = state.finalize();
+
+        assert_eq!(hash, Blake2sHasher::hash(b"ab"));
+        assert_eq!(hash_empty, Blake2sHasher::hash(b""));
+    }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<Blake2sSimdHasher>(&(0..64).collect::<Vec<u8>>());
+    }
+}
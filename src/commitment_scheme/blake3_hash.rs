@@ -1,6 +1,10 @@
-use std::fmt;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
 
-use super::hasher::Name;
+use super::error::Error;
+use super::hasher::{write_output, HashConversionError, Name};
 
 // Wrapper for the blake3 hash type.
 #[derive(Clone, Copy, PartialEq, Default, Eq)]
@@ -12,23 +16,22 @@ impl From<Blake3Hash> for Vec<u8> {
     }
 }
 
-impl From<Vec<u8>> for Blake3Hash {
-    fn from(value: Vec<u8>) -> Self {
-        Self(
-            value
-                .try_into()
-                .expect("Failed converting Vec<u8> to Blake3Hash Type!"),
-        )
+impl TryFrom<&[u8]> for Blake3Hash {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(value.try_into().map_err(|_| HashConversionError {
+            expected: 32,
+            actual: value.len(),
+        })?))
     }
 }
 
-impl From<&[u8]> for Blake3Hash {
-    fn from(value: &[u8]) -> Self {
-        Self(
-            value
-                .try_into()
-                .expect("Failed converting &[u8] to Blake3Hash Type!"),
-        )
+impl TryFrom<Vec<u8>> for Blake3Hash {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
     }
 }
 
@@ -51,7 +54,7 @@ impl fmt::Debug for Blake3Hash {
 }
 
 impl Name for Blake3Hash {
-    const NAME: std::borrow::Cow<'static, str> = std::borrow::Cow::Borrowed("BLAKE3");
+    const NAME: alloc::borrow::Cow<'static, str> = alloc::borrow::Cow::Borrowed("BLAKE3");
 }
 
 impl super::hasher::Hash<u8> for Blake3Hash {}
@@ -67,6 +70,7 @@ impl super::hasher::Hasher for Blake3Hasher {
     const BLOCK_SIZE: usize = 64;
     const OUTPUT_SIZE: usize = 32;
     type NativeType = u8;
+    type Digest = [u8; 32];
 
     fn new() -> Self {
         Self {
@@ -99,23 +103,34 @@ impl super::hasher::Hasher for Blake3Hasher {
     ) {
         let mut hasher = blake3::Hasher::new();
         data.iter()
-            .map(|p| std::slice::from_raw_parts(*p, single_input_length_bytes))
-            .zip(
-                dst.iter()
-                    .map(|p| std::slice::from_raw_parts_mut(*p, Self::OUTPUT_SIZE)),
-            )
+            .map(|p| core::slice::from_raw_parts(*p, single_input_length_bytes))
+            .zip(dst)
             .for_each(|(input, out)| {
                 hasher.update(input);
-                let mut output_reader = hasher.finalize_xof();
-                output_reader.fill(&mut out[..Self::OUTPUT_SIZE]);
+                write_output(hasher.finalize().as_bytes(), *out);
                 hasher.reset();
             })
     }
 }
 
+/// Allows using the hasher as an [io::Write] sink, equivalent to calling `update` on the written
+/// data.
+#[cfg(feature = "std")]
+impl io::Write for Blake3Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        super::hasher::Hasher::update(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::commitment_scheme::blake3_hash::Blake3Hasher;
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
     use crate::commitment_scheme::hasher::Hasher;
 
     #[test]
@@ -151,4 +166,9 @@ mod tests {
         assert_eq!(hash.to_string(), Blake3Hasher::hash(b"ab").to_string());
         assert_eq!(hash_empty.to_string(), Blake3Hasher::hash(b"").to_string())
     }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<Blake3Hasher>(&(0..64).collect::<Vec<u8>>());
+    }
 }
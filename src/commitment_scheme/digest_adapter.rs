@@ -0,0 +1,152 @@
+use digest::consts::{U136, U32, U64};
+use digest::generic_array::ArrayLength;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+
+use super::blake2_hash::Blake2sHasher;
+use super::blake2s_simd_hash::Blake2sSimdHasher;
+use super::blake3_hash::Blake3Hasher;
+use super::hasher::Hasher;
+use super::keccak_hash::KeccakHasher;
+use super::sha256_hash::Sha256Hasher;
+
+/// The output and block sizes of a byte [Hasher] as type-level sizes, as the `digest` crate traits
+/// require. They must equal [Hasher::OUTPUT_SIZE] and [Hasher::BLOCK_SIZE].
+pub trait DigestSizes: Hasher<NativeType = u8> {
+    type OutputSize: ArrayLength<u8> + 'static;
+    type BlockSize: ArrayLength<u8> + 'static;
+}
+
+impl DigestSizes for Blake2sHasher {
+    type OutputSize = U32;
+    type BlockSize = U64;
+}
+
+impl DigestSizes for Blake2sSimdHasher {
+    type OutputSize = U32;
+    type BlockSize = U64;
+}
+
+impl DigestSizes for Blake3Hasher {
+    type OutputSize = U32;
+    type BlockSize = U64;
+}
+
+impl DigestSizes for KeccakHasher {
+    type OutputSize = U32;
+    type BlockSize = U136;
+}
+
+impl DigestSizes for Sha256Hasher {
+    type OutputSize = U32;
+    type BlockSize = U64;
+}
+
+/// Adapts a [Hasher] to the `digest` crate traits, so it can be used wherever a [digest::Digest] is
+/// expected (e.g. `hmac::SimpleHmac`). Supports the hashers implementing [DigestSizes].
+#[derive(Clone, Debug)]
+pub struct DigestAdapter<H: DigestSizes>(H);
+
+impl<H: DigestSizes> Default for DigestAdapter<H> {
+    fn default() -> Self {
+        Self(H::new())
+    }
+}
+
+impl<H: DigestSizes> HashMarker for DigestAdapter<H> {}
+
+impl<H: DigestSizes> OutputSizeUser for DigestAdapter<H> {
+    type OutputSize = H::OutputSize;
+}
+
+impl<H: DigestSizes> digest::core_api::BlockSizeUser for DigestAdapter<H> {
+    type BlockSize = H::BlockSize;
+}
+
+impl<H: DigestSizes> Update for DigestAdapter<H> {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+}
+
+impl<H: DigestSizes> FixedOutput for DigestAdapter<H> {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(self.0.finalize().as_ref());
+    }
+}
+
+impl<H: DigestSizes> FixedOutputReset for DigestAdapter<H> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(self.0.finalize_reset().as_ref());
+    }
+}
+
+impl<H: DigestSizes> Reset for DigestAdapter<H> {
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blake2::Blake2s256;
+    use digest::Digest;
+    use hmac::{Mac, SimpleHmac};
+
+    use super::{DigestAdapter, DigestSizes};
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::blake2s_simd_hash::Blake2sSimdHasher;
+    use crate::commitment_scheme::blake3_hash::Blake3Hasher;
+    use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::keccak_hash::KeccakHasher;
+    use crate::commitment_scheme::sha256_hash::Sha256Hasher;
+
+    #[test]
+    fn digest_test() {
+        let mut digest = DigestAdapter::<Blake2sHasher>::new();
+        Digest::update(&mut digest, b"a");
+        Digest::update(&mut digest, b"b");
+
+        assert_eq!(
+            digest.finalize_reset()[..],
+            *Blake2sHasher::hash(b"ab").as_ref()
+        );
+        assert_eq!(digest.finalize()[..], *Blake2sHasher::hash(b"").as_ref());
+    }
+
+    #[test]
+    fn hmac_test() {
+        let mut mac = SimpleHmac::<DigestAdapter<Blake2sHasher>>::new_from_slice(b"key").unwrap();
+        let mut expected_mac = SimpleHmac::<Blake2s256>::new_from_slice(b"key").unwrap();
+        mac.update(b"message");
+        expected_mac.update(b"message");
+
+        assert_eq!(
+            mac.finalize().into_bytes(),
+            expected_mac.finalize().into_bytes()
+        );
+    }
+
+    fn assert_sizes<H: DigestSizes>() {
+        use digest::typenum::Unsigned;
+
+        assert_eq!(H::OutputSize::USIZE, H::OUTPUT_SIZE);
+        assert_eq!(H::BlockSize::USIZE, H::BLOCK_SIZE);
+    }
+
+    #[test]
+    fn digest_sizes_test() {
+        assert_sizes::<Blake2sHasher>();
+        assert_sizes::<Blake2sSimdHasher>();
+        assert_sizes::<Blake3Hasher>();
+        assert_sizes::<KeccakHasher>();
+        assert_sizes::<Sha256Hasher>();
+    }
+
+    #[test]
+    fn keccak_digest_test() {
+        let mut digest = DigestAdapter::<KeccakHasher>::new();
+        Digest::update(&mut digest, b"ab");
+
+        assert_eq!(digest.finalize()[..], *KeccakHasher::hash(b"ab").as_ref());
+    }
+}
@@ -0,0 +1,273 @@
+use core::convert::Infallible;
+use core::fmt;
+
+use super::blake2_hash::HexError;
+use super::hasher::{BufferTooSmallError, HashConversionError, InputLengthError};
+use super::merkle_decommitment::{DecommitmentParseError, MerkleVerifyError};
+use super::merkle_tree::MerkleTreeError;
+use super::serialize::FeltDecodingError;
+
+/// The error of the fallible APIs of the commitment scheme. The more specific errors of the
+/// individual modules are reported through it, so callers combining several APIs can use `?`
+/// throughout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    InvalidHashLength {
+        expected: usize,
+        got: usize,
+    },
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    VerificationFailed(MerkleVerifyError),
+    InvalidOpening {
+        index: usize,
+        error: MerkleVerifyError,
+    },
+    MismatchedColumnLengths {
+        column: usize,
+        length: usize,
+        expected: usize,
+    },
+    MismatchedInputLengths {
+        index: usize,
+        length: usize,
+        expected: usize,
+    },
+    NoColumns,
+    EmptyColumns,
+    BufferTooSmall {
+        required: usize,
+        got: usize,
+    },
+    InvalidDecommitment(DecommitmentParseError),
+    InvalidFelts(FeltDecodingError),
+    InvalidHex(HexError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHashLength { expected, got } => write!(
+                f,
+                "Invalid hash length: expected {expected} elements, got {got}."
+            ),
+            Self::IndexOutOfRange { index, len } => {
+                write!(f, "Index {index} is out of range for length {len}.")
+            }
+            Self::VerificationFailed(v) => write!(f, "Verification failed: {v}"),
+            Self::InvalidOpening { index, error } => {
+                write!(f, "Opening {index} failed verification: {error}")
+            }
+            Self::MismatchedColumnLengths {
+                column,
+                length,
+                expected,
+            } => write!(
+                f,
+                "Column {column} has length {length}, expected {expected}."
+            ),
+            Self::MismatchedInputLengths {
+                index,
+                length,
+                expected,
+            } => write!(f, "Input {index} has length {length}, expected {expected}."),
+            Self::NoColumns => write!(f, "Attempted committing to zero columns."),
+            Self::EmptyColumns => write!(f, "Attempted committing to empty columns."),
+            Self::BufferTooSmall { required, got } => write!(
+                f,
+                "Destination buffer too small: required {required} elements, got {got}."
+            ),
+            Self::InvalidDecommitment(v) => write!(f, "Invalid decommitment encoding: {v}"),
+            Self::InvalidFelts(v) => write!(f, "Invalid field element encoding: {v}"),
+            Self::InvalidHex(v) => write!(f, "Invalid hex hash: {v}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::VerificationFailed(err) => Some(err),
+            Self::InvalidDecommitment(err) => Some(err),
+            Self::InvalidFelts(err) => Some(err),
+            Self::InvalidHex(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// The error of infallible conversions, e.g. of [Hash](super::hasher::Hash) types converting from
+/// slices with [From].
+impl From<Infallible> for Error {
+    fn from(err: Infallible) -> Self {
+        match err {}
+    }
+}
+
+impl From<MerkleVerifyError> for Error {
+    fn from(err: MerkleVerifyError) -> Self {
+        Self::VerificationFailed(err)
+    }
+}
+
+impl From<DecommitmentParseError> for Error {
+    fn from(err: DecommitmentParseError) -> Self {
+        Self::InvalidDecommitment(err)
+    }
+}
+
+impl From<FeltDecodingError> for Error {
+    fn from(err: FeltDecodingError) -> Self {
+        Self::InvalidFelts(err)
+    }
+}
+
+impl From<HexError> for Error {
+    fn from(err: HexError) -> Self {
+        Self::InvalidHex(err)
+    }
+}
+
+impl From<HashConversionError> for Error {
+    fn from(err: HashConversionError) -> Self {
+        Self::InvalidHashLength {
+            expected: err.expected,
+            got: err.actual,
+        }
+    }
+}
+
+impl From<BufferTooSmallError> for Error {
+    fn from(err: BufferTooSmallError) -> Self {
+        Self::BufferTooSmall {
+            required: err.required,
+            got: err.actual,
+        }
+    }
+}
+
+impl From<MerkleTreeError> for Error {
+    fn from(err: MerkleTreeError) -> Self {
+        match err {
+            MerkleTreeError::QueryOutOfRange { query, n_rows } => Self::IndexOutOfRange {
+                index: query,
+                len: n_rows,
+            },
+            MerkleTreeError::NoColumns => Self::NoColumns,
+            MerkleTreeError::EmptyColumns => Self::EmptyColumns,
+            MerkleTreeError::ColumnLengthMismatch {
+                column,
+                length,
+                expected,
+            } => Self::MismatchedColumnLengths {
+                column,
+                length,
+                expected,
+            },
+        }
+    }
+}
+
+impl From<InputLengthError> for Error {
+    fn from(err: InputLengthError) -> Self {
+        Self::MismatchedInputLengths {
+            index: err.index,
+            length: err.length,
+            expected: err.expected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+    use crate::commitment_scheme::blake3_hash::Blake3Hash;
+    use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::merkle_decommitment::{
+        stateless_verify, MerkleDecommitment, MerkleVerifyError,
+    };
+    use crate::commitment_scheme::merkle_tree::MerkleTree;
+    use crate::commitment_scheme::serialize::bytes_to_felts;
+    use crate::core::fields::m31::M31;
+    use crate::m31;
+
+    #[test]
+    fn error_variants_test() {
+        let column = (0..8).map(M31::from_u32_unchecked).collect::<Vec<_>>();
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(vec![column.clone()]);
+
+        assert_eq!(
+            Blake3Hash::try_from(&[0; 31][..]),
+            Err(Error::InvalidHashLength {
+                expected: 32,
+                got: 31
+            })
+        );
+        assert!(matches!(
+            tree.try_generate_decommitment(vec![8]),
+            Err(Error::IndexOutOfRange { index: 8, len: 8 })
+        ));
+        assert!(matches!(
+            MerkleTree::<M31, Blake2sHasher>::commit_columns(&[&column, &column[1..]]),
+            Err(Error::MismatchedColumnLengths {
+                column: 1,
+                length: 7,
+                expected: 8
+            })
+        ));
+        assert!(matches!(
+            MerkleTree::<M31, Blake2sHasher>::commit_columns(&[]),
+            Err(Error::NoColumns)
+        ));
+        assert!(matches!(
+            MerkleTree::<M31, Blake2sHasher>::commit_columns(&[&[]]),
+            Err(Error::EmptyColumns)
+        ));
+        assert!(matches!(
+            stateless_verify(
+                Blake2sHasher::hash(b"wrong root"),
+                &[(2, vec![m31!(2)], tree.generate_decommitment(vec![2]))]
+            ),
+            Err(Error::InvalidOpening {
+                index: 0,
+                error: MerkleVerifyError::RootMismatch
+            })
+        ));
+        assert!(matches!(
+            tree.generate_decommitment(vec![2])
+                .verify_detailed(Blake2sHasher::hash(b"wrong root"), &[2]),
+            Err(Error::VerificationFailed(MerkleVerifyError::RootMismatch))
+        ));
+        assert!(matches!(
+            Blake2sHasher::hash_many(&[b"a", b"bc"]),
+            Err(Error::MismatchedInputLengths {
+                index: 1,
+                length: 2,
+                expected: 1
+            })
+        ));
+        assert!(matches!(
+            Blake2sHasher::new().finalize_into(&mut [0; 8]),
+            Err(Error::BufferTooSmall {
+                required: 32,
+                got: 8
+            })
+        ));
+        assert!(matches!(
+            MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(&[]),
+            Err(Error::InvalidDecommitment(_))
+        ));
+        assert!(matches!(
+            bytes_to_felts(&[0; 3]),
+            Err(Error::InvalidFelts(_))
+        ));
+        assert!(matches!(
+            "zz".parse::<Blake2sHash>(),
+            Err(Error::InvalidHex(_))
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+
+use super::error;
+use super::hasher::Hasher;
+use super::merkle_decommitment::MerkleDecommitment;
+use super::merkle_tree::MerkleTree;
+use crate::core::fields::{Field, IntoSlice};
+
+/// A merkle tree over columns of field elements, e.g. [crate::core::fields::m31::M31] or
+/// [crate::core::fields::qm31::QM31], whose API never exposes the hasher's native type: columns
+/// are committed as `&[F]`, and openings return the opened rows as `F` values.
+///
+/// A thin wrapper of [MerkleTree], with the same root.
+pub struct FieldMerkleTree<F: Field, H: Hasher> {
+    tree: MerkleTree<F, H>,
+    n_columns: usize,
+}
+
+impl<F: Field, H: Hasher> FieldMerkleTree<F, H>
+where
+    F: IntoSlice<H::NativeType>,
+    H::NativeType: Copy + From<u8>,
+{
+    /// Commits on the given columns, which must all be of the same, non-zero, length, see
+    /// [MerkleTree::commit_columns].
+    pub fn commit(columns: &[&[F]]) -> Result<Self, error::Error> {
+        Ok(Self {
+            tree: MerkleTree::commit_columns(columns)?,
+            n_columns: columns.len(),
+        })
+    }
+
+    pub fn root(&self) -> H::Hash {
+        self.tree.root()
+    }
+
+    pub fn n_columns(&self) -> usize {
+        self.n_columns
+    }
+
+    /// Returns the number of committed rows, including padding rows.
+    pub fn n_rows(&self) -> usize {
+        self.tree.n_rows()
+    }
+
+    /// Opens the rows at the given queries, which must be in the range of the committed rows.
+    pub fn open(&self, queries: &[usize]) -> Result<FieldOpening<F, H>, error::Error> {
+        let queries = queries
+            .iter()
+            .copied()
+            .sorted_unstable()
+            .dedup()
+            .collect_vec();
+        let decommitment = self.tree.try_generate_decommitment(queries.clone())?;
+        let values = decommitment.values().collect();
+        Ok(FieldOpening {
+            queries,
+            values,
+            decommitment,
+        })
+    }
+
+    /// The underlying tree, e.g. to decommit with the [MerkleTree] API.
+    pub fn tree(&self) -> &MerkleTree<F, H> {
+        &self.tree
+    }
+}
+
+/// The rows opened by [FieldMerkleTree::open], along with the decommitment proving them.
+pub struct FieldOpening<F: Field, H: Hasher> {
+    queries: Vec<usize>,
+    values: Vec<Vec<F>>,
+    decommitment: MerkleDecommitment<F, H>,
+}
+
+impl<F: Field, H: Hasher> FieldOpening<F, H>
+where
+    F: IntoSlice<H::NativeType>,
+    H::NativeType: Copy + From<u8>,
+{
+    /// The opened queries, sorted and deduplicated.
+    pub fn queries(&self) -> &[usize] {
+        &self.queries
+    }
+
+    /// The values of every opened row, in the order of [FieldOpening::queries]. Every row holds a
+    /// value per column.
+    pub fn values(&self) -> &[Vec<F>] {
+        &self.values
+    }
+
+    /// The values of the row at `query`, if it was opened.
+    pub fn row(&self, query: usize) -> Option<&[F]> {
+        let i = self.queries.binary_search(&query).ok()?;
+        Some(&self.values[i])
+    }
+
+    /// Verifies that the opened rows are committed to by `root`.
+    pub fn verify(&self, root: H::Hash) -> Result<(), error::Error> {
+        self.decommitment.verify_detailed(root, &self.queries)
+    }
+
+    pub fn decommitment(&self) -> &MerkleDecommitment<F, H> {
+        &self.decommitment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldMerkleTree;
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::error::Error;
+    use crate::commitment_scheme::merkle_tree::MerkleTree;
+    use crate::core::fields::m31::M31;
+    use crate::core::fields::qm31::QM31;
+
+    #[test]
+    fn m31_open_test() {
+        let columns = (0..3)
+            .map(|j| {
+                (0..64)
+                    .map(|i| M31::from_u32_unchecked(i * 3 + j))
+                    .collect()
+            })
+            .collect::<Vec<Vec<M31>>>();
+        let column_refs = columns.iter().map(|c| &c[..]).collect::<Vec<_>>();
+        let tree = FieldMerkleTree::<M31, Blake2sHasher>::commit(&column_refs).unwrap();
+
+        let opening = tree.open(&[33, 5, 33]).unwrap();
+
+        assert_eq!(opening.verify(tree.root()), Ok(()));
+        assert_eq!(opening.queries(), [5, 33]);
+        assert_eq!(
+            opening.row(33).unwrap(),
+            [columns[0][33], columns[1][33], columns[2][33]]
+        );
+        assert_eq!(
+            opening.values()[0],
+            [columns[0][5], columns[1][5], columns[2][5]]
+        );
+        assert_eq!(opening.row(6), None);
+        assert_eq!(
+            tree.root(),
+            MerkleTree::<M31, Blake2sHasher>::commit(columns).root()
+        );
+    }
+
+    #[test]
+    fn qm31_open_test() {
+        let columns = (0..2)
+            .map(|j| {
+                (0..16)
+                    .map(|i| QM31::from_u32_unchecked(i, j, i + j, 7))
+                    .collect()
+            })
+            .collect::<Vec<Vec<QM31>>>();
+        let column_refs = columns.iter().map(|c| &c[..]).collect::<Vec<_>>();
+        let tree = FieldMerkleTree::<QM31, Blake2sHasher>::commit(&column_refs).unwrap();
+
+        let opening = tree.open(&[0, 15]).unwrap();
+
+        assert_eq!(opening.verify(tree.root()), Ok(()));
+        assert_eq!(tree.n_columns(), 2);
+        assert_eq!(
+            opening.values(),
+            [
+                vec![columns[0][0], columns[1][0]],
+                vec![columns[0][15], columns[1][15]]
+            ]
+        );
+    }
+
+    #[test]
+    fn open_wrong_root_test() {
+        let column = (0..16).map(M31::from_u32_unchecked).collect::<Vec<_>>();
+        let tree = FieldMerkleTree::<M31, Blake2sHasher>::commit(&[&column]).unwrap();
+        let other_tree = FieldMerkleTree::<M31, Blake2sHasher>::commit(&[&column[..8]]).unwrap();
+
+        let opening = tree.open(&[3]).unwrap();
+
+        assert!(opening.verify(other_tree.root()).is_err());
+        assert_eq!(
+            tree.open(&[16]).err(),
+            Some(Error::IndexOutOfRange { index: 16, len: 16 })
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::OnceLock;
+
+use thiserror::Error;
+
+use super::blake2_hash::{Blake2sHash, Blake2sHasher};
+use super::hasher::{write_output, Hasher};
+
+/// A compute device that hashes batches of equal length inputs with the standard, 32 byte,
+/// blake2s, e.g. a Metal or CUDA compute shader running a blake2s invocation per input.
+///
+/// Implementations live outside of this crate, next to the GPU API they're written against, and
+/// are installed with [set_device].
+pub trait Blake2sDevice: Send + Sync {
+    /// Hashes the `outputs.len() / 32` inputs of `input_length` bytes each, concatenated in
+    /// `inputs`, writing the hash of the `i`th input to `outputs[32 * i..32 * (i + 1)]`.
+    ///
+    /// `inputs` is the buffer to upload to the device, and `outputs` the buffer to read the
+    /// digests back into. On error the contents of `outputs` are ignored, and the batch is hashed
+    /// on the CPU instead.
+    fn hash_batch(
+        &self,
+        inputs: &[u8],
+        input_length: usize,
+        outputs: &mut [u8],
+    ) -> Result<(), DeviceError>;
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum DeviceError {
+    #[error("The device is unavailable.")]
+    Unavailable,
+    #[error("The device ran out of memory.")]
+    OutOfMemory,
+    #[error("The device failed to run the batch.")]
+    ExecutionFailed,
+}
+
+static DEVICE: OnceLock<Box<dyn Blake2sDevice>> = OnceLock::new();
+
+/// Installs the device used by [GpuBlake2sHasher] for the rest of the process. Returns the
+/// device back if one is already installed.
+pub fn set_device(device: Box<dyn Blake2sDevice>) -> Result<(), Box<dyn Blake2sDevice>> {
+    DEVICE.set(device)
+}
+
+/// Whether a device is installed, i.e. whether [GpuBlake2sHasher] offloads batches at all.
+pub fn device_available() -> bool {
+    DEVICE.get().is_some()
+}
+
+/// A blake2s hasher that offloads batch hashing to the device installed with [set_device].
+///
+/// Outputs are bit-identical to [Blake2sHasher], hence the same hash type is used and commitments
+/// are interchangeable. Only `hash_many_in_place` is offloaded; incremental hashing, and batches
+/// hashed while no device is installed or that the device fails to hash, run on the CPU.
+#[derive(Clone, Debug, Default)]
+pub struct GpuBlake2sHasher(Blake2sHasher);
+
+impl GpuBlake2sHasher {
+    /// Hashes the batch on `device`, packing the inputs into a single upload buffer. Falls back
+    /// to [Blake2sHasher] when there is no device or it fails.
+    ///
+    /// # Safety
+    ///
+    /// See [Hasher::hash_many_in_place].
+    unsafe fn hash_many_on(
+        device: Option<&dyn Blake2sDevice>,
+        data: &[*const u8],
+        single_input_length_bytes: usize,
+        dst: &[*mut u8],
+    ) {
+        if let Some(device) = device.filter(|_| !data.is_empty()) {
+            let mut inputs = Vec::with_capacity(data.len() * single_input_length_bytes);
+            for src in data {
+                inputs
+                    .extend_from_slice(std::slice::from_raw_parts(*src, single_input_length_bytes));
+            }
+            let mut outputs = vec![0; data.len() * Self::OUTPUT_SIZE];
+            if device
+                .hash_batch(&inputs, single_input_length_bytes, &mut outputs)
+                .is_ok()
+            {
+                for (hash, dst) in outputs.chunks_exact(Self::OUTPUT_SIZE).zip(dst) {
+                    write_output(hash, *dst);
+                }
+                return;
+            }
+        }
+        Blake2sHasher::hash_many_in_place(data, single_input_length_bytes, dst);
+    }
+}
+
+impl Hasher for GpuBlake2sHasher {
+    type Hash = Blake2sHash;
+    const BLOCK_SIZE: usize = 64;
+    const OUTPUT_SIZE: usize = 32;
+    type NativeType = u8;
+    type Digest = Blake2sHash;
+
+    fn new() -> Self {
+        Self(Blake2sHasher::new())
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Blake2sHash {
+        self.0.finalize()
+    }
+
+    fn finalize_reset(&mut self) -> Blake2sHash {
+        self.0.finalize_reset()
+    }
+
+    unsafe fn hash_many_in_place(
+        data: &[*const u8],
+        single_input_length_bytes: usize,
+        dst: &[*mut u8],
+    ) {
+        Self::hash_many_on(
+            DEVICE.get().map(|device| &**device),
+            data,
+            single_input_length_bytes,
+            dst,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{device_available, Blake2sDevice, DeviceError, GpuBlake2sHasher};
+    use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
+    use crate::commitment_scheme::hasher::Hasher;
+
+    /// Runs the batch on the CPU through the device interface, counting the batches.
+    #[derive(Default)]
+    struct CpuDevice {
+        batches: AtomicUsize,
+    }
+
+    impl Blake2sDevice for CpuDevice {
+        fn hash_batch(
+            &self,
+            inputs: &[u8],
+            input_length: usize,
+            outputs: &mut [u8],
+        ) -> Result<(), DeviceError> {
+            self.batches.fetch_add(1, Ordering::Relaxed);
+            assert_eq!(inputs.len(), outputs.len() / 32 * input_length);
+            for (i, output) in outputs.chunks_exact_mut(32).enumerate() {
+                let input = &inputs[i * input_length..(i + 1) * input_length];
+                output.copy_from_slice(Blake2sHasher::hash(input).as_ref());
+            }
+            Ok(())
+        }
+    }
+
+    struct FailingDevice;
+
+    impl Blake2sDevice for FailingDevice {
+        fn hash_batch(&self, _: &[u8], _: usize, _: &mut [u8]) -> Result<(), DeviceError> {
+            Err(DeviceError::ExecutionFailed)
+        }
+    }
+
+    fn hash_many_on(device: &dyn Blake2sDevice, inputs: &[&[u8]]) -> Vec<Blake2sHash> {
+        let mut hashes = vec![Blake2sHash::default(); inputs.len()];
+        let data = inputs
+            .iter()
+            .map(|input| input.as_ptr())
+            .collect::<Vec<_>>();
+        let dst = hashes
+            .iter_mut()
+            .map(|hash| hash.as_mut().as_mut_ptr())
+            .collect::<Vec<_>>();
+        let input_length = inputs.first().map_or(0, |input| input.len());
+        unsafe { GpuBlake2sHasher::hash_many_on(Some(device), &data, input_length, &dst) };
+        hashes
+    }
+
+    /// Inputs spanning more than one blake2s block.
+    fn random_inputs(n_inputs: usize) -> Vec<Vec<u8>> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..n_inputs)
+            .map(|_| (0..100).map(|_| rng.gen()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn device_hash_many_test() {
+        let inputs = random_inputs(37);
+        let input_refs = inputs.iter().map(|input| &input[..]).collect::<Vec<_>>();
+        let device = CpuDevice::default();
+
+        let hashes = hash_many_on(&device, &input_refs);
+
+        assert_eq!(device.batches.load(Ordering::Relaxed), 1);
+        assert_eq!(hashes, Blake2sHasher::hash_many(&input_refs).unwrap());
+    }
+
+    #[test]
+    fn failing_device_falls_back_to_cpu_test() {
+        let inputs = random_inputs(5);
+        let input_refs = inputs.iter().map(|input| &input[..]).collect::<Vec<_>>();
+
+        let hashes = hash_many_on(&FailingDevice, &input_refs);
+
+        assert_eq!(hashes, Blake2sHasher::hash_many(&input_refs).unwrap());
+    }
+
+    #[test]
+    fn empty_batch_skips_device_test() {
+        let device = CpuDevice::default();
+
+        let hashes = hash_many_on(&device, &[]);
+
+        assert!(hashes.is_empty());
+        assert_eq!(device.batches.load(Ordering::Relaxed), 0);
+    }
+
+    /// Hashes on the installed device, if any. No device is installed by the tests, so this checks
+    /// the CPU fallback, unless a real device is installed by a test binary that has one.
+    #[test]
+    fn hash_many_matches_blake2s_test() {
+        if !device_available() {
+            eprintln!("No blake2s device installed, checking the CPU fallback only.");
+        }
+        let inputs = random_inputs(37);
+        let input_refs = inputs.iter().map(|input| &input[..]).collect::<Vec<_>>();
+
+        assert_eq!(
+            GpuBlake2sHasher::hash_many(&input_refs),
+            Blake2sHasher::hash_many(&input_refs)
+        );
+    }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<GpuBlake2sHasher>(&(0..64).collect::<Vec<u8>>());
+    }
+}
@@ -1,8 +1,32 @@
-use std::borrow::Cow;
-use std::fmt::{Debug, Display};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+#[cfg(feature = "std")]
+use std::io;
+
+use subtle::Choice;
+
+use super::error;
+use super::serialize::Endianness;
+use crate::core::fields::m31::BaseField;
+use crate::core::fields::IntoSlice;
+
+/// Domain separation tag prepended to Merkle leaves, see [Hasher::hash_leaf].
+pub const LEAF_DOMAIN_TAG: u8 = 0x00;
+/// Domain separation tag prepended to Merkle internal nodes, see [Hasher::hash_nodes].
+pub const NODE_DOMAIN_TAG: u8 = 0x01;
+/// Domain separation tag prepended to the root of a Merkle tree, which binds its top node to its
+/// number of rows, see [super::utils::hash_root].
+pub const ROOT_DOMAIN_TAG: u8 = 0x02;
 
 pub trait Name {
+    /// Name of the hash family, e.g. `BLAKE2`.
     const NAME: Cow<'static, str>;
+    /// Name of the exact hash function, e.g. `BLAKE2s-256`, which tells apart members of a family
+    /// with different parameters. Defaults to [Name::NAME].
+    const ALGORITHM_ID: Cow<'static, str> = Self::NAME;
 }
 
 /// An API for hash functions that support incremental hashing.
@@ -24,42 +48,319 @@ pub trait Name {
 
 pub trait Hasher: Sized {
     type Hash: Hash<Self::NativeType>;
-    type NativeType: Sized + Eq;
+    type NativeType: Sized + Eq + Send + Sync;
+    /// A stack-allocated buffer of exactly `OUTPUT_SIZE` native elements, e.g. `[u8; 32]`, see
+    /// [Hasher::finalize_into_digest].
+    type Digest: AsRef<[Self::NativeType]> + AsMut<[Self::NativeType]> + Default;
 
     // Input size of the compression function.
     const BLOCK_SIZE: usize;
     const OUTPUT_SIZE: usize;
 
+    /// Name of the hash family, used to tag which hash function a commitment was made with.
+    const NAME: Cow<'static, str> = <Self::Hash as Name>::NAME;
+    /// Name of the exact hash function, see [Name::ALGORITHM_ID].
+    const ALGORITHM_ID: Cow<'static, str> = <Self::Hash as Name>::ALGORITHM_ID;
+
     fn new() -> Self;
 
     fn reset(&mut self);
 
     fn update(&mut self, data: &[Self::NativeType]);
 
+    /// Absorbs `data` and returns the hasher, for chaining, e.g. `H::new().chain(a).chain(b)`.
+    fn chain(mut self, data: &[Self::NativeType]) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Like [Hasher::chain], for a hasher behind a mutable reference.
+    fn chain_update(&mut self, data: &[Self::NativeType]) -> &mut Self {
+        self.update(data);
+        self
+    }
+
     fn finalize(self) -> Self::Hash;
 
     fn finalize_reset(&mut self) -> Self::Hash;
 
-    fn concat_and_hash(v1: &Self::Hash, v2: &Self::Hash) -> Self::Hash {
+    /// Finalizes the hash into its raw native elements, i.e. bytes for byte hashers.
+    fn finalize_bytes(self) -> Vec<Self::NativeType> {
+        self.finalize().into()
+    }
+
+    /// Finalizes the hash directly into the start of `dst`, returning the number of elements
+    /// written, i.e. `OUTPUT_SIZE`.
+    fn finalize_into(self, dst: &mut [Self::NativeType]) -> Result<usize, error::Error>
+    where
+        Self::NativeType: Copy,
+    {
+        if dst.len() < Self::OUTPUT_SIZE {
+            return Err(BufferTooSmallError {
+                required: Self::OUTPUT_SIZE,
+                actual: dst.len(),
+            }
+            .into());
+        }
+        dst[..Self::OUTPUT_SIZE].copy_from_slice(self.finalize().as_ref());
+        Ok(Self::OUTPUT_SIZE)
+    }
+
+    /// Finalizes the hash into a [Hasher::Digest] buffer, without heap allocation.
+    fn finalize_into_digest(self) -> Self::Digest
+    where
+        Self::NativeType: Copy,
+    {
+        let mut digest = Self::Digest::default();
+        digest.as_mut().copy_from_slice(self.finalize().as_ref());
+        digest
+    }
+
+    /// Two-to-one compression of a pair of hashes, equal to hashing their concatenation.
+    /// Backends with a fixed-arity permutation (e.g. SIMD or algebraic hashes) may override this
+    /// to skip the generic block setup. Such backends should also override [Hasher::hash_nodes],
+    /// which is the domain-separated variant used by Merkle trees.
+    fn compress(left: &Self::Hash, right: &Self::Hash) -> Self::Hash {
         let mut hasher = Self::new();
-        hasher.update(v1.as_ref());
-        hasher.update(v2.as_ref());
+        hasher.update(left.as_ref());
+        hasher.update(right.as_ref());
         hasher.finalize()
     }
 
+    fn concat_and_hash(v1: &Self::Hash, v2: &Self::Hash) -> Self::Hash {
+        Self::compress(v1, v2)
+    }
+
     fn hash(data: &[Self::NativeType]) -> Self::Hash {
         let mut hasher = Self::new();
         hasher.update(data);
         hasher.finalize()
     }
 
+    /// Hashes the concatenation of `slices` without copying them into a single buffer, e.g. the
+    /// values of a row spread over several columns.
+    fn hash_slices(slices: &[&[Self::NativeType]]) -> Self::Hash {
+        let mut hasher = Self::new();
+        slices.iter().for_each(|slice| hasher.update(slice));
+        hasher.finalize()
+    }
+
+    /// Size of the pieces in which large inputs should be fed to [Hasher::update], see
+    /// [Hasher::hash_large]. Defaults to [Hasher::BLOCK_SIZE].
+    fn optimal_chunk_bytes() -> usize {
+        Self::BLOCK_SIZE
+    }
+
+    /// Hashes everything `reader` yields until its end, reading [Hasher::optimal_chunk_bytes]
+    /// bytes at a time. Equivalent to [Hasher::hash] on the whole input.
+    #[cfg(feature = "std")]
+    fn hash_large<R: io::Read>(reader: &mut R) -> io::Result<Self::Hash>
+    where
+        Self: Hasher<NativeType = u8>,
+    {
+        let mut hasher = Self::new();
+        let mut buffer = vec![0; Self::optimal_chunk_bytes()];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(hasher.finalize()),
+                Ok(n) => hasher.update(&buffer[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Hashes field elements in their canonical encoding, i.e. 4 little-endian bytes per element
+    /// for byte hashers, and the elements themselves for hashers native to the field.
+    fn hash_felts(felts: &[BaseField]) -> Self::Hash
+    where
+        BaseField: IntoSlice<Self::NativeType>,
+        Self::NativeType: Copy,
+    {
+        Self::hash(&Endianness::Little.encode_felts(felts)[..])
+    }
+
+    /// The hash of the empty input. Not to be confused with an all-zero hash value.
+    fn empty_hash() -> Self::Hash {
+        Self::hash(&[])
+    }
+
+    /// Hashes a Merkle leaf. The input is prefixed with [LEAF_DOMAIN_TAG], so a leaf can never
+    /// be confused with an internal node.
+    fn hash_leaf(data: &[Self::NativeType]) -> Self::Hash
+    where
+        Self::NativeType: From<u8>,
+    {
+        let mut hasher = Self::new();
+        hasher.update(&[LEAF_DOMAIN_TAG.into()]);
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    /// Hashes two child nodes into their Merkle parent. The input is prefixed with
+    /// [NODE_DOMAIN_TAG], so an internal node can never be confused with a leaf.
+    fn hash_nodes(left: &Self::Hash, right: &Self::Hash) -> Self::Hash
+    where
+        Self::NativeType: From<u8>,
+    {
+        let mut hasher = Self::new();
+        hasher.update(&[NODE_DOMAIN_TAG.into()]);
+        hasher.update(left.as_ref());
+        hasher.update(right.as_ref());
+        hasher.finalize()
+    }
+
+    /// Like [Hasher::hash_nodes], but orders the two children by their native representation
+    /// first, so the result doesn't depend on their order. Trees built this way have proofs
+    /// without direction bits, see [super::utils::verify_sorted_path], at the cost of not
+    /// authenticating the position of the opened leaf.
+    fn hash_pair_sorted(a: &Self::Hash, b: &Self::Hash) -> Self::Hash
+    where
+        Self::NativeType: From<u8> + Ord,
+    {
+        match a.as_ref() <= b.as_ref() {
+            true => Self::hash_nodes(a, b),
+            false => Self::hash_nodes(b, a),
+        }
+    }
+
+    /// Hash many inputs of the same length.
+    /// A safe wrapper around [Hasher::hash_many_in_place], returns the hashes in the order of the
+    /// inputs. The output buffers are allocated here, and no inputs result in no hashes.
+    ///
+    /// Fails if the inputs are not of the same length, before hashing anything.
+    fn hash_many(inputs: &[&[Self::NativeType]]) -> Result<Vec<Self::Hash>, error::Error> {
+        let Some(single_input_length) = inputs.first().map(|input| input.len()) else {
+            return Ok(Vec::new());
+        };
+        if let Some((index, input)) = inputs
+            .iter()
+            .enumerate()
+            .find(|(_, input)| input.len() != single_input_length)
+        {
+            return Err(InputLengthError {
+                index,
+                length: input.len(),
+                expected: single_input_length,
+            }
+            .into());
+        }
+
+        let mut dst = (0..inputs.len())
+            .map(|_| Self::Digest::default())
+            .collect::<Vec<_>>();
+        let src_ptrs: Vec<*const Self::NativeType> =
+            inputs.iter().map(|input| input.as_ptr()).collect();
+        let dst_ptrs: Vec<*mut Self::NativeType> = dst
+            .iter_mut()
+            .map(|digest| digest.as_mut().as_mut_ptr())
+            .collect();
+
+        // Safe because inputs are of the same length, and every destination is a buffer of
+        // `OUTPUT_SIZE` elements.
+        unsafe { Self::hash_many_in_place(&src_ptrs, single_input_length, &dst_ptrs) };
+        dst.iter()
+            .map(|digest| Self::Hash::try_from_natives(digest.as_ref()))
+            .collect()
+    }
+
+    /// An infallible [Hasher::hash_many], for inputs known to be of the same length. Hashes into a
+    /// single buffer of `inputs.len() * OUTPUT_SIZE` elements with [Hasher::hash_many_in_place],
+    /// then splits it into hashes. No inputs result in no hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the inputs are not of the same length.
+    fn hash_many_owned(inputs: &[&[Self::NativeType]]) -> Vec<Self::Hash>
+    where
+        Self::NativeType: Copy + Default,
+    {
+        let Some(single_input_length) = inputs.first().map(|input| input.len()) else {
+            return Vec::new();
+        };
+        assert!(
+            inputs
+                .iter()
+                .all(|input| input.len() == single_input_length),
+            "Inputs are not of the same length!"
+        );
+
+        let mut dst = vec![Self::NativeType::default(); inputs.len() * Self::OUTPUT_SIZE];
+        let src_ptrs: Vec<*const Self::NativeType> =
+            inputs.iter().map(|input| input.as_ptr()).collect();
+        let dst_ptrs: Vec<*mut Self::NativeType> = dst
+            .chunks_exact_mut(Self::OUTPUT_SIZE)
+            .map(|chunk| chunk.as_mut_ptr())
+            .collect();
+
+        // Safe because inputs are of the same length, and every destination is a distinct chunk of
+        // `OUTPUT_SIZE` elements.
+        unsafe { Self::hash_many_in_place(&src_ptrs, single_input_length, &dst_ptrs) };
+        dst.chunks_exact(Self::OUTPUT_SIZE)
+            .map(|chunk| {
+                Self::Hash::try_from(chunk)
+                    .unwrap_or_else(|_| panic!("Chunks have OUTPUT_SIZE elements."))
+            })
+            .collect()
+    }
+
+    /// Lazily hashes every input, reusing a single hasher. Unlike [Hasher::hash_many], the inputs
+    /// may be of different lengths, and no intermediate buffer is allocated.
+    fn hash_many_iter<'a>(
+        inputs: impl Iterator<Item = &'a [Self::NativeType]> + 'a,
+    ) -> impl Iterator<Item = Self::Hash> + 'a
+    where
+        Self: 'a,
+    {
+        let mut hasher = Self::new();
+        inputs.map(move |input| {
+            hasher.update(input);
+            hasher.finalize_reset()
+        })
+    }
+
+    /// Hashes every `stride` elements of `flat_input`, returning the concatenated hashes, i.e.
+    /// `flat_input.len() / stride * OUTPUT_SIZE` elements. A safe, pointer-free counterpart of
+    /// [Hasher::hash_many_in_place] for callers that can only pass flat buffers, e.g. wasm-bindgen
+    /// glue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is 0 or doesn't divide the length of `flat_input`.
+    fn hash_many_flat(flat_input: &[Self::NativeType], stride: usize) -> Vec<Self::NativeType>
+    where
+        Self::NativeType: Copy,
+    {
+        assert_ne!(stride, 0, "Attempted hashing with a stride of 0!");
+        assert_eq!(
+            flat_input.len() % stride,
+            0,
+            "Input length is not a multiple of the stride!"
+        );
+        let mut hasher = Self::new();
+        let mut output = Vec::with_capacity(flat_input.len() / stride * Self::OUTPUT_SIZE);
+        for input in flat_input.chunks_exact(stride) {
+            hasher.update(input);
+            output.extend_from_slice(hasher.finalize_reset().as_ref());
+        }
+        output
+    }
+
     /// Hash many inputs of the same length.
     /// Writes output directly to corresponding pointers in dst.
     ///
     /// # Safety
     ///
     /// Inputs must be of the same size. output locations must all point to valid, allocated and
-    /// distinct locations in memory.
+    /// distinct locations in memory. They may be uninitialized, e.g. [core::mem::MaybeUninit]
+    /// buffers: implementations only write the outputs through the pointers, without reading them
+    /// or creating references to them.
+    ///
+    /// # Determinism
+    ///
+    /// Implementations may hash the inputs in parallel, but the hash of `data[i]` must be written
+    /// to `dst[i]` and nowhere else. The output must not depend on the number of threads or on the
+    /// scheduling order, as Merkle roots are built on top of it.
     // TODO(Ohad): make redundent and delete.
     unsafe fn hash_many_in_place(
         data: &[*const Self::NativeType],
@@ -68,6 +369,66 @@ pub trait Hasher: Sized {
     );
 }
 
+/// Writes `hash` to an output of [Hasher::hash_many_in_place], which may be uninitialized, so no
+/// reference to it is created.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes of `hash.len()` elements, and must not overlap `hash`.
+pub(crate) unsafe fn write_output<T: Copy>(hash: &[T], dst: *mut T) {
+    core::ptr::copy_nonoverlapping(hash.as_ptr(), dst, hash.len());
+}
+
+/// An object-safe facade of byte [Hasher]s, e.g. to pick the hash function of a proof at runtime
+/// from a tag in its header, see [Hasher::ALGORITHM_ID]. Implemented for every [Hasher] of bytes.
+pub trait DynHasher {
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize_boxed(self: Box<Self>) -> Vec<u8>;
+
+    /// The name of the hash family, equal to [Hasher::NAME].
+    // Returns a `Cow`, as a reference can't be taken to an associated const with drop glue.
+    fn name(&self) -> Cow<'static, str>;
+
+    /// The name of the exact hash function, equal to [Hasher::ALGORITHM_ID].
+    fn algorithm_id(&self) -> Cow<'static, str>;
+}
+
+impl<H: Hasher<NativeType = u8>> DynHasher for H {
+    fn update(&mut self, data: &[u8]) {
+        Hasher::update(self, data);
+    }
+
+    fn finalize_boxed(self: Box<Self>) -> Vec<u8> {
+        Hasher::finalize(*self).into()
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        H::NAME
+    }
+
+    fn algorithm_id(&self) -> Cow<'static, str> {
+        H::ALGORITHM_ID
+    }
+}
+
+/// Conversion of native elements into a [Hash], reporting failures as an [error::Error].
+/// Implemented for every type converting from `&[NativeType]` with an error that converts into
+/// [error::Error], including the `From<&[NativeType]>` types, whose conversions can't fail.
+pub trait TryFromNatives<NativeType>: Sized {
+    fn try_from_natives(natives: &[NativeType]) -> Result<Self, error::Error>;
+}
+
+impl<NativeType, T> TryFromNatives<NativeType> for T
+where
+    T: for<'a> TryFrom<&'a [NativeType]>,
+    for<'a> <T as TryFrom<&'a [NativeType]>>::Error: Into<error::Error>,
+{
+    fn try_from_natives(natives: &[NativeType]) -> Result<Self, error::Error> {
+        T::try_from(natives).map_err(Into::into)
+    }
+}
+
 pub trait Hash<NativeType: Sized + Eq>:
     Copy
     + Default
@@ -78,9 +439,236 @@ pub trait Hash<NativeType: Sized + Eq>:
     + Into<Vec<NativeType>>
     + TryFrom<Vec<NativeType>>
     + AsRef<[NativeType]>
-    + for<'a> From<&'a [NativeType]>
+    + for<'a> TryFrom<&'a [NativeType]>
+    + TryFromNatives<NativeType>
     + Send
     + Sync
     + 'static
 {
+    /// Compares two hashes without returning early on the first differing element, to avoid leaking
+    /// timing information when comparing against untrusted values.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.as_ref()
+            .iter()
+            .zip(other.as_ref())
+            .fold(Choice::from(1), |acc, (a, b)| {
+                acc & Choice::from((a == b) as u8)
+            })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HashConversionError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for HashConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { expected, actual } = self;
+        write!(
+            f,
+            "Invalid hash length: expected {expected} elements, got {actual}."
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HashConversionError {}
+
+/// The inputs of [Hasher::hash_many] must all be of the same length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputLengthError {
+    pub index: usize,
+    pub length: usize,
+    pub expected: usize,
+}
+
+impl fmt::Display for InputLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            index,
+            length,
+            expected,
+        } = self;
+        write!(f, "Input {index} has length {length}, expected {expected}.")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InputLengthError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferTooSmallError {
+    pub required: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for BufferTooSmallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { required, actual } = self;
+        write!(
+            f,
+            "Destination buffer too small: required {required} elements, got {actual}."
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmallError {}
+
+#[cfg(test)]
+pub mod tests {
+    use std::borrow::Cow;
+    use std::fmt;
+    use std::mem::MaybeUninit;
+
+    use super::{Hash, Hasher, TryFromNatives};
+
+    /// Checks that hashes and [Hasher::Digest] buffers hold exactly `OUTPUT_SIZE` native elements.
+    /// The destination buffers of e.g. [Hasher::hash_many_in_place] are sized from `OUTPUT_SIZE`,
+    /// so a mismatch would make hashes overlap or overrun them.
+    pub fn check_output_size<H: Hasher>() {
+        let hash = H::empty_hash();
+        assert_eq!(hash.as_ref().len(), H::OUTPUT_SIZE);
+        assert_eq!(Into::<Vec<_>>::into(hash).len(), H::OUTPUT_SIZE);
+        assert_eq!(
+            std::mem::size_of::<H::Hash>(),
+            H::OUTPUT_SIZE * std::mem::size_of::<H::NativeType>()
+        );
+        assert_eq!(H::Digest::default().as_ref().len(), H::OUTPUT_SIZE);
+    }
+
+    /// Checks the contract of [Hasher] that the trait can't enforce, on inputs cut from `data`.
+    /// Every backend should call this from its tests.
+    pub fn hasher_conformance<H: Hasher>(data: &[H::NativeType])
+    where
+        H::NativeType: Copy + Default,
+    {
+        assert!(data.len() >= 8, "Not enough data to cut inputs from.");
+        let (x, y) = data.split_at(data.len() / 2);
+        check_output_size::<H>();
+
+        // Incremental hashing is independent of how the input is split.
+        for split in [0, 1, x.len() / 2, x.len()] {
+            let mut hasher = H::new();
+            hasher.update(&x[..split]);
+            hasher.update(&x[split..]);
+            assert_eq!(hasher.finalize(), H::hash(x));
+        }
+
+        assert_eq!(H::hash_slices(&[x, y]), H::hash(data));
+        assert_eq!(H::hash_slices(&[]), H::empty_hash());
+
+        // `finalize_reset` leaves the hasher in the state of a fresh one.
+        let mut hasher = H::new();
+        hasher.update(x);
+        assert_eq!(hasher.finalize_reset(), H::hash(x));
+        hasher.update(y);
+        assert_eq!(hasher.finalize_reset(), H::hash(y));
+        assert_eq!(hasher.finalize(), H::empty_hash());
+
+        // `reset` discards the data fed so far.
+        let mut hasher = H::new();
+        hasher.update(x);
+        hasher.reset();
+        hasher.update(y);
+        assert_eq!(hasher.finalize(), H::hash(y));
+
+        // Batched hashing matches hashing every input separately.
+        let inputs = data.chunks_exact(4).collect::<Vec<_>>();
+        let hashes = inputs
+            .iter()
+            .map(|input| H::hash(input))
+            .collect::<Vec<_>>();
+        assert_eq!(H::hash_many(&inputs), Ok(hashes.clone()));
+        assert_eq!(H::hash_many_owned(&inputs), hashes);
+        assert_eq!(H::hash_many_owned(&[]), Vec::new());
+
+        // Destinations of `hash_many_in_place` may be uninitialized.
+        let mut dst = vec![MaybeUninit::<H::NativeType>::uninit(); inputs.len() * H::OUTPUT_SIZE];
+        let src_ptrs = inputs
+            .iter()
+            .map(|input| input.as_ptr())
+            .collect::<Vec<_>>();
+        let dst_ptrs = dst
+            .chunks_exact_mut(H::OUTPUT_SIZE)
+            .map(|chunk| chunk.as_mut_ptr() as *mut H::NativeType)
+            .collect::<Vec<_>>();
+        let outputs = unsafe {
+            H::hash_many_in_place(&src_ptrs, inputs[0].len(), &dst_ptrs);
+            core::slice::from_raw_parts(dst.as_ptr() as *const H::NativeType, dst.len())
+        };
+        let expected = hashes
+            .iter()
+            .flat_map(|hash| hash.as_ref().iter().copied())
+            .collect::<Vec<_>>();
+        assert!(outputs == expected);
+        assert_eq!(
+            H::hash_many_iter(inputs.iter().copied()).collect::<Vec<_>>(),
+            hashes
+        );
+
+        let hash = H::hash(x);
+        let mut hasher = H::new();
+        hasher.update(x);
+        assert!(hasher.finalize_bytes() == hash.into());
+    }
+
+    /// A hash converting from slices with [From], and from vectors with its own error, as was
+    /// required of [Hash] types before conversions reported [super::error::Error]s.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    struct XorHash([u8; 4]);
+
+    #[derive(Debug)]
+    struct WrongLength;
+
+    impl super::Name for XorHash {
+        const NAME: Cow<'static, str> = Cow::Borrowed("XOR");
+    }
+
+    impl fmt::Display for XorHash {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+
+    impl From<XorHash> for Vec<u8> {
+        fn from(hash: XorHash) -> Self {
+            hash.0.to_vec()
+        }
+    }
+
+    impl AsRef<[u8]> for XorHash {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl From<&[u8]> for XorHash {
+        fn from(value: &[u8]) -> Self {
+            let mut hash = [0; 4];
+            value.iter().enumerate().for_each(|(i, x)| hash[i % 4] ^= x);
+            Self(hash)
+        }
+    }
+
+    impl TryFrom<Vec<u8>> for XorHash {
+        type Error = WrongLength;
+
+        fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+            Ok(Self(value.try_into().map_err(|_| WrongLength)?))
+        }
+    }
+
+    impl Hash<u8> for XorHash {}
+
+    #[test]
+    fn infallible_hash_conversion_test() {
+        assert_eq!(
+            XorHash::try_from_natives(&[1, 2, 3, 4, 5]),
+            Ok(XorHash([4, 2, 3, 4]))
+        );
+        assert!(XorHash::try_from(vec![1, 2, 3]).is_err());
+    }
 }
@@ -0,0 +1,80 @@
+use std::sync::Mutex;
+
+use super::hasher::Hasher;
+
+/// A pool of reusable hasher states, for hashing many inputs in tight loops without constructing
+/// a new hasher per input. Hashers are returned to the pool in a reset state, so the pool can be
+/// shared between threads.
+pub struct HasherPool<H: Hasher> {
+    hashers: Mutex<Vec<H>>,
+}
+
+impl<H: Hasher> HasherPool<H> {
+    pub fn new() -> Self {
+        Self {
+            hashers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Hashes `data` with a pooled hasher, constructing one only if the pool is empty.
+    pub fn hash(&self, data: &[H::NativeType]) -> H::Hash {
+        let mut hasher = self.take();
+        hasher.update(data);
+        let hash = hasher.finalize_reset();
+        self.hashers.lock().unwrap().push(hasher);
+        hash
+    }
+
+    /// Number of idle hashers in the pool.
+    pub fn len(&self) -> usize {
+        self.hashers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn take(&self) -> H {
+        self.hashers.lock().unwrap().pop().unwrap_or_else(H::new)
+    }
+}
+
+impl<H: Hasher> Default for HasherPool<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HasherPool;
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::hasher::Hasher;
+
+    #[test]
+    fn pool_hash_test() {
+        let pool = HasherPool::<Blake2sHasher>::new();
+
+        for input in [&b"a"[..], b"", b"abc", b"a"] {
+            assert_eq!(pool.hash(input), Blake2sHasher::hash(input));
+        }
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn pool_hash_shared_test() {
+        let pool = HasherPool::<Blake2sHasher>::new();
+
+        std::thread::scope(|s| {
+            for i in 0..4u8 {
+                let pool = &pool;
+                s.spawn(move || {
+                    for j in 0..100u8 {
+                        assert_eq!(pool.hash(&[i, j]), Blake2sHasher::hash(&[i, j]));
+                    }
+                });
+            }
+        });
+        assert!(pool.len() <= 4);
+    }
+}
@@ -0,0 +1,182 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use super::hasher::Hasher;
+use super::utils::hash_root;
+
+/// A merkle tree that is built by appending leaves one by one.
+///
+/// Only the "frontier" is stored: the roots of the maximal complete subtrees of all leaves but the
+/// last one, at most one per level. Appending a leaf is O(log n), and the root is computed (and
+/// cached until the next append) in O(log n).
+///
+/// Roots are consistent with [super::merkle_tree::MerkleTree] committing to one row per leaf: when
+/// the number of leaves is not a power of two, the tree is padded by duplicating the last leaf, and
+/// the root binds the number of leaves, see [hash_root].
+pub struct IncrementalMerkleTree<H: Hasher> {
+    /// `frontier[i]` is the root of a complete subtree of 2^i leaves, if the i-th bit of the
+    /// number of leaves preceding `last_leaf` is set.
+    frontier: Vec<Option<H::Hash>>,
+    last_leaf: Option<H::Hash>,
+    n_leaves: usize,
+    root: Cell<Option<H::Hash>>,
+}
+
+impl<H: Hasher> IncrementalMerkleTree<H>
+where
+    H::NativeType: From<u8>,
+{
+    pub fn new() -> Self {
+        Self {
+            frontier: Vec::new(),
+            last_leaf: None,
+            n_leaves: 0,
+            root: Cell::new(None),
+        }
+    }
+
+    /// Appends a leaf, hashed with [Hasher::hash_leaf].
+    pub fn push_leaf(&mut self, leaf: &[H::NativeType]) {
+        if let Some(prev_leaf) = self.last_leaf.take() {
+            self.push_to_frontier(prev_leaf);
+        }
+        self.last_leaf = Some(H::hash_leaf(leaf));
+        self.n_leaves += 1;
+        self.root.set(None);
+    }
+
+    pub fn n_leaves(&self) -> usize {
+        self.n_leaves
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_leaves == 0
+    }
+
+    /// Returns the root of the tree, or `None` if no leaf was pushed.
+    pub fn root(&self) -> Option<H::Hash> {
+        if self.root.get().is_none() {
+            self.root
+                .set(self.last_leaf.map(|last_leaf| self.compute_root(last_leaf)));
+        }
+        self.root.get()
+    }
+
+    /// Merges a leaf hash into the frontier of the leaves preceding it, like a binary counter.
+    fn push_to_frontier(&mut self, leaf_hash: H::Hash) {
+        let mut carry = leaf_hash;
+        for node in self.frontier.iter_mut() {
+            match node.take() {
+                Some(left) => carry = H::hash_nodes(&left, &carry),
+                None => {
+                    *node = Some(carry);
+                    return;
+                }
+            }
+        }
+        self.frontier.push(Some(carry));
+    }
+
+    /// Walks up the path of the last leaf. Its left siblings are frontier nodes, and its right
+    /// siblings are padding subtrees, consisting solely of copies of the last leaf.
+    fn compute_root(&self, last_leaf: H::Hash) -> H::Hash {
+        let log_padded_size = self.n_leaves.next_power_of_two().ilog2() as usize;
+        let mut node = last_leaf;
+        let mut padding = last_leaf;
+        for level in 0..log_padded_size {
+            node = match self.frontier.get(level).copied().flatten() {
+                Some(left) => H::hash_nodes(&left, &node),
+                None => H::hash_nodes(&node, &padding),
+            };
+            padding = H::hash_nodes(&padding, &padding);
+        }
+        hash_root::<H>(&node, self.n_leaves)
+    }
+}
+
+impl<H: Hasher> Default for IncrementalMerkleTree<H>
+where
+    H::NativeType: From<u8>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncrementalMerkleTree;
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::blake3_hash::Blake3Hasher;
+    use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::merkle_tree::MerkleTree;
+    use crate::commitment_scheme::utils::hash_root;
+    use crate::core::fields::m31::M31;
+    use crate::core::fields::IntoSlice;
+
+    // 16 columns of M31 fill a 64 byte block, i.e. the reference tree holds one row per leaf.
+    const N_COLUMNS: usize = 16;
+
+    fn test_row(i: usize) -> Vec<M31> {
+        (0..N_COLUMNS)
+            .map(|j| M31::from_u32_unchecked((i * N_COLUMNS + j) as u32))
+            .collect()
+    }
+
+    fn reference_root<H: Hasher>(n_rows: usize) -> H::Hash
+    where
+        M31: IntoSlice<H::NativeType>,
+        H::NativeType: Copy + From<u8>,
+    {
+        let columns = (0..N_COLUMNS)
+            .map(|j| (0..n_rows).map(|i| test_row(i)[j]).collect())
+            .collect();
+        MerkleTree::<M31, H>::commit(columns).root()
+    }
+
+    #[test]
+    fn empty_tree_test() {
+        let tree = IncrementalMerkleTree::<Blake2sHasher>::new();
+
+        assert!(tree.is_empty());
+        assert_eq!(tree.root(), None);
+    }
+
+    #[test]
+    fn single_leaf_test() {
+        let mut tree = IncrementalMerkleTree::<Blake2sHasher>::new();
+
+        tree.push_leaf(b"leaf");
+
+        assert_eq!(tree.n_leaves(), 1);
+        assert_eq!(
+            tree.root(),
+            Some(hash_root::<Blake2sHasher>(
+                &Blake2sHasher::hash_leaf(b"leaf"),
+                1
+            ))
+        );
+    }
+
+    #[test]
+    fn interleaved_push_and_root_test() {
+        let mut tree = IncrementalMerkleTree::<Blake2sHasher>::new();
+
+        for i in 0..37 {
+            tree.push_leaf(IntoSlice::<u8>::into_slice(&test_row(i)));
+
+            assert_eq!(tree.root(), Some(reference_root::<Blake2sHasher>(i + 1)));
+        }
+    }
+
+    #[test]
+    fn root_is_cached_test() {
+        let mut tree = IncrementalMerkleTree::<Blake3Hasher>::new();
+        (0..5).for_each(|i| tree.push_leaf(IntoSlice::<u8>::into_slice(&test_row(i))));
+
+        let root = tree.root();
+
+        assert_eq!(tree.root(), root);
+        assert_eq!(root, Some(reference_root::<Blake3Hasher>(5)));
+    }
+}
@@ -0,0 +1,182 @@
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use sha3::{Digest, Keccak256};
+
+use super::error::Error;
+use super::hasher::{write_output, HashConversionError};
+
+// Wrapper for the keccak256 hash type.
+#[derive(Clone, Copy, PartialEq, Default, Eq)]
+pub struct Keccak256Hash([u8; 32]);
+
+impl From<Keccak256Hash> for Vec<u8> {
+    fn from(value: Keccak256Hash) -> Self {
+        Vec::from(value.0)
+    }
+}
+
+impl TryFrom<&[u8]> for Keccak256Hash {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(value.try_into().map_err(|_| HashConversionError {
+            expected: 32,
+            actual: value.len(),
+        })?))
+    }
+}
+
+impl TryFrom<Vec<u8>> for Keccak256Hash {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
+    }
+}
+
+impl AsRef<[u8]> for Keccak256Hash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Keccak256Hash> for [u8; 32] {
+    fn from(val: Keccak256Hash) -> Self {
+        val.0
+    }
+}
+
+impl fmt::Display for Keccak256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for Keccak256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Keccak256Hash as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl super::hasher::Name for Keccak256Hash {
+    const NAME: alloc::borrow::Cow<'static, str> = alloc::borrow::Cow::Borrowed("KECCAK256");
+}
+
+impl super::hasher::Hash<u8> for Keccak256Hash {}
+
+// Wrapper for the keccak256 Hashing functionalities.
+#[derive(Clone, Debug)]
+pub struct KeccakHasher {
+    state: Keccak256,
+}
+
+impl super::hasher::Hasher for KeccakHasher {
+    type Hash = Keccak256Hash;
+    const BLOCK_SIZE: usize = 136;
+    const OUTPUT_SIZE: usize = 32;
+    type NativeType = u8;
+    type Digest = [u8; 32];
+
+    fn new() -> Self {
+        Self {
+            state: Keccak256::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        Digest::reset(&mut self.state);
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.state, data);
+    }
+
+    fn finalize(self) -> Keccak256Hash {
+        Keccak256Hash(self.state.finalize().into())
+    }
+
+    fn finalize_reset(&mut self) -> Keccak256Hash {
+        Keccak256Hash(self.state.finalize_reset().into())
+    }
+
+    unsafe fn hash_many_in_place(
+        data: &[*const u8],
+        single_input_length_bytes: usize,
+        dst: &[*mut u8],
+    ) {
+        data.iter()
+            .map(|p| core::slice::from_raw_parts(*p, single_input_length_bytes))
+            .zip(dst)
+            .for_each(|(input, out)| {
+                let mut hasher = Keccak256::new();
+                Digest::update(&mut hasher, input);
+                write_output(&hasher.finalize(), *out);
+            })
+    }
+}
+
+/// Allows using the hasher as an [io::Write] sink, equivalent to calling `update` on the written
+/// data.
+#[cfg(feature = "std")]
+impl io::Write for KeccakHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        super::hasher::Hasher::update(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeccakHasher;
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
+    use crate::commitment_scheme::hasher::Hasher;
+
+    #[test]
+    fn single_hash_test() {
+        let hash_a = KeccakHasher::hash(b"a");
+        assert_eq!(
+            hash_a.to_string(),
+            "3ac225168df54212a25c1c01fd35bebfea408fdac2e31ddd6f80a4bbf9a5f1cb"
+        );
+    }
+
+    #[test]
+    fn hash_many_xof_test() {
+        let input1 = "a";
+        let input2 = "b";
+        let input_arr = [input1.as_ptr(), input2.as_ptr()];
+
+        let mut out = [0_u8; 96];
+        let out_ptrs = [out.as_mut_ptr(), unsafe { out.as_mut_ptr().add(42) }];
+        unsafe { KeccakHasher::hash_many_in_place(&input_arr, 1, &out_ptrs) };
+
+        assert_eq!("3ac225168df54212a25c1c01fd35bebfea408fdac2e31ddd6f80a4bbf9a5f1cb00000000000000000000b5553de315e0edf504d9150af82dafa5c4667fa618ed0a6f19c69b41166c551000000000000000000000000000000000000000000000", hex::encode(out));
+    }
+
+    #[test]
+    fn hash_state_test() {
+        let mut state = KeccakHasher::new();
+        state.update(b"a");
+        state.update(b"b");
+        let hash = state.finalize_reset();
+        let hash_empty = state.finalize();
+
+        assert_eq!(hash.to_string(), KeccakHasher::hash(b"ab").to_string());
+        assert_eq!(
+            hash_empty.to_string(),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<KeccakHasher>(&(0..64).collect::<Vec<u8>>());
+    }
+}
@@ -1,106 +1,360 @@
-use std::fmt::{self, Display};
-use std::iter::Peekable;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::iter::Peekable;
 
 use itertools::Itertools;
 
-use super::hasher::Hasher;
-use crate::core::fields::IntoSlice;
+use super::error;
+use super::hasher::{Hash, Hasher};
+use super::serialize::Endianness;
+use super::utils::{hash_root, hash_salted_leaf, hash_tagged_nodes, layer_hashes};
+use crate::core::fields::m31::{BaseField, N_BYTES_FELT, P};
+use crate::core::fields::{ExtensionOf, IntoSlice};
+
+/// Version of the [MerkleDecommitment::to_bytes] and [CompressedBatchProof::to_bytes] encodings.
+pub const DECOMMITMENT_ENCODING_VERSION: u8 = 4;
+
+/// Tag following the version in the encoding of a [MerkleDecommitment].
+const DECOMMITMENT_TAG: u8 = 0;
+/// Tag following the version in the encoding of a [CompressedBatchProof], so that neither format
+/// parses as the other.
+const COMPRESSED_BATCH_PROOF_TAG: u8 = 1;
 
 /// Merkle proof of queried indices.
 /// Used for storing a merkle proof of a given tree and a set of queries.
 /// # Attributes
 /// * `leaf_blocks` - The blocks of the bottom layer of the tree.
 /// * `layers` - Internal nodes(hashes) of a specific layer in the tree. nodes that are not in a
-///   queried path, or nodes with both children in the queried path are excluded.
+///   queried path, or nodes with both children in the queried path are excluded. In a tree of arity
+///   `ARITY`, a layer holds up to `ARITY - 1` siblings per queried path.
 /// * `n_rows_in_leaf_block` - The number of trace-rows packed in each leaf block.
+/// * `n_rows` - The number of rows of the committed trace, excluding padding, which the root binds,
+///   see [super::merkle_tree::MerkleTree::root].
+/// * `salts` - The salts of the leaf blocks, if the tree is salted, see
+///   [super::merkle_tree::MerkleTree::build_salted]. Empty otherwise.
+/// * `endianness` - The byte order the leaf values are hashed in, see
+///   [super::merkle_tree::MerkleTreeConfig]. Not part of the encoding of the decommitment.
 // TODO(Ohad): derive Debug.
 #[derive(Default, Debug)]
-pub struct MerkleDecommitment<T: Sized + Display, H: Hasher> {
+pub struct MerkleDecommitment<T: Sized + Display, H: Hasher, const ARITY: usize = 2> {
     pub leaf_blocks: Vec<Vec<T>>,
     pub layers: Vec<Vec<H::Hash>>,
     pub n_rows_in_leaf_block: usize,
+    pub n_rows: usize,
+    pub salts: Vec<H::Hash>,
+    pub endianness: Endianness,
     queries: Vec<usize>,
 }
 
-impl<T: Sized + Display + Copy, H: Hasher> MerkleDecommitment<T, H>
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Sized + Display, H: Hasher, const ARITY: usize> arbitrary::Arbitrary<'a>
+    for MerkleDecommitment<T, H, ARITY>
+where
+    T: arbitrary::Arbitrary<'a>,
+    H::Hash: arbitrary::Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self {
+            leaf_blocks: u.arbitrary()?,
+            layers: u.arbitrary()?,
+            n_rows_in_leaf_block: u.arbitrary()?,
+            n_rows: u.arbitrary()?,
+            salts: u.arbitrary()?,
+            endianness: Endianness::Little,
+            queries: u
+                .arbitrary::<Vec<usize>>()?
+                .into_iter()
+                .sorted()
+                .dedup()
+                .collect(),
+        })
+    }
+}
+
+impl<T: Sized + Display + Copy, H: Hasher, const ARITY: usize> MerkleDecommitment<T, H, ARITY>
 where
     T: IntoSlice<H::NativeType>,
 {
+    /// The queries are sorted and deduplicated, as the leaf blocks are in the order of the sorted
+    /// queries.
     pub fn new(
         leaf_blocks: Vec<Vec<T>>,
         layers: Vec<Vec<H::Hash>>,
         n_rows_in_leaf_block: usize,
-        queries: Vec<usize>,
+        mut queries: Vec<usize>,
+        n_rows: usize,
     ) -> Self {
+        queries.sort_unstable();
+        queries.dedup();
         Self {
             leaf_blocks,
             layers,
             n_rows_in_leaf_block,
+            n_rows,
+            salts: Vec::new(),
+            endianness: Endianness::Little,
             queries,
         }
     }
 
+    /// Sets the salts of the leaf blocks, one per leaf block, for decommitments of salted trees.
+    pub fn with_salts(mut self, salts: Vec<H::Hash>) -> Self {
+        self.salts = salts;
+        self
+    }
+
+    /// Sets the byte order the leaf values are hashed in, for decommitments of trees built with
+    /// [Endianness::Big].
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
     pub fn height(&self) -> usize {
         self.layers.len() + 1
     }
 
-    // TODO(Ohad): Implement more verbose error handling.
-    /// Verifies the decommitment against a given root. Queries are assumed to be sorted.
-    pub fn verify(&self, root: H::Hash, queries: &[usize]) -> bool {
+    /// Verifies the decommitment against a given root. See [Self::verify_detailed] for the reason
+    /// of a failure.
+    pub fn verify(&self, root: H::Hash, queries: &[usize]) -> bool
+    where
+        H::NativeType: Copy + From<u8>,
+    {
+        self.verify_detailed(root, queries).is_ok()
+    }
+
+    /// Verifies the decommitment against a given root, reporting why verification failed.
+    ///
+    /// A verifier only learns that the recomputed root differs from the commitment. To find the
+    /// first diverging node, check the decommitment against the prover's tree with
+    /// [super::merkle_tree::MerkleTree::check_decommitment].
+    pub fn verify_detailed(&self, root: H::Hash, queries: &[usize]) -> Result<(), error::Error>
+    where
+        H::NativeType: Copy + From<u8>,
+    {
+        Ok(self.check_root(root, queries)?)
+    }
+
+    /// Verifies the decommitment against a given root, then checks that it opens the row at
+    /// `query` to `expected_row`, e.g. values recomputed by the verifier. A
+    /// [MerkleVerifyError::ValueMismatch] means the path is valid but holds other values.
+    pub fn verify_opening_with_values(
+        &self,
+        root: H::Hash,
+        query: usize,
+        expected_row: &[T],
+    ) -> Result<(), error::Error>
+    where
+        T: PartialEq,
+        H::NativeType: Copy + From<u8>,
+    {
+        Ok(self.check_opening(root, query, expected_row)?)
+    }
+
+    fn check_root(&self, root: H::Hash, queries: &[usize]) -> Result<(), MerkleVerifyError>
+    where
+        H::NativeType: Copy + From<u8>,
+    {
+        let recomputed_root = self.recompute_root(queries, |_, _, _| Ok(()))?;
+        if bool::from(recomputed_root.ct_eq(&root)) {
+            Ok(())
+        } else {
+            Err(MerkleVerifyError::RootMismatch)
+        }
+    }
+
+    fn check_opening(
+        &self,
+        root: H::Hash,
+        query: usize,
+        expected_row: &[T],
+    ) -> Result<(), MerkleVerifyError>
+    where
+        T: PartialEq,
+        H::NativeType: Copy + From<u8>,
+    {
+        self.check_root(root, &self.queries)?;
+        if !self.queries.contains(&query) {
+            return Err(MerkleVerifyError::QueryNotOpened { query });
+        }
+        // Leaf blocks are in the order of the queries, sorted by `new`.
+        let leaf_block_index = self
+            .queries
+            .iter()
+            .map(|q| q / self.n_rows_in_leaf_block)
+            .dedup()
+            .position(|block| block == query / self.n_rows_in_leaf_block)
+            .unwrap();
+        let leaf_block = &self.leaf_blocks[leaf_block_index];
+        let n_elements_in_row = leaf_block.len() / self.n_rows_in_leaf_block;
+        let row_start = query % self.n_rows_in_leaf_block * n_elements_in_row;
+        if leaf_block[row_start..row_start + n_elements_in_row] != *expected_row {
+            return Err(MerkleVerifyError::ValueMismatch { query });
+        }
+        Ok(())
+    }
+
+    /// Checks that the shape of the decommitment is consistent with `queries` before any hashing,
+    /// as the decommitment may come from a malicious prover: the queries must fit in a tree of
+    /// [Self::height], and every layer must hold exactly the siblings of the queried paths.
+    /// Returns the sorted and deduplicated queried leaf blocks.
+    fn validate_shape(&self, queries: &[usize]) -> Result<Vec<usize>, MerkleVerifyError> {
+        if self.n_rows_in_leaf_block == 0 {
+            return Err(MerkleVerifyError::EmptyLeafBlocks);
+        }
+        if let Some(index) = self
+            .leaf_blocks
+            .iter()
+            .position(|leaf_block| leaf_block.len() % self.n_rows_in_leaf_block != 0)
+        {
+            return Err(MerkleVerifyError::InvalidLeafBlockLength { index });
+        }
+        let n_leaf_blocks = u32::try_from(self.layers.len())
+            .ok()
+            .and_then(|n_layers| ARITY.checked_pow(n_layers))
+            .ok_or(MerkleVerifyError::TooManyLayers {
+                n_layers: self.layers.len(),
+            })?;
+        if let Some(query) = queries
+            .iter()
+            .find(|q| *q / self.n_rows_in_leaf_block >= n_leaf_blocks)
+        {
+            return Err(MerkleVerifyError::QueryOutOfRange { query: *query });
+        }
+
         let leaf_block_queries = queries
             .iter()
+            .sorted_unstable()
             .map(|q| q / self.n_rows_in_leaf_block)
             .dedup()
             .collect::<Vec<usize>>();
-        assert_eq!(self.leaf_blocks.len(), leaf_block_queries.len());
+        if self.leaf_blocks.len() != leaf_block_queries.len() {
+            return Err(MerkleVerifyError::LeafBlocksCountMismatch {
+                expected: leaf_block_queries.len(),
+                actual: self.leaf_blocks.len(),
+            });
+        }
+        if !self.salts.is_empty() && self.salts.len() != self.leaf_blocks.len() {
+            return Err(MerkleVerifyError::SaltsCountMismatch {
+                expected: self.leaf_blocks.len(),
+                actual: self.salts.len(),
+            });
+        }
+
+        let n_witnesses = witness_counts::<ARITY>(&leaf_block_queries, self.layers.len());
+        for (layer_index, (layer, n_siblings)) in self.layers.iter().zip(n_witnesses).enumerate() {
+            match layer.len().cmp(&n_siblings) {
+                core::cmp::Ordering::Less => {
+                    return Err(MerkleVerifyError::MissingWitness { layer: layer_index })
+                }
+                core::cmp::Ordering::Greater => {
+                    return Err(MerkleVerifyError::UnusedWitness { layer: layer_index })
+                }
+                core::cmp::Ordering::Equal => {}
+            }
+        }
+        Ok(leaf_block_queries)
+    }
+
+    /// Recomputes the root from the decommitment, calling `check_node` with the layer, index and
+    /// hash of every recomputed node, bottom layer first. Layer 0 holds the leaf hashes. The top
+    /// node is hashed with [Self::n_rows] into the root, see [hash_root].
+    pub(crate) fn recompute_root(
+        &self,
+        queries: &[usize],
+        mut check_node: impl FnMut(usize, usize, &H::Hash) -> Result<(), MerkleVerifyError>,
+    ) -> Result<H::Hash, MerkleVerifyError>
+    where
+        H::NativeType: Copy + From<u8>,
+    {
+        let leaf_block_queries = self.validate_shape(queries)?;
 
         let mut curr_hashes = self
             .leaf_blocks
             .iter()
-            .map(|leaf_block| H::hash(<T as IntoSlice<H::NativeType>>::into_slice(leaf_block)))
+            .enumerate()
+            .map(|(i, leaf_block)| {
+                let salt = self.salts.get(i).map_or(&[][..], |salt| salt.as_ref());
+                hash_salted_leaf::<H>(&self.endianness.encode_felts(leaf_block), salt)
+            })
             .collect::<Vec<H::Hash>>();
+        for (index, hash) in leaf_block_queries.iter().zip(&curr_hashes) {
+            check_node(0, *index, hash)?;
+        }
 
-        let mut layer_queries = leaf_block_queries.clone();
-        for layer in self.layers.iter() {
-            let mut next_layer_hashes = Vec::<H::Hash>::new();
-            let mut query_iter = layer_queries.iter().enumerate().peekable();
+        let mut layer_queries = leaf_block_queries;
+        for (layer_index, layer) in self.layers.iter().enumerate() {
+            let parent_queries = layer_queries
+                .iter()
+                .map(|q| q / ARITY)
+                .dedup()
+                .collect::<Vec<usize>>();
+            let mut queried_nodes = layer_queries.iter().zip(&curr_hashes).peekable();
             let mut layer_iter = layer.iter();
+            let mut next_layer_hashes = Vec::<H::Hash>::with_capacity(parent_queries.len());
 
-            while let Some((i, q)) = query_iter.next() {
-                let mut f = || -> Option<_> {
-                    if *q % 2 != 0 {
-                        // Right child.
-                        return Some(H::concat_and_hash(layer_iter.next()?, curr_hashes.get(i)?));
-                    }
-                    match query_iter.peek() {
-                        Some((_, next_q)) if *q + 1 == **next_q => {
-                            query_iter.next();
-                            Some(H::concat_and_hash(
-                                curr_hashes.get(i)?,
-                                curr_hashes.get(i + 1)?,
-                            ))
-                        }
-                        _ => Some(H::concat_and_hash(curr_hashes.get(i)?, layer_iter.next()?)),
-                    }
-                };
-                next_layer_hashes.push(f().expect("Error verifying proof!"));
+            for parent in &parent_queries {
+                let children = (parent * ARITY..(parent + 1) * ARITY)
+                    .map(|child| match queried_nodes.next_if(|(q, _)| **q == child) {
+                        Some((_, hash)) => Some(*hash),
+                        None => layer_iter.next().copied(),
+                    })
+                    .collect::<Option<Vec<H::Hash>>>()
+                    .ok_or(MerkleVerifyError::MissingWitness { layer: layer_index })?;
+                let parent_hash = hash_tagged_nodes::<H>(&children);
+                check_node(layer_index + 1, *parent, &parent_hash)?;
+                next_layer_hashes.push(parent_hash);
+            }
+            if layer_iter.next().is_some() {
+                return Err(MerkleVerifyError::UnusedWitness { layer: layer_index });
             }
-            assert!(layer_iter.next().is_none());
             curr_hashes = next_layer_hashes;
-            layer_queries = layer_queries
-                .iter()
-                .map(|q| q / 2)
-                .dedup()
-                .collect::<Vec<usize>>();
+            layer_queries = parent_queries;
+        }
+        if layer_queries != [0] {
+            return Err(MerkleVerifyError::MissingLayers);
+        }
+        Ok(hash_root::<H>(&curr_hashes[0], self.n_rows))
+    }
+
+    /// Compresses the decommitment by storing every distinct witness hash once, see
+    /// [CompressedBatchProof].
+    pub fn compress(&self) -> CompressedBatchProof<T, H, ARITY>
+    where
+        H::NativeType: Ord,
+    {
+        let mut hashes = Vec::new();
+        let mut hash_indices = BTreeMap::<&[H::NativeType], u32>::new();
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|hash| {
+                        *hash_indices.entry(hash.as_ref()).or_insert_with(|| {
+                            hashes.push(*hash);
+                            (hashes.len() - 1) as u32
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        CompressedBatchProof {
+            leaf_blocks: self.leaf_blocks.clone(),
+            hashes,
+            layers,
+            n_rows_in_leaf_block: self.n_rows_in_leaf_block,
+            n_rows: self.n_rows,
+            salts: self.salts.clone(),
+            endianness: self.endianness,
+            queries: self.queries.clone(),
         }
-        assert_eq!(
-            layer_queries.into_iter().collect::<Vec<usize>>(),
-            vec![0_usize]
-        );
-        assert_eq!(curr_hashes.len(), 1);
-        curr_hashes[0].into() == root.into()
     }
 
+    /// Returns the queried rows, in the (sorted and deduplicated) order of the decommitted queries.
     pub fn values(&self) -> impl Iterator<Item = Vec<T>> + '_ {
         QueriedValuesIterator {
             query_iterator: self.queries.iter(),
@@ -112,9 +366,527 @@ where
     }
 }
 
+impl<T: ExtensionOf<BaseField> + Display, H: Hasher<NativeType = u8>, const ARITY: usize>
+    MerkleDecommitment<T, H, ARITY>
+{
+    /// Encodes the decommitment in a stable binary format, independent of serde.
+    ///
+    /// All integers are little-endian, lengths are u32 and queries and `n_rows` are u64:
+    /// `version: u8 | tag: u8 | depth | n_queries | queries | n_rows_in_leaf_block | n_rows |
+    /// n_leaf_blocks | leaf_block_len | leaf values | for each of the depth layers: hashes |
+    /// n_salts | salts`.
+    /// Leaf values are encoded as their little-endian M31 words, as hashed in the tree. The number
+    /// of hashes of every layer, like the direction of every node, derives from the queries, so
+    /// a decommitment of a single query costs `depth * OUTPUT_SIZE` bytes over its header. The tag
+    /// tells this encoding apart from that of a [CompressedBatchProof].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layers don't hold exactly the siblings of the queried paths, as generated by
+    /// [super::merkle_tree::MerkleTree::generate_decommitment].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let leaf_block_queries = leaf_block_queries(&self.queries, self.n_rows_in_leaf_block)
+            .expect("Queried leaf blocks must hold at least one row.");
+        assert!(
+            self.layers.iter().map(Vec::len).eq(witness_counts::<ARITY>(
+                &leaf_block_queries,
+                self.layers.len()
+            )),
+            "The layers don't hold the siblings of the queried paths!"
+        );
+
+        let mut bytes = write_header(
+            DECOMMITMENT_TAG,
+            self.layers.len(),
+            &self.queries,
+            self.n_rows_in_leaf_block,
+            self.n_rows,
+            &self.leaf_blocks,
+        );
+        self.layers
+            .iter()
+            .flatten()
+            .for_each(|hash| bytes.extend_from_slice(hash.as_ref()));
+        write_hashes::<H>(&mut bytes, &self.salts);
+        bytes
+    }
+
+    /// Decodes a decommitment encoded by [MerkleDecommitment::to_bytes]. Never panics on malformed
+    /// input.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, error::Error> {
+        let mut reader = ByteReader(bytes);
+        let header = reader.read_header::<T, ARITY>(DECOMMITMENT_TAG)?;
+        let leaf_block_queries = leaf_block_queries(&header.queries, header.n_rows_in_leaf_block)
+            .ok_or(DecommitmentParseError::EmptyLeafBlock)?;
+        let layers = witness_counts::<ARITY>(&leaf_block_queries, header.depth)
+            .into_iter()
+            .map(|n_hashes| {
+                Ok(layer_hashes::<H>(
+                    reader.take_many(n_hashes, H::OUTPUT_SIZE)?,
+                ))
+            })
+            .collect::<Result<_, DecommitmentParseError>>()?;
+        let salts = reader.read_hashes::<H>()?;
+        reader.finish()?;
+        Ok(Self::new(
+            header.leaf_blocks,
+            layers,
+            header.n_rows_in_leaf_block,
+            header.queries,
+            header.n_rows,
+        )
+        .with_salts(salts))
+    }
+}
+
+/// A [MerkleDecommitment] in which every distinct witness hash is stored exactly once, and every
+/// layer refers to its witnesses by their index in [CompressedBatchProof::hashes]. Witnesses of a
+/// decommitment never repeat a node, but different nodes often have equal hashes, e.g. subtrees of
+/// padding rows or of constant columns, which then only cost an index.
+///
+/// Hashes are stored in the order of their first occurrence in the decommitment, bottom layer
+/// first, so equal decommitments compress to identical proofs.
+#[derive(Debug)]
+pub struct CompressedBatchProof<T: Sized + Display, H: Hasher, const ARITY: usize = 2> {
+    pub leaf_blocks: Vec<Vec<T>>,
+    pub hashes: Vec<H::Hash>,
+    /// The index in `hashes` of every witness of every layer, in the order of
+    /// [MerkleDecommitment::layers].
+    pub layers: Vec<Vec<u32>>,
+    pub n_rows_in_leaf_block: usize,
+    pub n_rows: usize,
+    pub salts: Vec<H::Hash>,
+    pub endianness: Endianness,
+    queries: Vec<usize>,
+}
+
+impl<T: Sized + Display + Copy, H: Hasher, const ARITY: usize> CompressedBatchProof<T, H, ARITY>
+where
+    T: IntoSlice<H::NativeType>,
+{
+    /// Rebuilds the uncompressed decommitment, failing on an index out of the range of `hashes`.
+    pub fn decompress(&self) -> Result<MerkleDecommitment<T, H, ARITY>, error::Error> {
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(layer, indices)| {
+                indices
+                    .iter()
+                    .map(|i| self.hashes.get(*i as usize).copied())
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(MerkleVerifyError::MissingWitness { layer })
+            })
+            .collect::<Result<_, MerkleVerifyError>>()?;
+        Ok(MerkleDecommitment::new(
+            self.leaf_blocks.clone(),
+            layers,
+            self.n_rows_in_leaf_block,
+            self.queries.clone(),
+            self.n_rows,
+        )
+        .with_salts(self.salts.clone())
+        .with_endianness(self.endianness))
+    }
+
+    pub fn verify(&self, root: H::Hash, queries: &[usize]) -> bool
+    where
+        H::NativeType: Copy + From<u8>,
+    {
+        self.verify_detailed(root, queries).is_ok()
+    }
+
+    pub fn verify_detailed(&self, root: H::Hash, queries: &[usize]) -> Result<(), error::Error>
+    where
+        H::NativeType: Copy + From<u8>,
+    {
+        self.decompress()?.verify_detailed(root, queries)
+    }
+}
+
+impl<T: ExtensionOf<BaseField> + Display, H: Hasher<NativeType = u8>, const ARITY: usize>
+    CompressedBatchProof<T, H, ARITY>
+{
+    /// Encodes the proof with the header of [MerkleDecommitment::to_bytes] under its own tag,
+    /// except that the layers are replaced by the distinct hashes followed by the indices of every
+    /// layer:
+    /// `version: u8 | tag: u8 | depth | n_queries | queries | n_rows_in_leaf_block | n_rows |
+    /// n_leaf_blocks | leaf_block_len | leaf values | n_hashes | hashes | for each of the depth
+    /// layers: (n_indices | u32 indices) | n_salts | salts`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = write_header(
+            COMPRESSED_BATCH_PROOF_TAG,
+            self.layers.len(),
+            &self.queries,
+            self.n_rows_in_leaf_block,
+            self.n_rows,
+            &self.leaf_blocks,
+        );
+        write_hashes::<H>(&mut bytes, &self.hashes);
+        for layer in &self.layers {
+            write_len(&mut bytes, layer.len());
+            layer
+                .iter()
+                .for_each(|index| bytes.extend_from_slice(&index.to_le_bytes()));
+        }
+        write_hashes::<H>(&mut bytes, &self.salts);
+        bytes
+    }
+
+    /// Decodes a proof encoded by [CompressedBatchProof::to_bytes]. Never panics on malformed
+    /// input. Indices are only checked against the hashes when decompressing.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, error::Error> {
+        let mut reader = ByteReader(bytes);
+        let header = reader.read_header::<T, ARITY>(COMPRESSED_BATCH_PROOF_TAG)?;
+        let hashes = reader.read_hashes::<H>()?;
+        let layers = (0..header.depth)
+            .map(|_| {
+                let n_indices = reader.read_len()?;
+                Ok(reader
+                    .take_many(n_indices, 4)?
+                    .chunks_exact(4)
+                    .map(|i| u32::from_le_bytes(i.try_into().unwrap()))
+                    .collect())
+            })
+            .collect::<Result<_, DecommitmentParseError>>()?;
+        let salts = reader.read_hashes::<H>()?;
+        reader.finish()?;
+        Ok(Self {
+            leaf_blocks: header.leaf_blocks,
+            hashes,
+            layers,
+            n_rows_in_leaf_block: header.n_rows_in_leaf_block,
+            n_rows: header.n_rows,
+            salts,
+            endianness: Endianness::Little,
+            queries: header.queries,
+        })
+    }
+}
+
+/// Stateless verifier entry point, for light clients that got `root` out-of-band and check a few
+/// openings without any tree. Every opening is a query, the row it is expected to open to, and a
+/// decommitment containing the query, checked with
+/// [MerkleDecommitment::verify_opening_with_values].
+///
+/// Stops at the first invalid opening, reported as an [error::Error::InvalidOpening] with its index
+/// in `openings`.
+pub fn stateless_verify<T, H: Hasher, const ARITY: usize>(
+    root: H::Hash,
+    openings: &[(usize, Vec<T>, MerkleDecommitment<T, H, ARITY>)],
+) -> Result<(), error::Error>
+where
+    T: Sized + Display + Copy + PartialEq + IntoSlice<H::NativeType>,
+    H::NativeType: Copy + From<u8>,
+{
+    openings
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, (query, row, decommitment))| {
+            decommitment
+                .check_opening(root, *query, row)
+                .map_err(|error| error::Error::InvalidOpening { index, error })
+        })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleVerifyError {
+    LeafBlocksCountMismatch { expected: usize, actual: usize },
+    SaltsCountMismatch { expected: usize, actual: usize },
+    MissingWitness { layer: usize },
+    UnusedWitness { layer: usize },
+    MissingLayers,
+    NodeMismatch { layer: usize, index: usize },
+    RootMismatch,
+    QueryNotOpened { query: usize },
+    ValueMismatch { query: usize },
+    EmptyLeafBlocks,
+    InvalidLeafBlockLength { index: usize },
+    TooManyLayers { n_layers: usize },
+    QueryOutOfRange { query: usize },
+}
+
+impl fmt::Display for MerkleVerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LeafBlocksCountMismatch { expected, actual } => write!(
+                f,
+                "Expected {expected} leaf blocks for the queries, got {actual}."
+            ),
+            Self::SaltsCountMismatch { expected, actual } => write!(
+                f,
+                "Expected {expected} salts for the leaf blocks, got {actual}."
+            ),
+            Self::MissingWitness { layer } => write!(f, "Missing witness hashes in layer {layer}."),
+            Self::UnusedWitness { layer } => write!(f, "Unused witness hashes in layer {layer}."),
+            Self::MissingLayers => write!(f, "Too few layers to reach the root."),
+            Self::NodeMismatch { layer, index } => write!(
+                f,
+                "Node {index} of layer {layer} differs from the committed tree."
+            ),
+            Self::RootMismatch => write!(f, "The recomputed root differs from the commitment."),
+            Self::QueryNotOpened { query } => {
+                write!(f, "Query {query} is not opened by the decommitment.")
+            }
+            Self::ValueMismatch { query } => write!(
+                f,
+                "The opened row of query {query} differs from the expected values."
+            ),
+            Self::EmptyLeafBlocks => write!(f, "Leaf blocks must hold at least one row."),
+            Self::InvalidLeafBlockLength { index } => {
+                write!(f, "Leaf block {index} doesn't hold a whole number of rows.")
+            }
+            Self::TooManyLayers { n_layers } => {
+                write!(f, "{n_layers} layers exceed the largest supported tree.")
+            }
+            Self::QueryOutOfRange { query } => write!(
+                f,
+                "Query {query} is out of range for the height of the decommitment."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleVerifyError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecommitmentParseError {
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidFieldElement,
+    TrailingBytes,
+    EmptyLeafBlock,
+    TooManyLayers(usize),
+    UnexpectedTag(u8),
+}
+
+impl fmt::Display for DecommitmentParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => {
+                write!(f, "Unsupported decommitment encoding version {v}.")
+            }
+            Self::UnexpectedEnd => write!(f, "Unexpected end of input."),
+            Self::InvalidFieldElement => write!(f, "Invalid field element."),
+            Self::TrailingBytes => write!(f, "Unexpected bytes after the end of the decommitment."),
+            Self::EmptyLeafBlock => write!(f, "Empty leaf blocks."),
+            Self::TooManyLayers(v) => write!(f, "{v} layers exceed the largest supported tree."),
+            Self::UnexpectedTag(v) => write!(f, "Unexpected encoding tag {v}."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecommitmentParseError {}
+
+/// The sorted and deduplicated leaf blocks of the given queries, or `None` if there are queries but
+/// leaf blocks hold no rows.
+fn leaf_block_queries(queries: &[usize], n_rows_in_leaf_block: usize) -> Option<Vec<usize>> {
+    if n_rows_in_leaf_block == 0 && !queries.is_empty() {
+        return None;
+    }
+    Some(
+        queries
+            .iter()
+            .sorted_unstable()
+            .map(|q| q / n_rows_in_leaf_block)
+            .dedup()
+            .collect(),
+    )
+}
+
+/// The number of witnesses in each of the `depth` layers of a decommitment of the given leaf
+/// blocks: the siblings of the queried paths that are not on a queried path themselves.
+fn witness_counts<const ARITY: usize>(leaf_block_queries: &[usize], depth: usize) -> Vec<usize> {
+    let mut layer_queries = leaf_block_queries.to_vec();
+    (0..depth)
+        .map(|_| {
+            let parent_queries = layer_queries
+                .iter()
+                .map(|q| q / ARITY)
+                .dedup()
+                .collect::<Vec<usize>>();
+            let n_witnesses = parent_queries.len() * ARITY - layer_queries.len();
+            layer_queries = parent_queries;
+            n_witnesses
+        })
+        .collect()
+}
+
+/// The fields leading the encodings of both [MerkleDecommitment] and [CompressedBatchProof], see
+/// [MerkleDecommitment::to_bytes].
+struct EncodingHeader<T> {
+    depth: usize,
+    queries: Vec<usize>,
+    n_rows_in_leaf_block: usize,
+    n_rows: usize,
+    leaf_blocks: Vec<Vec<T>>,
+}
+
+/// Encodes an [EncodingHeader] of the format of the given tag.
+fn write_header<T: ExtensionOf<BaseField>>(
+    tag: u8,
+    depth: usize,
+    queries: &[usize],
+    n_rows_in_leaf_block: usize,
+    n_rows: usize,
+    leaf_blocks: &[Vec<T>],
+) -> Vec<u8> {
+    let leaf_block_len = leaf_blocks.first().map_or(0, Vec::len);
+    assert!(leaf_blocks.iter().all(|b| b.len() == leaf_block_len));
+
+    let mut bytes = vec![DECOMMITMENT_ENCODING_VERSION, tag];
+    write_len(&mut bytes, depth);
+    write_len(&mut bytes, queries.len());
+    queries
+        .iter()
+        .for_each(|q| bytes.extend_from_slice(&(*q as u64).to_le_bytes()));
+    write_len(&mut bytes, n_rows_in_leaf_block);
+    bytes.extend_from_slice(&(n_rows as u64).to_le_bytes());
+    write_len(&mut bytes, leaf_blocks.len());
+    write_len(&mut bytes, leaf_block_len);
+    leaf_blocks
+        .iter()
+        .for_each(|block| bytes.extend_from_slice(&Endianness::Little.encode_felts(block)));
+    bytes
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&u32::try_from(len).unwrap().to_le_bytes())
+}
+
+/// Writes the number of hashes followed by the hashes.
+fn write_hashes<H: Hasher<NativeType = u8>>(bytes: &mut Vec<u8>, hashes: &[H::Hash]) {
+    write_len(bytes, hashes.len());
+    hashes
+        .iter()
+        .for_each(|hash| bytes.extend_from_slice(hash.as_ref()));
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n_bytes: usize) -> Result<&'a [u8], DecommitmentParseError> {
+        if n_bytes > self.0.len() {
+            return Err(DecommitmentParseError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.0.split_at(n_bytes);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    /// Takes `n_items` items of `item_size` bytes, checking the length before any allocation.
+    fn take_many(
+        &mut self,
+        n_items: usize,
+        item_size: usize,
+    ) -> Result<&'a [u8], DecommitmentParseError> {
+        let n_bytes = n_items
+            .checked_mul(item_size)
+            .ok_or(DecommitmentParseError::UnexpectedEnd)?;
+        self.take(n_bytes)
+    }
+
+    /// Reads the header written by [write_header] with the given tag. Layers may hold no hashes,
+    /// so the depth is bounded by the largest supported tree instead of by the length of the
+    /// input.
+    fn read_header<T: ExtensionOf<BaseField>, const ARITY: usize>(
+        &mut self,
+        tag: u8,
+    ) -> Result<EncodingHeader<T>, DecommitmentParseError> {
+        let version = self.take(1)?[0];
+        if version != DECOMMITMENT_ENCODING_VERSION {
+            return Err(DecommitmentParseError::UnsupportedVersion(version));
+        }
+        let encoded_tag = self.take(1)?[0];
+        if encoded_tag != tag {
+            return Err(DecommitmentParseError::UnexpectedTag(encoded_tag));
+        }
+        let depth = self.read_len()?;
+        if ARITY.checked_pow(depth as u32).is_none() {
+            return Err(DecommitmentParseError::TooManyLayers(depth));
+        }
+        let n_queries = self.read_len()?;
+        let queries = self
+            .take_many(n_queries, 8)?
+            .chunks_exact(8)
+            .map(|q| u64::from_le_bytes(q.try_into().unwrap()) as usize)
+            .collect();
+        let n_rows_in_leaf_block = self.read_len()?;
+        let n_rows = u64::from_le_bytes(self.take(8)?.try_into().unwrap()) as usize;
+        let leaf_blocks = self.read_leaf_blocks::<T>()?;
+        Ok(EncodingHeader {
+            depth,
+            queries,
+            n_rows_in_leaf_block,
+            n_rows,
+            leaf_blocks,
+        })
+    }
+
+    /// Reads hashes written by [write_hashes].
+    fn read_hashes<H: Hasher<NativeType = u8>>(
+        &mut self,
+    ) -> Result<Vec<H::Hash>, DecommitmentParseError> {
+        let n_hashes = self.read_len()?;
+        Ok(layer_hashes::<H>(self.take_many(n_hashes, H::OUTPUT_SIZE)?))
+    }
+
+    /// Checks that the whole input was read.
+    fn finish(&self) -> Result<(), DecommitmentParseError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(DecommitmentParseError::TrailingBytes),
+        }
+    }
+
+    fn read_len(&mut self) -> Result<usize, DecommitmentParseError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    /// Reads the number of leaf blocks, their common length and their values.
+    fn read_leaf_blocks<T: ExtensionOf<BaseField>>(
+        &mut self,
+    ) -> Result<Vec<Vec<T>>, DecommitmentParseError> {
+        let n_leaf_blocks = self.read_len()?;
+        let leaf_block_len = self.read_len()?;
+        if n_leaf_blocks > 0 && leaf_block_len == 0 {
+            return Err(DecommitmentParseError::EmptyLeafBlock);
+        }
+        let leaf_block_size = leaf_block_len
+            .checked_mul(core::mem::size_of::<T>())
+            .ok_or(DecommitmentParseError::UnexpectedEnd)?;
+        self.take_many(n_leaf_blocks, leaf_block_size)?
+            .chunks_exact(leaf_block_size.max(1))
+            .map(felts_from_bytes)
+            .collect()
+    }
+}
+
+/// Inverse of [IntoSlice::into_slice] for field elements, rejecting non-canonical M31 words.
+fn felts_from_bytes<T: ExtensionOf<BaseField>>(
+    bytes: &[u8],
+) -> Result<Vec<T>, DecommitmentParseError> {
+    if bytes
+        .chunks_exact(N_BYTES_FELT)
+        .any(|word| u32::from_le_bytes(word.try_into().unwrap()) >= P)
+    {
+        return Err(DecommitmentParseError::InvalidFieldElement);
+    }
+    let mut felts = vec![T::zero(); bytes.len() / core::mem::size_of::<T>()];
+    // Safe because field elements consist of M31 words, which were checked to be canonical, with
+    // the same memory layout [IntoSlice] relies on.
+    unsafe {
+        core::slice::from_raw_parts_mut(felts.as_mut_ptr() as *mut u8, bytes.len())
+            .copy_from_slice(bytes);
+    }
+    Ok(felts)
+}
+
 pub struct QueriedValuesIterator<'a, T: Sized + Display> {
-    query_iterator: std::slice::Iter<'a, usize>,
-    leaf_block_iterator: Peekable<std::slice::Iter<'a, Vec<T>>>,
+    query_iterator: core::slice::Iter<'a, usize>,
+    leaf_block_iterator: Peekable<core::slice::Iter<'a, Vec<T>>>,
     current_leaf_block_index: usize,
     n_elements_in_row: usize,
     n_rows_in_leaf_block: usize,
@@ -150,19 +922,21 @@ impl<'a, T: Sized + Display> QueriedValuesIterator<'a, T> {
     }
 }
 
-impl<T: Sized + Display, H: Hasher> fmt::Display for MerkleDecommitment<T, H> {
+impl<T: Sized + Display, H: Hasher, const ARITY: usize> fmt::Display
+    for MerkleDecommitment<T, H, ARITY>
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.layers.last() {
             Some(_) => {
                 self.leaf_blocks.iter().enumerate().for_each(|(i, leaf)| {
-                    f.write_str(&std::format!("\nLeaf #[{:}]: ", i)).unwrap();
+                    f.write_str(&alloc::format!("\nLeaf #[{:}]: ", i)).unwrap();
                     leaf.iter()
-                        .for_each(|node| f.write_str(&std::format!("{} ", node)).unwrap());
+                        .for_each(|node| f.write_str(&alloc::format!("{} ", node)).unwrap());
                 });
                 for (i, layer) in self.layers.iter().enumerate().take(self.layers.len()) {
-                    f.write_str(&std::format!("\nLayer #[{}]:", i))?;
+                    f.write_str(&alloc::format!("\nLayer #[{}]:", i))?;
                     for (j, node) in layer.iter().enumerate() {
-                        f.write_str(&std::format!("\n\tNode #[{}]: {}", j, node))?;
+                        f.write_str(&alloc::format!("\n\tNode #[{}]: {}", j, node))?;
                     }
                 }
             }
@@ -174,12 +948,23 @@ impl<T: Sized + Display, H: Hasher> fmt::Display for MerkleDecommitment<T, H> {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
+    use super::{
+        stateless_verify, CompressedBatchProof, DecommitmentParseError, MerkleDecommitment,
+        MerkleVerifyError, COMPRESSED_BATCH_PROOF_TAG, DECOMMITMENT_ENCODING_VERSION,
+        DECOMMITMENT_TAG,
+    };
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
     use crate::commitment_scheme::blake3_hash::Blake3Hasher;
+    use crate::commitment_scheme::error::Error;
     use crate::commitment_scheme::hasher::Hasher;
     use crate::commitment_scheme::merkle_tree::MerkleTree;
     use crate::commitment_scheme::utils::tests::generate_test_queries;
-    use crate::commitment_scheme::utils::ColumnArray;
-    use crate::core::fields::m31::M31;
+    use crate::commitment_scheme::utils::{hash_root, hash_tagged_nodes, ColumnArray};
+    use crate::core::fields::m31::{M31, P};
+    use crate::core::fields::qm31::QM31;
+    use crate::core::fields::IntoSlice;
 
     #[test]
     pub fn verify_test() {
@@ -218,6 +1003,514 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verify_detailed_test() {
+        let trace: ColumnArray<M31> = vec![(0..1024).map(M31::from_u32_unchecked).collect(); 4];
+        let tree = MerkleTree::<M31, Blake3Hasher>::commit(trace);
+        let query = 777;
+        let decommitment = tree.generate_decommitment(vec![query]);
+        let leaf_block_index = query / decommitment.n_rows_in_leaf_block;
+        let mut bad_sibling_decommitment = tree.generate_decommitment(vec![query]);
+        let mut missing_sibling_decommitment = tree.generate_decommitment(vec![query]);
+
+        bad_sibling_decommitment.layers[2][0] = Blake3Hasher::hash(&[0]);
+        missing_sibling_decommitment.layers[1].clear();
+
+        assert_eq!(decommitment.verify_detailed(tree.root(), &[query]), Ok(()));
+        assert_eq!(tree.check_decommitment(&decommitment, &[query]), Ok(()));
+        assert_eq!(
+            bad_sibling_decommitment.verify_detailed(tree.root(), &[query]),
+            Err(Error::VerificationFailed(MerkleVerifyError::RootMismatch))
+        );
+        assert_eq!(
+            tree.check_decommitment(&bad_sibling_decommitment, &[query]),
+            Err(Error::VerificationFailed(MerkleVerifyError::NodeMismatch {
+                layer: 3,
+                index: leaf_block_index >> 3
+            }))
+        );
+        assert_eq!(
+            missing_sibling_decommitment.verify_detailed(tree.root(), &[query]),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::MissingWitness { layer: 1 }
+            ))
+        );
+        assert!(!missing_sibling_decommitment.verify(tree.root(), &[query]));
+    }
+
+    #[test]
+    fn malformed_decommitment_test() {
+        let trace: ColumnArray<M31> = vec![(0..1024).map(M31::from_u32_unchecked).collect(); 4];
+        let tree = MerkleTree::<M31, Blake3Hasher>::commit(trace);
+        let queries = [5, 777];
+        let decommitment = || tree.generate_decommitment(queries.to_vec());
+        let verify = |decommitment: &MerkleDecommitment<M31, Blake3Hasher>, queries: &[usize]| {
+            decommitment.verify_detailed(tree.root(), queries)
+        };
+
+        let mut no_rows = decommitment();
+        no_rows.n_rows_in_leaf_block = 0;
+        assert_eq!(
+            verify(&no_rows, &queries),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::EmptyLeafBlocks
+            ))
+        );
+
+        let mut partial_leaf_block = decommitment();
+        partial_leaf_block.leaf_blocks[1].pop();
+        assert_eq!(
+            verify(&partial_leaf_block, &queries),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::InvalidLeafBlockLength { index: 1 }
+            ))
+        );
+
+        let mut too_deep = decommitment();
+        too_deep.layers.resize(usize::BITS as usize, Vec::new());
+        assert_eq!(
+            verify(&too_deep, &queries),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::TooManyLayers {
+                    n_layers: usize::BITS as usize
+                }
+            ))
+        );
+
+        let mut too_shallow = decommitment();
+        too_shallow.layers.pop();
+        assert_eq!(
+            verify(&too_shallow, &queries),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::QueryOutOfRange { query: 777 }
+            ))
+        );
+        assert_eq!(
+            verify(&decommitment(), &[5, usize::MAX]),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::QueryOutOfRange { query: usize::MAX }
+            ))
+        );
+
+        let mut extra_sibling = decommitment();
+        extra_sibling.layers[2].push(Blake3Hasher::hash(&[0]));
+        assert_eq!(
+            verify(&extra_sibling, &queries),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::UnusedWitness { layer: 2 }
+            ))
+        );
+
+        let mut missing_sibling = decommitment();
+        missing_sibling.layers[1].clear();
+        assert_eq!(
+            verify(&missing_sibling, &queries),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::MissingWitness { layer: 1 }
+            ))
+        );
+        assert!(!missing_sibling.verify(tree.root(), &queries));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes = (0..=255).cycle().take(1024).collect::<Vec<u8>>();
+        let mut u = Unstructured::new(&bytes);
+
+        let decommitment = MerkleDecommitment::<M31, Blake2sHasher>::arbitrary(&mut u).unwrap();
+
+        assert!(decommitment
+            .leaf_blocks
+            .iter()
+            .flatten()
+            .all(|felt| felt.0 < P));
+    }
+
+    #[test]
+    fn bytes_round_trip_test() {
+        let trace: ColumnArray<M31> = vec![(0..256).map(M31::from_u32_unchecked).collect(); 3];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let queries = vec![3, 100, 101, 255];
+        let decommitment = tree.generate_decommitment(queries.clone());
+
+        let bytes = decommitment.to_bytes();
+        let parsed = MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(parsed.leaf_blocks, decommitment.leaf_blocks);
+        assert_eq!(parsed.layers, decommitment.layers);
+        assert_eq!(
+            parsed.values().collect_vec(),
+            decommitment.values().collect_vec()
+        );
+        assert!(parsed.verify(tree.root(), &queries));
+        assert_eq!(parsed.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn bytes_round_trip_secure_field_test() {
+        let trace: ColumnArray<QM31> = vec![
+            (0..64)
+                .map(|i| QM31::from(M31::from_u32_unchecked(i)))
+                .collect();
+            2
+        ];
+        let tree = MerkleTree::<QM31, Blake2sHasher>::commit(trace);
+        let decommitment = tree.generate_decommitment(vec![7, 40]);
+
+        let parsed =
+            MerkleDecommitment::<QM31, Blake2sHasher>::from_bytes(&decommitment.to_bytes())
+                .unwrap();
+
+        assert_eq!(parsed.leaf_blocks, decommitment.leaf_blocks);
+        assert!(parsed.verify(tree.root(), &[7, 40]));
+    }
+
+    #[test]
+    fn salted_test() {
+        // 16 rows of a single M31 column fill a leaf block, so both leaf blocks are identical.
+        let trace: ColumnArray<M31> = vec![vec![M31::from_u32_unchecked(7); 32]];
+        let salts = vec![Blake2sHasher::hash(b"salt0"), Blake2sHasher::hash(b"salt1")];
+        let tree = MerkleTree::<M31, Blake2sHasher>::build_salted(trace.clone(), salts.clone());
+        let unsalted_tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let queries = vec![3, 20];
+
+        let decommitment = tree.generate_decommitment(queries.clone());
+        let parsed =
+            MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(&decommitment.to_bytes()).unwrap();
+
+        let leaf_hashes = tree.layer(0);
+        assert_ne!(leaf_hashes[0], leaf_hashes[1]);
+        assert_eq!(unsalted_tree.layer(0)[0], unsalted_tree.layer(0)[1]);
+        assert_ne!(tree.root(), unsalted_tree.root());
+        assert_eq!(decommitment.salts, salts);
+        assert!(decommitment.verify(tree.root(), &queries));
+        assert!(parsed.verify(tree.root(), &queries));
+        assert_eq!(tree.check_decommitment(&decommitment, &queries), Ok(()));
+    }
+
+    #[test]
+    fn salted_wrong_salt_test() {
+        let trace: ColumnArray<M31> = vec![(0..64).map(M31::from_u32_unchecked).collect(); 2];
+        let salts = (0..8_u8).map(|i| Blake2sHasher::hash(&[i])).collect();
+        let tree = MerkleTree::<M31, Blake2sHasher>::build_salted(trace, salts);
+        let mut decommitment = tree.generate_decommitment(vec![5, 17]);
+
+        decommitment.salts[0] = Blake2sHasher::hash(b"wrong");
+
+        assert_eq!(
+            decommitment.verify_detailed(tree.root(), &[5, 17]),
+            Err(Error::VerificationFailed(MerkleVerifyError::RootMismatch))
+        );
+        decommitment.salts.pop();
+        assert_eq!(
+            decommitment.verify_detailed(tree.root(), &[5, 17]),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::SaltsCountMismatch {
+                    expected: 2,
+                    actual: 1
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn verify_opening_with_values_test() {
+        let trace: ColumnArray<M31> = (0..3)
+            .map(|i| (0..256).map(|j| M31::from_u32_unchecked(i * j)).collect())
+            .collect();
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        let decommitment = tree.generate_decommitment(vec![5, 17, 100]);
+        let row = |query: usize| trace.iter().map(|column| column[query]).collect_vec();
+
+        for query in [5, 17, 100] {
+            assert_eq!(
+                decommitment.verify_opening_with_values(tree.root(), query, &row(query)),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            decommitment.verify_opening_with_values(tree.root(), 17, &row(5)),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::ValueMismatch { query: 17 }
+            ))
+        );
+        assert_eq!(
+            decommitment.verify_opening_with_values(tree.root(), 6, &row(6)),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::QueryNotOpened { query: 6 }
+            ))
+        );
+        assert_eq!(
+            decommitment.verify_opening_with_values(Blake2sHasher::hash(b"root"), 5, &row(5)),
+            Err(Error::VerificationFailed(MerkleVerifyError::RootMismatch))
+        );
+    }
+
+    #[test]
+    fn verify_opening_with_reordered_queries_test() {
+        // A single column packs 16 rows per leaf block.
+        let trace: ColumnArray<M31> = vec![(0..64).map(M31::from_u32_unchecked).collect()];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let decommitment = tree.generate_decommitment(vec![5, 40]);
+        let mut bytes = decommitment.to_bytes();
+        // Swap the two queries, which follow the version, the tag, the depth and the number of
+        // queries.
+        let queries_start = 1 + 1 + 4 + 4;
+        bytes[queries_start..queries_start + 16].rotate_left(8);
+        let reordered_decommitments = [
+            MerkleDecommitment::<M31, Blake2sHasher>::new(
+                decommitment.leaf_blocks.clone(),
+                decommitment.layers.clone(),
+                decommitment.n_rows_in_leaf_block,
+                vec![40, 5],
+                decommitment.n_rows,
+            ),
+            MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(&bytes).unwrap(),
+        ];
+
+        for reordered in reordered_decommitments {
+            // Row 8 shares the leaf block of row 5, with the same offset as row 40 in its block.
+            assert_eq!(
+                reordered.verify_opening_with_values(tree.root(), 40, &[M31::from(8)]),
+                Err(Error::VerificationFailed(
+                    MerkleVerifyError::ValueMismatch { query: 40 }
+                ))
+            );
+            assert_eq!(
+                reordered.verify_opening_with_values(tree.root(), 40, &[M31::from(40)]),
+                Ok(())
+            );
+            assert_eq!(
+                stateless_verify(tree.root(), &[(40, vec![M31::from(8)], reordered)]),
+                Err(Error::InvalidOpening {
+                    index: 0,
+                    error: MerkleVerifyError::ValueMismatch { query: 40 }
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn stateless_verify_test() {
+        let trace: ColumnArray<M31> = (0..2)
+            .map(|i| (0..64).map(|j| M31::from_u32_unchecked(i + j)).collect())
+            .collect();
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        let row = |query: usize| trace.iter().map(|column| column[query]).collect_vec();
+        let opening =
+            |query: usize, row: Vec<M31>| (query, row, tree.generate_decommitment(vec![query]));
+        let mut openings = vec![opening(3, row(3)), opening(40, row(40))];
+
+        assert_eq!(stateless_verify(tree.root(), &openings), Ok(()));
+
+        openings.push(opening(12, row(13)));
+        openings.push(opening(63, row(63)));
+
+        assert_eq!(
+            stateless_verify(tree.root(), &openings),
+            Err(Error::InvalidOpening {
+                index: 2,
+                error: MerkleVerifyError::ValueMismatch { query: 12 }
+            })
+        );
+    }
+
+    #[test]
+    fn compressed_batch_proof_test() {
+        // Constant columns make every subtree of a layer equal.
+        let trace: ColumnArray<M31> = vec![vec![M31::from_u32_unchecked(3); 1 << 12]; 2];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let queries = generate_test_queries(30, 1 << 12);
+        let decommitment = tree.generate_decommitment(queries.clone());
+
+        let compressed = decommitment.compress();
+        let parsed =
+            CompressedBatchProof::<M31, Blake2sHasher>::from_bytes(&compressed.to_bytes()).unwrap();
+
+        // At most one distinct hash per layer.
+        assert!(compressed.hashes.len() <= decommitment.layers.len());
+        assert!(compressed.to_bytes().len() < decommitment.to_bytes().len());
+        assert!(decommitment.verify(tree.root(), &queries));
+        assert!(compressed.verify(tree.root(), &queries));
+        assert!(parsed.verify(tree.root(), &queries));
+        assert_eq!(parsed.to_bytes(), compressed.to_bytes());
+        assert_eq!(compressed.decompress().unwrap().layers, decommitment.layers);
+    }
+
+    #[test]
+    fn compressed_batch_proof_cross_parse_test() {
+        let trace: ColumnArray<M31> = vec![(0..256).map(M31::from_u32_unchecked).collect(); 2];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let decommitment = tree.generate_decommitment(vec![3, 100]);
+
+        assert_eq!(
+            CompressedBatchProof::<M31, Blake2sHasher>::from_bytes(&decommitment.to_bytes())
+                .unwrap_err(),
+            Error::InvalidDecommitment(DecommitmentParseError::UnexpectedTag(DECOMMITMENT_TAG))
+        );
+        assert_eq!(
+            MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(
+                &decommitment.compress().to_bytes()
+            )
+            .unwrap_err(),
+            Error::InvalidDecommitment(DecommitmentParseError::UnexpectedTag(
+                COMPRESSED_BATCH_PROOF_TAG
+            ))
+        );
+    }
+
+    #[test]
+    fn compressed_batch_proof_invalid_index_test() {
+        let trace: ColumnArray<M31> = vec![(0..256).map(M31::from_u32_unchecked).collect(); 2];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let mut compressed = tree.generate_decommitment(vec![3, 100]).compress();
+
+        compressed.layers[1][0] = compressed.hashes.len() as u32;
+
+        assert_eq!(
+            compressed.verify_detailed(tree.root(), &[3, 100]),
+            Err(Error::VerificationFailed(
+                MerkleVerifyError::MissingWitness { layer: 1 }
+            ))
+        );
+    }
+
+    #[test]
+    fn deep_decommitment_encoding_test() {
+        const DEPTH: usize = 20;
+        let query = 0b1011_0110_1100_1010_0101;
+        let leaf_block = vec![M31::from_u32_unchecked(7)];
+        let siblings = (0..DEPTH as u8)
+            .map(|i| Blake2sHasher::hash(&[i]))
+            .collect_vec();
+        let leaf_hash = Blake2sHasher::hash_leaf(IntoSlice::<u8>::into_slice(&leaf_block));
+        let top_node = siblings
+            .iter()
+            .enumerate()
+            .fold(leaf_hash, |node, (layer, sibling)| {
+                match (query >> layer) & 1 {
+                    0 => hash_tagged_nodes::<Blake2sHasher>(&[node, *sibling]),
+                    _ => hash_tagged_nodes::<Blake2sHasher>(&[*sibling, node]),
+                }
+            });
+        let root = hash_root::<Blake2sHasher>(&top_node, 1 << DEPTH);
+        let decommitment = MerkleDecommitment::<M31, Blake2sHasher>::new(
+            vec![leaf_block],
+            siblings.into_iter().map(|sibling| vec![sibling]).collect(),
+            1,
+            vec![query],
+            1 << DEPTH,
+        );
+
+        let bytes = decommitment.to_bytes();
+        let parsed = MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(&bytes).unwrap();
+
+        // The directions and the number of hashes of every layer derive from the query, so only
+        // the siblings grow with the depth.
+        let header_len = 1 + 1 + 4 + 4 + 8 + 4 + 8 + 4 + 4 + 4 + 4;
+        assert_eq!(bytes.len(), DEPTH * Blake2sHasher::OUTPUT_SIZE + header_len);
+        assert_eq!(parsed.verify_detailed(root, &[query]), Ok(()));
+    }
+
+    #[test]
+    fn from_bytes_malformed_test() {
+        type Decommitment = MerkleDecommitment<M31, Blake2sHasher>;
+        let trace: ColumnArray<M31> = vec![(0..64).map(M31::from_u32_unchecked).collect(); 2];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let bytes = tree.generate_decommitment(vec![5, 17]).to_bytes();
+
+        for len in 0..bytes.len() {
+            assert_eq!(
+                Decommitment::from_bytes(&bytes[..len]).unwrap_err(),
+                Error::InvalidDecommitment(DecommitmentParseError::UnexpectedEnd)
+            );
+        }
+        assert_eq!(
+            Decommitment::from_bytes(&[bytes.clone(), vec![0]].concat()).unwrap_err(),
+            Error::InvalidDecommitment(DecommitmentParseError::TrailingBytes)
+        );
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = DECOMMITMENT_ENCODING_VERSION + 1;
+        assert_eq!(
+            Decommitment::from_bytes(&wrong_version).unwrap_err(),
+            Error::InvalidDecommitment(DecommitmentParseError::UnsupportedVersion(
+                DECOMMITMENT_ENCODING_VERSION + 1
+            ))
+        );
+        // The first leaf value follows the header, the two queries and the number of rows.
+        let mut invalid_felt = bytes;
+        invalid_felt[1 + 1 + 4 * 2 + 8 * 2 + 4 * 3 + 8..][..4].copy_from_slice(&P.to_le_bytes());
+        assert_eq!(
+            Decommitment::from_bytes(&invalid_felt).unwrap_err(),
+            Error::InvalidDecommitment(DecommitmentParseError::InvalidFieldElement)
+        );
+    }
+
+    #[test]
+    fn from_bytes_untrusted_counts_test() {
+        type Decommitment = MerkleDecommitment<M31, Blake2sHasher>;
+        type Compressed = CompressedBatchProof<M31, Blake2sHasher>;
+        // A header of the given format without queries, followed by the given depth and leaf block
+        // counts, and 64 zero bytes.
+        let encode = |tag: u8, depth: u32, n_leaf_blocks: u32, leaf_block_len: u32| {
+            [
+                &[DECOMMITMENT_ENCODING_VERSION, tag][..],
+                &depth.to_le_bytes(),
+                &0u32.to_le_bytes(),
+                &16u32.to_le_bytes(),
+                &64u64.to_le_bytes(),
+                &n_leaf_blocks.to_le_bytes(),
+                &leaf_block_len.to_le_bytes(),
+                &[0; 64],
+            ]
+            .concat()
+        };
+
+        for ((depth, n_leaf_blocks, leaf_block_len), expected_err) in [
+            ((0, 1 << 26, 0), DecommitmentParseError::EmptyLeafBlock),
+            ((0, u32::MAX, 0), DecommitmentParseError::EmptyLeafBlock),
+            ((0, u32::MAX, 1), DecommitmentParseError::UnexpectedEnd),
+            ((0, 1, u32::MAX), DecommitmentParseError::UnexpectedEnd),
+            // Layers of a decommitment without queries hold no hashes, so only the depth of the
+            // tree bounds them.
+            (
+                (u32::MAX, 0, 0),
+                DecommitmentParseError::TooManyLayers(u32::MAX as usize),
+            ),
+            ((64, 0, 0), DecommitmentParseError::TooManyLayers(64)),
+        ] {
+            let expected_err = Error::InvalidDecommitment(expected_err);
+            assert_eq!(
+                Decommitment::from_bytes(&encode(
+                    DECOMMITMENT_TAG,
+                    depth,
+                    n_leaf_blocks,
+                    leaf_block_len
+                ))
+                .unwrap_err(),
+                expected_err
+            );
+            assert_eq!(
+                Compressed::from_bytes(&encode(
+                    COMPRESSED_BATCH_PROOF_TAG,
+                    depth,
+                    n_leaf_blocks,
+                    leaf_block_len
+                ))
+                .unwrap_err(),
+                expected_err
+            );
+        }
+        // Every layer of a compressed proof is encoded with at least its length.
+        assert_eq!(
+            Compressed::from_bytes(&encode(COMPRESSED_BATCH_PROOF_TAG, 17, 0, 0)).unwrap_err(),
+            Error::InvalidDecommitment(DecommitmentParseError::UnexpectedEnd)
+        );
+    }
+
     #[test]
     fn values_test() {
         let trace_column_length = 1 << 6;
@@ -241,4 +1534,24 @@ mod tests {
             .zip(test_skip_decommitment.values())
             .all(|(q, v)| v == vec![trace[0][*q], trace[1][*q]]));
     }
+
+    #[test]
+    fn secure_field_values_test() {
+        let trace: ColumnArray<QM31> = (0..3)
+            .map(|j| {
+                (0..64)
+                    .map(|i| QM31::from_u32_unchecked(i, j, i + j, 7))
+                    .collect()
+            })
+            .collect();
+        let tree = MerkleTree::<QM31, Blake2sHasher>::commit(trace.clone());
+        let queries = vec![5, 33, 34];
+        let decommitment = tree.generate_decommitment(queries.clone());
+
+        assert!(decommitment.verify(tree.root(), &queries));
+        assert!(queries
+            .iter()
+            .zip(decommitment.values())
+            .all(|(q, v)| v == vec![trace[0][*q], trace[1][*q], trace[2][*q]]));
+    }
 }
@@ -1,3 +1,6 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
 use super::utils::get_column_chunk;
 use crate::core::fields::Field;
 
@@ -1,4 +1,6 @@
-use std::fmt::{self, Display};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
 
 use super::hasher::Hasher;
 use super::merkle_input::MerkleTreeInput;
@@ -150,9 +152,9 @@ impl<H: Hasher> Display for MerkleMultiLayer<H> {
             .chunks(self.config.sub_tree_size)
             .enumerate()
             .for_each(|(i, c)| {
-                f.write_str(&std::format!("\nSubTree #[{}]:", i)).unwrap();
+                f.write_str(&alloc::format!("\nSubTree #[{}]:", i)).unwrap();
                 for (i, h) in c.iter().enumerate() {
-                    f.write_str(&std::format!("\nNode #[{}]: {}", i, h))
+                    f.write_str(&alloc::format!("\nNode #[{}]: {}", i, h))
                         .unwrap();
                 }
             });
@@ -1,86 +1,582 @@
-use std::cmp::max;
-use std::collections::BTreeSet;
-use std::fmt::{Debug, Display};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::max;
+use core::fmt::{self, Debug, Display};
 
 use itertools::Itertools;
 
-use super::hasher::Hasher;
-use super::merkle_decommitment::MerkleDecommitment;
+use super::error;
+use super::hasher::{Hasher, LEAF_DOMAIN_TAG};
+use super::merkle_decommitment::{MerkleDecommitment, MerkleVerifyError};
+use super::serialize::Endianness;
 use crate::commitment_scheme::utils::{
-    allocate_balanced_tree, column_to_row_major, hash_merkle_tree_from_bottom_layer,
-    tree_data_as_mut_ref, ColumnArray, TreeData,
+    allocate_layer, column_to_row_major, hash_padded_node_layer, hash_root, hash_salted_leaf,
+    hash_tagged_layer, hash_tagged_nodes, layer_hashes, ColumnArray, TreeLayer,
 };
+#[cfg(feature = "avx512")]
+use crate::core::backend::avx512::{PackedBaseField, K_BLOCK_SIZE};
+#[cfg(feature = "avx512")]
+use crate::core::fields::m31::BaseField;
 use crate::core::fields::{Field, IntoSlice};
+use crate::core::utils::bit_reverse;
 use crate::math::utils::{prev_pow_two, usize_div_ceil};
 
-pub struct MerkleTree<T: Field + Sized + Debug + Display, H: Hasher> {
+/// Enters a `tracing` span for the rest of the enclosing scope. Compiles to nothing without the
+/// `tracing` feature. Subscribers measure the time spent in the span, e.g. with
+/// `tracing_subscriber::fmt::format::FmtSpan::CLOSE`.
+macro_rules! trace_span {
+    ($name:expr $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+/// A merkle tree over columns of field elements, e.g. [crate::core::fields::m31::M31] or
+/// [crate::core::fields::qm31::QM31]. The serialization of the elements to the hasher's native
+/// type is handled internally, and decommitments open typed values, see
+/// [MerkleDecommitment::values].
+///
+/// Every internal node hashes `ARITY` children, which must be a power of two. Wider trees are
+/// shallower, at the cost of `ARITY - 1` siblings per layer in a decommitment. When the number of
+/// nodes in a layer is not a multiple of `ARITY`, the last group of children is padded with
+/// default (i.e. all-zero) hashes.
+pub struct MerkleTree<T: Field + Sized + Debug + Display, H: Hasher, const ARITY: usize = 2> {
     pub bottom_layer: Vec<T>,
     pub bottom_layer_block_size: usize,
     pub bottom_layer_n_rows_in_node: usize,
-    pub data: TreeData<H::NativeType>,
+    /// The hashes of every layer, see [MerkleTree::layer]. Layers not stored by
+    /// [MerkleTree::build_capped] are empty.
+    pub data: Vec<Vec<H::Hash>>,
     pub height: usize,
-    phantom: std::marker::PhantomData<H>,
+    /// Number of rows of the committed trace, excluding padding, see [MerkleTree::root].
+    n_trace_rows: usize,
+    /// Number of bottom layers of `data` that are not stored, see [MerkleTree::build_capped].
+    n_dropped_layers: usize,
+    /// The salt of every leaf block, or empty if the tree is not salted, see
+    /// [MerkleTree::build_salted].
+    salts: Vec<H::Hash>,
+    /// The configuration the tree was built with, see [MerkleTree::build_with_config].
+    config: MerkleTreeConfig<H>,
+    phantom: core::marker::PhantomData<H>,
+}
+
+/// How a [MerkleTree] over a number of rows that is not a power of two is padded.
+pub enum PaddingMode<H: Hasher> {
+    /// The columns are padded to a power of two rows by duplicating their last row. Every layer
+    /// of a binary tree is then full.
+    DuplicateLast,
+    /// The columns are only padded to whole leaf blocks, by duplicating their last row, and
+    /// missing nodes of every layer are the all-zero hash.
+    ZeroHash,
+    /// Like [PaddingMode::ZeroHash], with a designated padding hash, e.g. a domain separator.
+    Custom(H::Hash),
+}
+
+impl<H: Hasher> Clone for PaddingMode<H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H: Hasher> Copy for PaddingMode<H> {}
+
+/// The configuration of [MerkleTree::build_with_config]. The default is the configuration of
+/// [MerkleTree::commit].
+pub struct MerkleTreeConfig<H: Hasher> {
+    pub padding: PaddingMode<H>,
+    /// The byte order leaf values are hashed in. Decommitments of the tree record it, verifiers
+    /// of deserialized decommitments must set it with [MerkleDecommitment::with_endianness].
+    pub endianness: Endianness,
+}
+
+impl<H: Hasher> Clone for MerkleTreeConfig<H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H: Hasher> Copy for MerkleTreeConfig<H> {}
+
+impl<H: Hasher> Default for MerkleTreeConfig<H> {
+    fn default() -> Self {
+        Self {
+            padding: PaddingMode::DuplicateLast,
+            endianness: Endianness::Little,
+        }
+    }
+}
+
+impl<H: Hasher> MerkleTreeConfig<H> {
+    /// The hash of the nodes missing from a layer, i.e. of the padding children of its parents.
+    fn padding_hash(&self) -> H::Hash {
+        match self.padding {
+            PaddingMode::DuplicateLast | PaddingMode::ZeroHash => H::Hash::default(),
+            PaddingMode::Custom(hash) => hash,
+        }
+    }
 }
 
-impl<T: Field + Sized + Copy + Debug + Display, H: Hasher> MerkleTree<T, H>
+impl<T: Field + Sized + Copy + Debug + Display, H: Hasher, const ARITY: usize>
+    MerkleTree<T, H, ARITY>
 where
     T: IntoSlice<H::NativeType>,
+    H::NativeType: Copy + From<u8>,
 {
     /// Commits on a given trace(matrix).
+    /// Leaves and internal nodes are domain separated, see [Hasher::hash_leaf].
+    /// Equivalent to [MerkleTree::from_leaf_hashes] of [MerkleTree::hash_leaves], without the
+    /// intermediate copy.
     pub fn commit(trace: ColumnArray<T>) -> Self {
+        Self::build_with_config(trace, MerkleTreeConfig::default())
+    }
+
+    /// Commits on a given trace(matrix) of columns in natural order, storing their rows in
+    /// bit-reversed order, the convention of [crate::core::poly::BitReversedOrder] evaluations.
+    /// Query `i` then opens natural row `bit_reverse_index(i, log_n_rows)`, i.e. the value that
+    /// FRI expects at index `i` of the evaluation. Decommitments verify as usual.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns are not of a power of two length.
+    pub fn build_bit_reversed(mut trace: ColumnArray<T>) -> Self {
+        trace.iter_mut().for_each(|column| bit_reverse(column));
+        Self::commit(trace)
+    }
+
+    /// Commits on a given trace(matrix), padded according to `config`, see [PaddingMode].
+    /// Decommitments include the padding nodes they need, so they verify as usual.
+    ///
+    /// A trace without columns commits to an empty tree, whose root is [root_of_empty].
+    pub fn build_with_config(trace: ColumnArray<T>, config: MerkleTreeConfig<H>) -> Self {
+        if trace.is_empty() {
+            return Self::empty(config);
+        }
+        let mut tree = Self::init_from_column_array_with_config(trace, config);
+        let mut leaf_hashes = allocate_layer(tree.layer_size(0) * H::OUTPUT_SIZE);
+        Self::hash_leaf_blocks(
+            &tree.bottom_layer,
+            tree.bottom_layer_block_size,
+            config.endianness,
+            &mut leaf_hashes,
+        );
+        tree.hash_node_layers(leaf_hashes);
+        tree
+    }
+
+    /// Hashes the leaf blocks of the tree committing to `trace`, i.e. the expensive part of
+    /// [MerkleTree::commit]. Leaf hashes only depend on the trace and the hasher, so they can be
+    /// cached and reused for trees of different arities, see [MerkleTree::from_leaf_hashes].
+    pub fn hash_leaves(trace: &ColumnArray<T>) -> Vec<H::Hash> {
+        let (bottom_layer, bottom_layer_block_size) = Self::bottom_layer_from_column_refs(trace);
+        let mut leaf_hashes = allocate_layer::<H::NativeType>(
+            bottom_layer.len() / bottom_layer_block_size * H::OUTPUT_SIZE,
+        );
+        Self::hash_leaf_blocks(
+            &bottom_layer,
+            bottom_layer_block_size,
+            Endianness::Little,
+            &mut leaf_hashes,
+        );
+        layer_hashes::<H>(&leaf_hashes)
+    }
+
+    /// Commits on a given trace(matrix), given the hashes of its leaf blocks, as computed by
+    /// [MerkleTree::hash_leaves].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of leaf hashes doesn't match the number of leaf blocks of the trace.
+    pub fn from_leaf_hashes(trace: ColumnArray<T>, leaf_hashes: &[H::Hash]) -> Self {
         let mut tree = Self::init_from_column_array(trace);
+        assert_eq!(
+            leaf_hashes.len(),
+            tree.layer_size(0),
+            "Leaf hashes don't match the trace!"
+        );
+        tree.hash_node_layers(flatten_hashes::<H>(leaf_hashes));
+        tree
+    }
 
-        hash_merkle_tree_from_bottom_layer::<T, H>(
-            &tree.bottom_layer[..],
-            tree.bottom_layer_block_size * std::mem::size_of::<T>(),
-            &mut tree_data_as_mut_ref(&mut tree.data)[..],
+    /// Commits on a given trace(matrix), storing only the layers of at most 2^`cap_log` nodes, i.e.
+    /// the top `cap_log + 1` layers of a binary tree, in addition to the trace itself.
+    /// Nodes of the dropped layers are recomputed from the trace when generating decommitments,
+    /// which trades decommitment time for memory. Decommitments are identical to the ones of a
+    /// tree built with [MerkleTree::commit].
+    pub fn build_capped(trace: ColumnArray<T>, cap_log: u32) -> Self {
+        Self::build_capped_with_config(trace, cap_log, MerkleTreeConfig::default())
+    }
+
+    /// Like [MerkleTree::build_capped], padded and hashed according to `config`. Decommitments are
+    /// identical to the ones of a tree built with [MerkleTree::build_with_config].
+    pub fn build_capped_with_config(
+        trace: ColumnArray<T>,
+        cap_log: u32,
+        config: MerkleTreeConfig<H>,
+    ) -> Self {
+        if trace.is_empty() {
+            return Self::empty(config);
+        }
+        let mut tree = Self::init_from_column_array_with_config(trace, config);
+        tree.n_dropped_layers = (0..tree.height - 1)
+            .take_while(|&layer| tree.layer_size(layer) > 1 << cap_log)
+            .count();
+
+        let mut leaf_hashes = allocate_layer(tree.layer_size(0) * H::OUTPUT_SIZE);
+        Self::hash_leaf_blocks(
+            &tree.bottom_layer,
+            tree.bottom_layer_block_size,
+            tree.config.endianness,
+            &mut leaf_hashes,
         );
+        tree.hash_node_layers(leaf_hashes);
+        tree
+    }
 
+    /// Commits on a given trace(matrix), appending `salts[i]` to the `i`-th leaf block before
+    /// hashing it. With fresh random salts, e.g. the output of a hasher over secret randomness,
+    /// the root hides the trace. Decommitments reveal the salts of the opened leaf blocks, so
+    /// they can be verified as usual.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't exactly one salt per leaf block.
+    pub fn build_salted(trace: ColumnArray<T>, salts: Vec<H::Hash>) -> Self {
+        let mut tree = Self::init_from_column_array(trace);
+        assert_eq!(
+            salts.len(),
+            tree.layer_size(0),
+            "Salts don't match the leaf blocks!"
+        );
+        tree.salts = salts;
+        trace_span!("hash_leaves", size = tree.layer_size(0));
+        let leaf_hashes = (0..tree.layer_size(0))
+            .map(|i| tree.hash_leaf_block(i))
+            .collect::<Vec<_>>();
+        tree.hash_node_layers(flatten_hashes::<H>(&leaf_hashes));
         tree
     }
 
+    /// Hashes the tree committing to `trace` like [MerkleTree::build_with_config], appending all
+    /// its layers to `arena`, from the leaf hashes up to the top node. The total number of nodes is
+    /// reserved up front, so building many trees into the same arena doesn't allocate a buffer per
+    /// layer.
+    ///
+    /// Returns the offsets in `arena` of the layers, followed by the end of the top layer, i.e.
+    /// layer `i` is `arena[offsets[i]..offsets[i + 1]]` and the top node is
+    /// `arena[offsets[depth]]`. The root is its [hash_root] with the number of rows of `trace`.
+    pub fn build_into(
+        trace: ColumnArray<T>,
+        config: &MerkleTreeConfig<H>,
+        arena: &mut Vec<H::Hash>,
+    ) -> Vec<usize> {
+        let tree = Self::init_from_column_array_with_config(trace, *config);
+        let mut layer_sizes = vec![tree.layer_size(0)];
+        while *layer_sizes.last().unwrap() > 1 {
+            layer_sizes.push(usize_div_ceil(*layer_sizes.last().unwrap(), ARITY));
+        }
+        arena.reserve(layer_sizes.iter().sum());
+
+        let mut offsets = vec![arena.len()];
+        let mut leaf_hashes = allocate_layer(layer_sizes[0] * H::OUTPUT_SIZE);
+        Self::hash_leaf_blocks(
+            &tree.bottom_layer,
+            tree.bottom_layer_block_size,
+            config.endianness,
+            &mut leaf_hashes,
+        );
+        arena.extend(layer_hashes::<H>(&leaf_hashes));
+        for layer_size in &layer_sizes[1..] {
+            let children_start = *offsets.last().unwrap();
+            let children_end = arena.len();
+            offsets.push(children_end);
+            for parent in 0..*layer_size {
+                let first_child = children_start + parent * ARITY;
+                let parent_hash = match first_child + ARITY <= children_end {
+                    true => hash_tagged_nodes::<H>(&arena[first_child..first_child + ARITY]),
+                    false => {
+                        let mut children = arena[first_child..children_end].to_vec();
+                        children.resize(ARITY, config.padding_hash());
+                        hash_tagged_nodes::<H>(&children)
+                    }
+                };
+                arena.push(parent_hash);
+            }
+        }
+        offsets.push(arena.len());
+        offsets
+    }
+
+    /// Commits on a trace given as borrowed columns, validating its shape first.
+    ///
+    /// Rows are serialized in a stable, row-major order: the values of every column at the row's
+    /// index, in the order of `columns`, each as its little-endian bytes. Consecutive rows may be
+    /// packed into a single leaf block, see [MerkleDecommitment::values] for reading back rows.
+    pub fn commit_columns(columns: &[&[T]]) -> Result<Self, error::Error> {
+        validate_columns(columns.iter().map(|c| c.len()))?;
+        Ok(Self::commit(columns.iter().map(|c| c.to_vec()).collect()))
+    }
+
+    /// Commits on a trace given as a matrix, whose rows are the rows of the trace and whose columns
+    /// are its columns, i.e. `matrix[[row, column]]`. The root is the same as committing on the
+    /// columns of the matrix with [MerkleTree::commit_columns], whatever the memory layout of the
+    /// matrix.
+    #[cfg(feature = "ndarray")]
+    pub fn commit_matrix(matrix: &ndarray::Array2<T>) -> Result<Self, error::Error> {
+        let columns = matrix
+            .columns()
+            .into_iter()
+            .map(|column| column.to_vec())
+            .collect::<ColumnArray<T>>();
+        validate_columns(columns.iter().map(Vec::len))?;
+        Ok(Self::commit(columns))
+    }
+
+    /// Commits on several groups of columns, e.g. the trace, interaction and composition columns of
+    /// a proof, as separate trees, see [MerkleTree::commit_columns]. Returns the root of every
+    /// group, in order. With the `parallel` feature, the trees are built in parallel.
+    ///
+    /// Fails on the first group with inconsistent columns.
+    pub fn commit_multiple(column_groups: &[&[&[T]]]) -> Result<Vec<H::Hash>, error::Error>
+    where
+        T: Sync,
+    {
+        let commit_group =
+            |columns: &&[&[T]]| Self::commit_columns(columns).map(|tree| tree.root());
+
+        #[cfg(not(feature = "parallel"))]
+        return column_groups.iter().map(commit_group).collect();
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            column_groups.par_iter().map(commit_group).collect()
+        }
+    }
+
+    /// The tree of a trace without columns: it has no rows and no leaves, and its only layer holds
+    /// the root, [root_of_empty]. Every query is out of range, see
+    /// [MerkleTree::try_generate_decommitment].
+    fn empty(config: MerkleTreeConfig<H>) -> Self {
+        Self {
+            bottom_layer: Vec::new(),
+            bottom_layer_block_size: 1,
+            bottom_layer_n_rows_in_node: 1,
+            height: 2,
+            data: vec![vec![root_of_empty::<H>()]],
+            n_trace_rows: 0,
+            n_dropped_layers: 0,
+            salts: Vec::new(),
+            config,
+            phantom: core::marker::PhantomData,
+        }
+    }
+
     /// Builds the base layer of the tree from the given trace.
     /// Allocates the rest of the tree.
-    // TODO(Ohad): add support for columns of different lengths.
     fn init_from_column_array(trace: ColumnArray<T>) -> Self {
+        Self::init_from_column_array_with_config(trace, MerkleTreeConfig::default())
+    }
+
+    fn init_from_column_array_with_config(
+        trace: ColumnArray<T>,
+        config: MerkleTreeConfig<H>,
+    ) -> Self {
+        let n_trace_rows = trace.first().map_or(0, Vec::len);
+        let (bottom_layer, bottom_layer_block_size, n_rows_in_node) =
+            Self::bottom_layer_from_column_array(
+                trace,
+                matches!(config.padding, PaddingMode::DuplicateLast),
+            );
+
+        // Count the layers from the leaf hashes up to the top node, hashed later.
+        let mut layer_size = usize_div_ceil(bottom_layer.len(), bottom_layer_block_size);
+        let mut n_layers = 1;
+        while layer_size > 1 {
+            layer_size = usize_div_ceil(layer_size, ARITY);
+            n_layers += 1;
+        }
+
+        Self {
+            bottom_layer,
+            bottom_layer_block_size,
+            bottom_layer_n_rows_in_node: n_rows_in_node,
+            height: n_layers + 1, // +1 for the bottom layer.
+            data: Vec::new(),
+            n_trace_rows,
+            n_dropped_layers: 0,
+            salts: Vec::new(),
+            config,
+            phantom: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the row-major bottom layer of the given trace, the number of elements in a leaf
+    /// block and the number of rows in a leaf block.
+    /// If `pad_to_power_of_two` and the columns are not of a power of two length, they are padded
+    /// by duplicating their last row, i.e. every layer of the tree is balanced and no node is
+    /// promoted. Otherwise the columns are only padded to whole leaf blocks. Leaf blocks hold the
+    /// same number of rows either way.
+    fn bottom_layer_from_column_array(
+        mut trace: ColumnArray<T>,
+        pad_to_power_of_two: bool,
+    ) -> (Vec<T>, usize, usize) {
+        let (n_rows_in_node, padded_length) = Self::leaf_block_shape(&trace, pad_to_power_of_two);
+        trace.iter_mut().for_each(|column| {
+            let last_row = *column.last().unwrap();
+            column.resize(padded_length, last_row);
+        });
+
+        let bottom_layer_block_size = n_rows_in_node * trace.len();
+        (
+            column_to_row_major(trace),
+            bottom_layer_block_size,
+            n_rows_in_node,
+        )
+    }
+
+    /// Like [Self::bottom_layer_from_column_array] padding to a power of two, but reads the columns
+    /// by reference, copying every value straight into the bottom layer. Returns the bottom layer
+    /// and the number of elements in a leaf block.
+    fn bottom_layer_from_column_refs(trace: &[Vec<T>]) -> (Vec<T>, usize) {
+        let (n_rows_in_node, padded_length) = Self::leaf_block_shape(trace, true);
+        let last_row = trace[0].len() - 1;
+        let bottom_layer = (0..padded_length)
+            .flat_map(|row| trace.iter().map(move |column| column[row.min(last_row)]))
+            .collect();
+        (bottom_layer, n_rows_in_node * trace.len())
+    }
+
+    /// Validates the trace, and returns the number of rows in a leaf block and the number of rows
+    /// the columns are padded to, see [Self::bottom_layer_from_column_array].
+    // TODO(Ohad): add support for columns of different lengths.
+    fn leaf_block_shape(trace: &[Vec<T>], pad_to_power_of_two: bool) -> (usize, usize) {
+        assert!(
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "Invalid arity {ARITY}."
+        );
         assert!(!trace.is_empty());
-        assert!(trace[0].len().is_power_of_two());
+        assert_ne!(trace[0].len(), 0, "Attempted committing to empty columns!");
         trace.iter().for_each(|column| {
             assert_eq!(column.len(), trace[0].len());
         });
-
-        let n_rows_in_node = std::cmp::min(
+        let n_rows_in_node = core::cmp::min(
             prev_pow_two(max(
-                H::BLOCK_SIZE / (trace.len() * std::mem::size_of::<T>()),
+                H::BLOCK_SIZE / (trace.len() * core::mem::size_of::<T>()),
                 1,
             )),
-            trace[0].len(),
+            trace[0].len().next_power_of_two(),
         );
+        let padded_length = match pad_to_power_of_two {
+            true => trace[0].len().next_power_of_two(),
+            false => trace[0].len().next_multiple_of(n_rows_in_node),
+        };
+        (n_rows_in_node, padded_length)
+    }
 
-        let bottom_layer_block_size = n_rows_in_node * trace.len();
-        let bottom_layer = column_to_row_major(trace);
-
-        // Allocate rest of the tree.
-        let bottom_layer_length_nodes = usize_div_ceil(bottom_layer.len(), bottom_layer_block_size);
-        let tree_data =
-            allocate_balanced_tree(bottom_layer_length_nodes, H::BLOCK_SIZE, H::OUTPUT_SIZE);
+    /// Hashes every leaf block of `bottom_layer` into `dst`, see [Hasher::hash_leaf].
+    fn hash_leaf_blocks(
+        bottom_layer: &[T],
+        bottom_layer_block_size: usize,
+        endianness: Endianness,
+        dst: &mut [H::NativeType],
+    ) {
+        trace_span!("hash_leaves", size = dst.len() / H::OUTPUT_SIZE);
+        hash_tagged_layer::<H>(
+            &endianness.encode_felts(bottom_layer),
+            bottom_layer_block_size * core::mem::size_of::<T>(),
+            LEAF_DOMAIN_TAG,
+            dst,
+        );
+    }
 
-        Self {
-            bottom_layer,
-            bottom_layer_block_size,
-            bottom_layer_n_rows_in_node: n_rows_in_node,
-            height: tree_data.len() + 1, // +1 for the bottom layer.
-            data: tree_data,
-            phantom: std::marker::PhantomData,
+    /// Hashes the internal layers of the tree and stores them in `data`, given the leaf hashes as
+    /// native elements. Only a layer and its parent are held as native elements at a time, and a
+    /// layer dropped by [MerkleTree::build_capped] is freed as soon as its parent is computed.
+    fn hash_node_layers(&mut self, leaf_hashes: TreeLayer<H::NativeType>) {
+        let n_layers = self.height - 1;
+        self.data = Vec::with_capacity(n_layers);
+        let mut layer = leaf_hashes;
+        for i in 0..n_layers {
+            self.data.push(match i < self.n_dropped_layers {
+                true => Vec::new(),
+                false => layer_hashes::<H>(&layer),
+            });
+            if i + 1 == n_layers {
+                break;
+            }
+            let mut parent_layer = allocate_layer(
+                usize_div_ceil(layer.len() / H::OUTPUT_SIZE, ARITY) * H::OUTPUT_SIZE,
+            );
+            trace_span!(
+                "hash_layer",
+                layer = i + 1,
+                size = parent_layer.len() / H::OUTPUT_SIZE
+            );
+            hash_padded_node_layer::<H>(
+                &layer,
+                ARITY,
+                self.config.padding_hash(),
+                &mut parent_layer,
+            );
+            layer = parent_layer;
         }
     }
 
+    /// The root of the tree: the top node of its layers hashed with the number of rows of the
+    /// trace, excluding padding, see [hash_root]. A trace and the same trace padded by duplicating
+    /// its last row have distinct roots, and so have traces of the same values in other shapes.
     pub fn root(&self) -> H::Hash {
-        (&self.data.last().unwrap()[..]).into()
+        let top_node = self.data.last().unwrap()[0];
+        if self.bottom_layer.is_empty() {
+            return top_node;
+        }
+        hash_root::<H>(&top_node, self.n_trace_rows)
+    }
+
+    /// Returns the hashes of the `i`-th layer of the tree, where layer 0 holds the leaf hashes and
+    /// the last layer holds the top node, see [MerkleTree::root].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer is out of range, or is not stored by [MerkleTree::build_capped], see
+    /// [MerkleTree::recompute_layer].
+    pub fn layer(&self, i: usize) -> &[H::Hash] {
+        assert!(i < self.data.len(), "Layer {i} is out of range!");
+        assert!(
+            i >= self.n_dropped_layers,
+            "Layer {i} is not stored by the capped tree!"
+        );
+        &self.data[i]
+    }
+
+    /// Iterates over the layers of the tree, see [MerkleTree::layer]. There are `depth + 1`
+    /// layers, from the leaf hashes up to the top node.
+    ///
+    /// # Panics
+    ///
+    /// Panics on reaching a layer not stored by [MerkleTree::build_capped].
+    pub fn layers(&self) -> impl Iterator<Item = &[H::Hash]> + '_ {
+        (0..self.data.len()).map(|i| self.layer(i))
     }
 
-    /// Generates a merkle decommitment for the given queries. Queries must be sorted.
-    pub fn generate_decommitment(&self, queries: Vec<usize>) -> MerkleDecommitment<T, H> {
+    /// Like [MerkleTree::layer], but recomputes the layer from the trace if it is not stored by
+    /// [MerkleTree::build_capped].
+    pub fn recompute_layer(&self, i: usize) -> Vec<H::Hash> {
+        assert!(i < self.data.len(), "Layer {i} is out of range!");
+        (0..self.layer_size(i)).map(|j| self.node(i, j)).collect()
+    }
+
+    /// Generates a merkle decommitment for the given queries. Queries are sorted and deduplicated,
+    /// and every node needed by more than one queried path is included at most once, so equal
+    /// query sets result in identical decommitments.
+    pub fn generate_decommitment(
+        &self,
+        mut queries: Vec<usize>,
+    ) -> MerkleDecommitment<T, H, ARITY> {
+        trace_span!("generate_decommitment", n_queries = queries.len());
+        queries.sort_unstable();
+        queries.dedup();
         let leaf_block_indices: Vec<usize> = queries
             .iter()
             .map(|query| query / self.bottom_layer_n_rows_in_node)
@@ -93,50 +589,119 @@ where
             leaf_blocks.push(self.get_leaf_block(*block_index));
         });
 
-        // TODO(Ohad): Change to Vec.
-        // Sorted indices of the current layer.
-        let mut curr_layer_indices = leaf_block_indices
-            .iter()
-            .map(|index| index ^ 1)
-            .collect::<BTreeSet<usize>>();
+        // Sorted indices of the queried nodes of the current layer.
+        let mut layer_indices = leaf_block_indices.clone();
         let mut layers = Vec::<Vec<H::Hash>>::new();
         for i in 0..self.height - 2 {
-            let mut proof_layer = Vec::<H::Hash>::with_capacity(curr_layer_indices.len());
-            let mut indices_iterator = curr_layer_indices.iter().peekable();
-            while let Some(q) = indices_iterator.next() {
-                let mut f = || -> Option<_> {
-                    match indices_iterator.peek() {
-                        // If both childs are in the layer, no extra data is needed to calculate
-                        // parent.
-                        Some(next_q) if *q % 2 == 0 && *q + 1 == **next_q => {
-                            indices_iterator.next();
-                            None
-                        }
-                        _ => {
-                            let node: H::Hash =
-                                self.data[i][*q * H::OUTPUT_SIZE..(*q + 1) * H::OUTPUT_SIZE].into();
-                            Some(node)
-                        }
+            // Every group of siblings with a queried node needs its non-queried nodes.
+            let parent_indices = layer_indices
+                .iter()
+                .map(|index| index / ARITY)
+                .dedup()
+                .collect::<Vec<usize>>();
+            let mut queried_indices = layer_indices.iter().peekable();
+            let mut proof_layer = Vec::<H::Hash>::new();
+            for parent in &parent_indices {
+                for child in parent * ARITY..(parent + 1) * ARITY {
+                    if queried_indices.next_if_eq(&&child).is_none() {
+                        proof_layer.push(self.node(i, child));
                     }
-                };
-                if let Some(node) = f() {
-                    proof_layer.push(node);
                 }
             }
             layers.push(proof_layer);
-
-            // Next layer indices are the parents' siblings.
-            curr_layer_indices = curr_layer_indices
-                .iter()
-                .map(|index| (index / 2) ^ 1)
-                .collect();
+            layer_indices = parent_indices;
         }
+        let salts = match self.salts.is_empty() {
+            true => Vec::new(),
+            false => leaf_block_indices.iter().map(|i| self.salts[*i]).collect(),
+        };
         MerkleDecommitment::new(
             leaf_blocks,
             layers,
             self.bottom_layer_n_rows_in_node,
             queries,
+            self.n_trace_rows,
         )
+        .with_salts(salts)
+        .with_endianness(self.config.endianness)
+    }
+
+    /// Generates a merkle decommitment for the given queries, checking that they are in the range
+    /// of the committed (padded) rows. A single row can be opened by passing a single query.
+    pub fn try_generate_decommitment(
+        &self,
+        queries: Vec<usize>,
+    ) -> Result<MerkleDecommitment<T, H, ARITY>, error::Error> {
+        let n_rows = self.n_rows();
+        if let Some(&query) = queries.iter().find(|&&query| query >= n_rows) {
+            return Err(MerkleTreeError::QueryOutOfRange { query, n_rows }.into());
+        }
+        Ok(self.generate_decommitment(queries))
+    }
+
+    /// Checks a decommitment against this tree, reporting the first recomputed node that differs
+    /// from the committed one. Meant for debugging mismatches between a prover and a verifier.
+    pub fn check_decommitment(
+        &self,
+        decommitment: &MerkleDecommitment<T, H, ARITY>,
+        queries: &[usize],
+    ) -> Result<(), error::Error> {
+        let root = decommitment.recompute_root(queries, |layer, index, hash| {
+            if self.node(layer, index) == *hash {
+                Ok(())
+            } else {
+                Err(MerkleVerifyError::NodeMismatch { layer, index })
+            }
+        })?;
+        if root == self.root() {
+            Ok(())
+        } else {
+            Err(MerkleVerifyError::RootMismatch.into())
+        }
+    }
+
+    /// Returns the number of committed rows, including padding rows.
+    pub fn n_rows(&self) -> usize {
+        self.bottom_layer.len() * self.bottom_layer_n_rows_in_node / self.bottom_layer_block_size
+    }
+
+    /// Returns the number of nodes in the `layer`-th layer of `data`, excluding padding.
+    fn layer_size(&self, layer: usize) -> usize {
+        // The only layer of the empty tree holds its root, see [MerkleTree::empty].
+        if self.bottom_layer.is_empty() {
+            return 1;
+        }
+        let n_leaf_blocks = self.bottom_layer.len() / self.bottom_layer_block_size;
+        (0..layer).fold(n_leaf_blocks, |size, _| usize_div_ceil(size, ARITY))
+    }
+
+    /// Returns the `index`-th node of the `layer`-th layer of `data`, recomputing it from the trace
+    /// if the layer is not stored. Indices past the end of the layer are padding nodes.
+    fn node(&self, layer: usize, index: usize) -> H::Hash {
+        if index >= self.layer_size(layer) {
+            return self.config.padding_hash();
+        }
+        if layer >= self.n_dropped_layers {
+            return self.data[layer][index];
+        }
+        if layer == 0 {
+            return self.hash_leaf_block(index);
+        }
+        let children = (0..ARITY)
+            .map(|i| self.node(layer - 1, ARITY * index + i))
+            .collect::<Vec<H::Hash>>();
+        hash_tagged_nodes::<H>(&children)
+    }
+
+    /// Hashes the `block_index`-th leaf block, followed by its salt if the tree is salted.
+    fn hash_leaf_block(&self, block_index: usize) -> H::Hash {
+        let leaf_block = self.get_leaf_block(block_index);
+        let salt = self
+            .salts
+            .get(block_index)
+            .map_or(&[][..], |salt| salt.as_ref());
+        let endianness = self.config.endianness;
+        hash_salted_leaf::<H>(&endianness.encode_felts(&leaf_block), salt)
     }
 
     fn get_leaf_block(&self, block_index: usize) -> Vec<T> {
@@ -147,13 +712,118 @@ where
         )
     }
 }
+
+#[cfg(feature = "avx512")]
+impl<H: Hasher, const ARITY: usize> MerkleTree<BaseField, H, ARITY>
+where
+    BaseField: IntoSlice<H::NativeType>,
+    H::NativeType: Copy + From<u8>,
+{
+    /// Commits on columns in the packed representation of the AVX512 backend, i.e. 16 consecutive
+    /// rows of a column per vector. Lanes are reduced and serialized in row order, so the tree
+    /// equals the tree of [MerkleTree::commit_columns] on the unpacked columns.
+    ///
+    /// Requires a CPU with AVX512F support, see [crate::platform::avx512_detected].
+    pub fn commit_packed_columns(columns: &[&[PackedBaseField]]) -> Result<Self, error::Error> {
+        validate_columns(columns.iter().map(|c| c.len() * K_BLOCK_SIZE))?;
+        Ok(Self::commit(
+            columns
+                .iter()
+                .map(|column| column.iter().flat_map(|packed| packed.to_array()).collect())
+                .collect(),
+        ))
+    }
+}
+
+/// Concatenates the native elements of `hashes`.
+fn flatten_hashes<H: Hasher>(hashes: &[H::Hash]) -> TreeLayer<H::NativeType>
+where
+    H::NativeType: Copy,
+{
+    hashes
+        .iter()
+        .flat_map(|hash| hash.as_ref().iter().copied())
+        .collect()
+}
+
+/// The root of a tree committing to no columns, e.g. `MerkleTree::commit(vec![])`: the hash of
+/// the empty input, [Hasher::empty_hash]. It differs from the root of any non-empty tree, whose
+/// hashes are all domain separated, see [Hasher::hash_leaf].
+pub fn root_of_empty<H: Hasher>() -> H::Hash {
+    H::empty_hash()
+}
+
+/// Checks that the columns of the given lengths form a non-empty trace.
+fn validate_columns(mut lengths: impl Iterator<Item = usize>) -> Result<(), MerkleTreeError> {
+    let n_rows = lengths.next().ok_or(MerkleTreeError::NoColumns)?;
+    if n_rows == 0 {
+        return Err(MerkleTreeError::EmptyColumns);
+    }
+    if let Some((column, length)) = lengths.find_position(|&length| length != n_rows) {
+        return Err(MerkleTreeError::ColumnLengthMismatch {
+            column: column + 1,
+            length,
+            expected: n_rows,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleTreeError {
+    QueryOutOfRange {
+        query: usize,
+        n_rows: usize,
+    },
+    NoColumns,
+    EmptyColumns,
+    ColumnLengthMismatch {
+        column: usize,
+        length: usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for MerkleTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueryOutOfRange { query, n_rows } => write!(
+                f,
+                "Query {query} is out of range for a tree of {n_rows} rows."
+            ),
+            Self::NoColumns => write!(f, "Attempted committing to zero columns."),
+            Self::EmptyColumns => write!(f, "Attempted committing to empty columns."),
+            Self::ColumnLengthMismatch {
+                column,
+                length,
+                expected,
+            } => write!(
+                f,
+                "Column {column} has length {length}, expected {expected}."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleTreeError {}
+
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
+    use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
     use crate::commitment_scheme::blake3_hash::*;
+    use crate::commitment_scheme::error::Error;
     use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::merkle_decommitment::MerkleDecommitment;
+    use crate::commitment_scheme::merkle_tree::{root_of_empty, MerkleTreeConfig, PaddingMode};
+    use crate::commitment_scheme::serialize::Endianness;
     use crate::commitment_scheme::utils::tests::generate_test_queries;
+    use crate::commitment_scheme::utils::{hash_root, hash_tagged_nodes};
     use crate::core::fields::m31::M31;
     use crate::core::fields::IntoSlice;
+    use crate::m31;
 
     fn init_m31_test_trace(len: usize) -> Vec<M31> {
         assert!(len.is_power_of_two());
@@ -188,10 +858,680 @@ mod tests {
 
         assert_eq!(
             hex::encode(tree_from_matrix.root()),
-            "c07e98e8a5d745ea99c3c3eac4c43b9df5ceb9e78973a785d90b3ffe4d5fcf5e"
+            "f463371871f1a1c97bfe0663b417da37bb43c4be0bc2bccfee0ea0be226302a7"
+        );
+    }
+
+    #[test]
+    pub fn commit_single_row_test() {
+        let matrix = vec![init_m31_test_trace(1); 2];
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(matrix.clone());
+
+        assert_eq!(tree.height, 2);
+        let leaf_hash = Blake2sHasher::hash_leaf(<M31 as IntoSlice<u8>>::into_slice(&[
+            matrix[0][0],
+            matrix[1][0],
+        ]));
+        assert_eq!(tree.root(), hash_root::<Blake2sHasher>(&leaf_hash, 1));
+    }
+
+    #[test]
+    pub fn commit_two_rows_test() {
+        let trace = init_m31_test_trace(2);
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![trace.clone()]);
+
+        // Both rows fit in a single leaf block.
+        assert_eq!(tree.height, 2);
+        let leaf_hash = Blake2sHasher::hash_leaf(<M31 as IntoSlice<u8>>::into_slice(&trace));
+        assert_eq!(tree.root(), hash_root::<Blake2sHasher>(&leaf_hash, 2));
+    }
+
+    #[test]
+    pub fn commit_non_power_of_two_test() {
+        let trace = (0..100).map(M31::from_u32_unchecked).collect::<Vec<_>>();
+        let mut padded_trace = trace.clone();
+        padded_trace.resize(128, trace[99]);
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![trace; 2]);
+        let padded_tree = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![padded_trace; 2]);
+        let queries = vec![0, 50, 99];
+        let decommitment = tree.generate_decommitment(queries.clone());
+
+        // Padding duplicates the last row, but the root binds the number of rows.
+        assert_eq!(tree.data, padded_tree.data);
+        assert_ne!(tree.root(), padded_tree.root());
+        assert!(decommitment.verify(tree.root(), &queries));
+        assert!(!decommitment.verify(padded_tree.root(), &queries));
+    }
+
+    #[test]
+    pub fn commit_blake2s_test() {
+        let trace = init_m31_test_trace(64);
+        let matrix = vec![trace; 4];
+
+        let tree_from_matrix = super::MerkleTree::<M31, Blake2sHasher>::commit(matrix);
+
+        assert_eq!(
+            hex::encode(tree_from_matrix.root()),
+            "a7e1080bc37f2490e0a6621a05bd65f87cebf2f58dbdcb811b0bed6312c173d6"
+        );
+    }
+
+    #[test]
+    pub fn commit_domain_separation_test() {
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![init_m31_test_trace(128)]);
+        let leaf_hash = |i: usize| -> Blake2sHash { tree.layer(0)[i] };
+
+        assert_eq!(
+            leaf_hash(0),
+            Blake2sHasher::hash_leaf(<M31 as IntoSlice<u8>>::into_slice(&tree.get_leaf_block(0)))
+        );
+        assert_eq!(
+            tree.layer(1)[0],
+            Blake2sHasher::hash_nodes(&leaf_hash(0), &leaf_hash(1))
+        );
+    }
+
+    #[test]
+    pub fn root_binds_shape_test() {
+        // Both traces pack the same two values into a single leaf block.
+        let column = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![vec![m31!(1), m31!(2)]]);
+        let row =
+            super::MerkleTree::<M31, Blake2sHasher>::commit(vec![vec![m31!(1)], vec![m31!(2)]]);
+
+        assert_eq!(column.data, row.data);
+        assert_ne!(column.root(), row.root());
+    }
+
+    #[test]
+    pub fn commit_columns_test() {
+        let columns = (0..3)
+            .map(|i| (0..4).map(|j| m31!(10 * i + j)).collect())
+            .collect::<Vec<Vec<M31>>>();
+        let column_refs = columns.iter().map(|c| &c[..]).collect::<Vec<_>>();
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit_columns(&column_refs).unwrap();
+        let decommitment = tree.generate_decommitment(vec![2]);
+
+        assert_eq!(
+            tree.root(),
+            super::MerkleTree::<M31, Blake2sHasher>::commit(columns).root()
+        );
+        assert!(decommitment.verify(tree.root(), &[2]));
+        assert_eq!(
+            decommitment.values().collect::<Vec<_>>(),
+            vec![vec![m31!(2), m31!(12), m31!(22)]]
         );
     }
 
+    #[test]
+    pub fn commit_single_column_test() {
+        let column = init_m31_test_trace(8);
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit_columns(&[&column]).unwrap();
+
+        assert_eq!(
+            tree.root(),
+            super::MerkleTree::<M31, Blake2sHasher>::commit(vec![column]).root()
+        );
+    }
+
+    #[test]
+    pub fn empty_tree_test() {
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![]);
+        let single_row_tree = super::MerkleTree::<M31, Blake2sHasher>::commit(vec![vec![m31!(0)]]);
+
+        assert_eq!(tree.root(), root_of_empty::<Blake2sHasher>());
+        assert_eq!(tree.root(), Blake2sHasher::hash(&[]));
+        assert_ne!(tree.root(), single_row_tree.root());
+        assert_eq!(tree.n_rows(), 0);
+        assert_eq!(tree.layers().collect::<Vec<_>>(), [[tree.root()]]);
+        assert_eq!(tree.recompute_layer(0), [tree.root()]);
+        assert!(matches!(
+            tree.try_generate_decommitment(vec![0]),
+            Err(Error::IndexOutOfRange { index: 0, len: 0 })
+        ));
+    }
+
+    #[test]
+    pub fn commit_columns_invalid_test() {
+        type Tree = super::MerkleTree<M31, Blake2sHasher>;
+        let column = init_m31_test_trace(4);
+
+        assert!(matches!(Tree::commit_columns(&[]), Err(Error::NoColumns)));
+        assert!(matches!(
+            Tree::commit_columns(&[&[]]),
+            Err(Error::EmptyColumns)
+        ));
+        assert!(matches!(
+            Tree::commit_columns(&[&column, &column[..3]]),
+            Err(Error::MismatchedColumnLengths {
+                column: 1,
+                length: 3,
+                expected: 4
+            })
+        ));
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    pub fn commit_matrix_test() {
+        use ndarray::ShapeBuilder;
+
+        let matrix = ndarray::Array2::from_shape_fn((100, 3), |(row, column)| {
+            M31::from_u32_unchecked((row * 3 + column) as u32)
+        });
+        let columns = (0..3)
+            .map(|column| {
+                (0..100)
+                    .map(|row| M31::from_u32_unchecked((row * 3 + column) as u32))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let column_refs = columns.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit_matrix(&matrix).unwrap();
+        let mut column_major_matrix = ndarray::Array2::zeros((100, 3).f());
+        column_major_matrix.assign(&matrix);
+        let column_major_tree =
+            super::MerkleTree::<M31, Blake2sHasher>::commit_matrix(&column_major_matrix).unwrap();
+
+        assert_eq!(
+            tree.root(),
+            super::MerkleTree::<M31, Blake2sHasher>::commit_columns(&column_refs)
+                .unwrap()
+                .root()
+        );
+        assert_eq!(column_major_tree.root(), tree.root());
+        assert!(matches!(
+            super::MerkleTree::<M31, Blake2sHasher>::commit_matrix(&ndarray::Array2::zeros((0, 3))),
+            Err(Error::EmptyColumns)
+        ));
+        assert!(matches!(
+            super::MerkleTree::<M31, Blake2sHasher>::commit_matrix(&ndarray::Array2::zeros((4, 0))),
+            Err(Error::NoColumns)
+        ));
+    }
+
+    #[test]
+    pub fn build_into_test() {
+        let trace = vec![(0..100).map(M31::from_u32_unchecked).collect::<Vec<_>>(); 3];
+        let binary_tree = super::MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        let quaternary_tree = super::MerkleTree::<M31, Blake2sHasher, 4>::commit(trace.clone());
+        let mut arena = Vec::new();
+
+        let binary_offsets = super::MerkleTree::<M31, Blake2sHasher>::build_into(
+            trace.clone(),
+            &MerkleTreeConfig::default(),
+            &mut arena,
+        );
+        let quaternary_offsets = super::MerkleTree::<M31, Blake2sHasher, 4>::build_into(
+            trace,
+            &MerkleTreeConfig::default(),
+            &mut arena,
+        );
+
+        for (tree_layers, offsets) in [
+            (binary_tree.layers().collect::<Vec<_>>(), &binary_offsets),
+            (
+                quaternary_tree.layers().collect::<Vec<_>>(),
+                &quaternary_offsets,
+            ),
+        ] {
+            let arena_layers = offsets
+                .iter()
+                .tuple_windows()
+                .map(|(start, end)| arena[*start..*end].to_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(arena_layers, tree_layers);
+        }
+        assert_eq!(binary_offsets[0], 0);
+        assert_eq!(quaternary_offsets[0], *binary_offsets.last().unwrap());
+        assert_eq!(*quaternary_offsets.last().unwrap(), arena.len());
+        assert_eq!(
+            hash_root::<Blake2sHasher>(&arena[binary_offsets[binary_offsets.len() - 2]], 100),
+            binary_tree.root()
+        );
+    }
+
+    #[test]
+    pub fn build_into_with_config_test() {
+        let trace = vec![(0..100).map(M31::from_u32_unchecked).collect::<Vec<_>>(); 3];
+        let configs = [
+            MerkleTreeConfig {
+                padding: PaddingMode::ZeroHash,
+                endianness: Endianness::Big,
+            },
+            MerkleTreeConfig {
+                padding: PaddingMode::Custom(Blake2sHasher::hash(b"padding")),
+                endianness: Endianness::Little,
+            },
+        ];
+
+        for config in &configs {
+            let tree = super::MerkleTree::<M31, Blake2sHasher, 4>::build_with_config(
+                trace.clone(),
+                *config,
+            );
+            let mut arena = Vec::new();
+
+            let offsets = super::MerkleTree::<M31, Blake2sHasher, 4>::build_into(
+                trace.clone(),
+                config,
+                &mut arena,
+            );
+
+            let arena_layers = offsets
+                .iter()
+                .tuple_windows()
+                .map(|(start, end)| arena[*start..*end].to_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(arena_layers, tree.layers().collect::<Vec<_>>());
+            assert_eq!(
+                hash_root::<Blake2sHasher>(&arena[offsets[offsets.len() - 2]], 100),
+                tree.root()
+            );
+            assert_ne!(
+                tree.root(),
+                super::MerkleTree::<M31, Blake2sHasher, 4>::commit(trace.clone()).root()
+            );
+        }
+    }
+
+    #[test]
+    pub fn build_capped_test() {
+        let trace = vec![init_m31_test_trace(512); 2];
+        let queries = vec![0, 1, 77, 200, 201, 511];
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        for cap_log in [0, 2, 20] {
+            let capped_tree =
+                super::MerkleTree::<M31, Blake2sHasher>::build_capped(trace.clone(), cap_log);
+            let decommitment = tree.generate_decommitment(queries.clone());
+            let capped_decommitment = capped_tree.generate_decommitment(queries.clone());
+
+            assert_eq!(capped_tree.root(), tree.root());
+            assert_eq!(capped_decommitment.leaf_blocks, decommitment.leaf_blocks);
+            assert_eq!(capped_decommitment.layers, decommitment.layers);
+            assert!(capped_decommitment.verify(capped_tree.root(), &queries));
+        }
+    }
+
+    #[test]
+    pub fn build_capped_with_config_test() {
+        let trace = vec![(0..300).map(M31::from_u32_unchecked).collect::<Vec<_>>(); 2];
+        let queries = vec![0, 1, 77, 200, 299];
+        let config = MerkleTreeConfig {
+            padding: PaddingMode::Custom(Blake2sHasher::hash(b"padding")),
+            endianness: Endianness::Big,
+        };
+
+        let tree =
+            super::MerkleTree::<M31, Blake2sHasher>::build_with_config(trace.clone(), config);
+        let decommitment = tree.generate_decommitment(queries.clone());
+        for cap_log in [0, 2, 20] {
+            let capped_tree = super::MerkleTree::<M31, Blake2sHasher>::build_capped_with_config(
+                trace.clone(),
+                cap_log,
+                config,
+            );
+            let capped_decommitment = capped_tree.generate_decommitment(queries.clone());
+
+            assert_eq!(capped_tree.root(), tree.root());
+            assert_eq!(capped_decommitment.leaf_blocks, decommitment.leaf_blocks);
+            assert_eq!(capped_decommitment.layers, decommitment.layers);
+            assert!(capped_decommitment.verify(capped_tree.root(), &queries));
+        }
+        assert_ne!(
+            tree.root(),
+            super::MerkleTree::<M31, Blake2sHasher>::build_capped(trace, 0).root()
+        );
+    }
+
+    fn assert_arity_tree_valid<const ARITY: usize>(n_rows: usize, expected_depth: usize) {
+        // 4 columns of M31 fill 4 rows per 64 byte leaf block.
+        let trace = vec![init_m31_test_trace(n_rows); 4];
+        let tree = super::MerkleTree::<M31, Blake2sHasher, ARITY>::commit(trace.clone());
+        let capped_tree = super::MerkleTree::<M31, Blake2sHasher, ARITY>::build_capped(trace, 1);
+        let queries = generate_test_queries(10, n_rows);
+        let decommitment = tree.generate_decommitment(queries.clone());
+        let capped_decommitment = capped_tree.generate_decommitment(queries.clone());
+        let single_decommitment = tree.generate_decommitment(vec![n_rows - 1]);
+
+        assert_eq!(tree.height - 2, expected_depth);
+        assert_eq!(tree.data.last().unwrap().len(), 1);
+        assert!(decommitment.verify(tree.root(), &queries));
+        assert_eq!(tree.check_decommitment(&decommitment, &queries), Ok(()));
+        assert_eq!(capped_tree.root(), tree.root());
+        assert_eq!(capped_decommitment.layers, decommitment.layers);
+        assert!(single_decommitment.verify(tree.root(), &[n_rows - 1]));
+        assert!(single_decommitment
+            .layers
+            .iter()
+            .all(|layer| layer.len() == ARITY - 1));
+    }
+
+    #[test]
+    pub fn from_leaf_hashes_test() {
+        let trace = vec![init_m31_test_trace(256); 3];
+        let leaf_hashes = super::MerkleTree::<M31, Blake2sHasher>::hash_leaves(&trace);
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        let tree_from_leaves =
+            super::MerkleTree::<M31, Blake2sHasher>::from_leaf_hashes(trace.clone(), &leaf_hashes);
+        let wide_tree = super::MerkleTree::<M31, Blake2sHasher, 4>::commit(trace.clone());
+        let wide_tree_from_leaves =
+            super::MerkleTree::<M31, Blake2sHasher, 4>::from_leaf_hashes(trace, &leaf_hashes);
+
+        assert_eq!(tree_from_leaves.root(), tree.root());
+        assert_eq!(tree_from_leaves.data, tree.data);
+        assert_eq!(wide_tree_from_leaves.root(), wide_tree.root());
+        assert_ne!(wide_tree.root(), tree.root());
+    }
+
+    #[test]
+    pub fn hash_leaves_padded_test() {
+        for n_columns in [1, 3] {
+            let trace = (0..n_columns)
+                .map(|j| (0..100).map(|i| m31!(i * 7 + j)).collect())
+                .collect::<Vec<Vec<M31>>>();
+
+            let leaf_hashes = super::MerkleTree::<M31, Blake2sHasher>::hash_leaves(&trace);
+            let tree_from_leaves = super::MerkleTree::<M31, Blake2sHasher>::from_leaf_hashes(
+                trace.clone(),
+                &leaf_hashes,
+            );
+
+            assert_eq!(
+                tree_from_leaves.root(),
+                super::MerkleTree::<M31, Blake2sHasher>::commit(trace).root()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Leaf hashes don't match the trace!")]
+    pub fn from_leaf_hashes_wrong_length_test() {
+        let trace = vec![init_m31_test_trace(256); 3];
+        let leaf_hashes = super::MerkleTree::<M31, Blake2sHasher>::hash_leaves(&trace);
+
+        super::MerkleTree::<M31, Blake2sHasher>::from_leaf_hashes(trace, &leaf_hashes[1..]);
+    }
+
+    #[test]
+    pub fn layers_test() {
+        let trace = vec![init_m31_test_trace(64); 4];
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        let capped_tree = super::MerkleTree::<M31, Blake2sHasher>::build_capped(trace.clone(), 1);
+
+        let layers = tree.layers().collect::<Vec<_>>();
+
+        assert_eq!(layers.len(), tree.height - 1);
+        assert_eq!(
+            layers[0],
+            super::MerkleTree::<M31, Blake2sHasher>::hash_leaves(&trace)
+        );
+        assert_eq!(
+            tree.root(),
+            hash_root::<Blake2sHasher>(&layers.last().unwrap()[0], 64)
+        );
+        assert_eq!(layers[2], tree.layer(2));
+        assert!((0..layers.len()).all(|i| capped_tree.recompute_layer(i) == layers[i]));
+        assert_eq!(capped_tree.layer(layers.len() - 1), *layers.last().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Layer 0 is not stored by the capped tree!")]
+    pub fn capped_layer_not_stored_test() {
+        let trace = vec![init_m31_test_trace(64); 4];
+        let capped_tree = super::MerkleTree::<M31, Blake2sHasher>::build_capped(trace, 1);
+
+        capped_tree.layer(0);
+    }
+
+    #[test]
+    pub fn arity_test() {
+        // 64 leaf blocks.
+        assert_arity_tree_valid::<2>(256, 6);
+        assert_arity_tree_valid::<4>(256, 3);
+        assert_arity_tree_valid::<8>(256, 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn build_bit_reversed_test() {
+        use crate::core::backend::cpu::CPUCircleEvaluation;
+        use crate::core::poly::circle::CanonicCoset;
+        use crate::core::poly::NaturalOrder;
+
+        let log_size = 6;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let values = (0..1 << log_size)
+            .map(|i| M31::from_u32_unchecked(i * i))
+            .collect::<Vec<_>>();
+        let evaluation = CPUCircleEvaluation::<M31, NaturalOrder>::new(domain, values.clone());
+        let bit_reversed_evaluation = evaluation.bit_reverse();
+
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::build_bit_reversed(vec![values]);
+        let queries = vec![1, 6, 50];
+        let decommitment = tree.generate_decommitment(queries.clone());
+
+        assert_eq!(
+            tree.root(),
+            super::MerkleTree::<M31, Blake2sHasher>::commit(vec![bit_reversed_evaluation
+                .values
+                .clone()])
+            .root()
+        );
+        assert!(decommitment.verify(tree.root(), &queries));
+        assert_eq!(
+            decommitment.values().collect::<Vec<_>>(),
+            queries
+                .iter()
+                .map(|&i| vec![bit_reversed_evaluation.values[i]])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn commit_multiple_test() {
+        let trace = [init_m31_test_trace(64), init_m31_test_trace(64)];
+        let interaction = [init_m31_test_trace(16)];
+        let composition = vec![init_m31_test_trace(128); 4];
+        let groups = [
+            trace.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            interaction.iter().map(Vec::as_slice).collect(),
+            composition.iter().map(Vec::as_slice).collect(),
+        ];
+        let group_refs = groups.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let roots = super::MerkleTree::<M31, Blake2sHasher>::commit_multiple(&group_refs).unwrap();
+
+        assert_eq!(
+            roots,
+            group_refs
+                .iter()
+                .map(|columns| {
+                    super::MerkleTree::<M31, Blake2sHasher>::commit_columns(columns)
+                        .unwrap()
+                        .root()
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            super::MerkleTree::<M31, Blake2sHasher>::commit_multiple(&[
+                group_refs[0],
+                &[&trace[0][..], &interaction[0][..]]
+            ])
+            .err(),
+            Some(Error::MismatchedColumnLengths {
+                column: 1,
+                length: 16,
+                expected: 64
+            })
+        );
+    }
+
+    #[cfg(all(feature = "avx512", target_arch = "x86_64", target_feature = "avx512f"))]
+    #[test]
+    fn commit_packed_columns_test() {
+        use crate::core::backend::avx512::{PackedBaseField, K_BLOCK_SIZE};
+
+        if !crate::platform::avx512_detected() {
+            return;
+        }
+        let columns = (0..3)
+            .map(|i| {
+                init_m31_test_trace(4 * K_BLOCK_SIZE)
+                    .iter()
+                    .map(|x| *x * m31!(i + 1))
+                    .collect()
+            })
+            .collect::<Vec<Vec<M31>>>();
+        let packed_columns = columns
+            .iter()
+            .map(|column| {
+                column
+                    .chunks_exact(K_BLOCK_SIZE)
+                    .map(|chunk| PackedBaseField::from_array(chunk.try_into().unwrap()))
+                    .collect()
+            })
+            .collect::<Vec<Vec<PackedBaseField>>>();
+        let column_refs = columns.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let packed_refs = packed_columns.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let packed_tree =
+            super::MerkleTree::<M31, Blake2sHasher>::commit_packed_columns(&packed_refs).unwrap();
+
+        assert_eq!(
+            packed_tree.root(),
+            super::MerkleTree::<M31, Blake2sHasher>::commit_columns(&column_refs)
+                .unwrap()
+                .root()
+        );
+        assert_eq!(
+            super::MerkleTree::<M31, Blake2sHasher>::commit_packed_columns(&[
+                packed_refs[0],
+                &packed_refs[1][1..]
+            ])
+            .err(),
+            Some(Error::MismatchedColumnLengths {
+                column: 1,
+                length: 3 * K_BLOCK_SIZE,
+                expected: 4 * K_BLOCK_SIZE
+            })
+        );
+    }
+
+    #[test]
+    fn padding_mode_test() {
+        // 16 columns of M31 fill a leaf block per row, i.e. the tree has 3 leaves.
+        let trace = (0..16)
+            .map(|i| (0..3).map(|j| M31::from_u32_unchecked(i * j)).collect())
+            .collect::<Vec<Vec<M31>>>();
+        let build = |padding| {
+            super::MerkleTree::<M31, Blake2sHasher>::build_with_config(
+                trace.clone(),
+                MerkleTreeConfig {
+                    padding,
+                    ..Default::default()
+                },
+            )
+        };
+        let trees = [
+            build(PaddingMode::DuplicateLast),
+            build(PaddingMode::ZeroHash),
+            build(PaddingMode::Custom(Blake2sHasher::hash(b"padding"))),
+        ];
+
+        assert_eq!(
+            trees[0].root(),
+            super::MerkleTree::<M31, Blake2sHasher>::commit(trace.clone()).root()
+        );
+        assert_eq!(trees[0].n_rows(), 4);
+        assert_eq!(trees[1].n_rows(), 3);
+        assert_eq!(trees[1].layer(0).len(), 3);
+        assert_ne!(trees[0].root(), trees[1].root());
+        assert_ne!(trees[0].root(), trees[2].root());
+        assert_ne!(trees[1].root(), trees[2].root());
+        for tree in &trees {
+            for queries in [vec![0], vec![2], vec![0, 1, 2]] {
+                let decommitment = tree.generate_decommitment(queries.clone());
+
+                assert!(decommitment.verify(tree.root(), &queries));
+                assert_eq!(tree.check_decommitment(&decommitment, &queries), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn big_endian_commit_test() {
+        let trace = vec![(0..64).map(M31::from_u32_unchecked).collect::<Vec<_>>(); 3];
+        let big_endian_config = MerkleTreeConfig {
+            endianness: Endianness::Big,
+            ..Default::default()
+        };
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::commit(trace.clone());
+        let big_endian_tree =
+            super::MerkleTree::<M31, Blake2sHasher>::build_with_config(trace, big_endian_config);
+        let decommitment = big_endian_tree.generate_decommitment(vec![5, 40]);
+        let parsed =
+            MerkleDecommitment::<M31, Blake2sHasher>::from_bytes(&decommitment.to_bytes()).unwrap();
+
+        assert_ne!(big_endian_tree.root(), tree.root());
+        assert!(decommitment.verify(big_endian_tree.root(), &[5, 40]));
+        assert!(decommitment
+            .compress()
+            .verify(big_endian_tree.root(), &[5, 40]));
+        assert!(!parsed.verify(big_endian_tree.root(), &[5, 40]));
+        assert!(parsed
+            .with_endianness(Endianness::Big)
+            .verify(big_endian_tree.root(), &[5, 40]));
+    }
+
+    #[test]
+    pub fn arity_padding_test() {
+        // 128 leaf blocks, the top layer of the 4-ary tree has 2 nodes and 2 padding nodes.
+        assert_arity_tree_valid::<4>(512, 4);
+        assert_arity_tree_valid::<8>(512, 3);
+
+        let tree =
+            super::MerkleTree::<M31, Blake2sHasher, 4>::commit(vec![init_m31_test_trace(512); 4]);
+        let padding = Blake2sHash::default();
+        let top_children = tree.data[tree.height - 3]
+            .iter()
+            .copied()
+            .chain([padding, padding])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tree.root(),
+            hash_root::<Blake2sHasher>(&hash_tagged_nodes::<Blake2sHasher>(&top_children), 512)
+        );
+    }
+
+    #[test]
+    pub fn build_capped_memory_test() {
+        let tree = super::MerkleTree::<M31, Blake2sHasher>::build_capped(
+            vec![init_m31_test_trace(256)],
+            1,
+        );
+
+        let stored_layer_sizes = tree
+            .data
+            .iter()
+            .map(|layer| layer.len())
+            .collect::<Vec<_>>();
+
+        // Only the root and its children are stored.
+        let n_layers = stored_layer_sizes.len();
+        assert!(stored_layer_sizes[..n_layers - 2]
+            .iter()
+            .all(|size| *size == 0));
+        assert_eq!(stored_layer_sizes[n_layers - 2..], [2, 1]);
+    }
+
     #[test]
     pub fn get_leaf_block_test() {
         let trace = vec![init_m31_test_trace(128)];
@@ -209,6 +1549,96 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn try_generate_decommitment_test() {
+        let trace = vec![init_m31_test_trace(128); 3];
+        let tree = super::MerkleTree::<M31, Blake3Hasher>::commit(trace);
+
+        let decommitment = tree.try_generate_decommitment(vec![127]).unwrap();
+
+        assert!(decommitment.verify(tree.root(), &[127]));
+        assert!(matches!(
+            tree.try_generate_decommitment(vec![3, 128]),
+            Err(Error::IndexOutOfRange {
+                index: 128,
+                len: 128
+            })
+        ));
+    }
+
+    #[test]
+    pub fn single_row_decommitment_test() {
+        let tree = super::MerkleTree::<M31, Blake3Hasher>::commit(vec![init_m31_test_trace(1)]);
+
+        let decommitment = tree.try_generate_decommitment(vec![0]).unwrap();
+
+        assert!(decommitment.layers.is_empty());
+        assert!(decommitment.verify(tree.root(), &[0]));
+    }
+
+    #[test]
+    pub fn batch_decommitment_test() {
+        const TRACE_LEN: usize = 128;
+        let tree =
+            super::MerkleTree::<M31, Blake3Hasher>::commit(vec![init_m31_test_trace(TRACE_LEN); 4]);
+        let queries: Vec<usize> = (0..TRACE_LEN).collect();
+
+        let batch_decommitment = tree.generate_decommitment(queries.clone());
+        let n_batch_hashes: usize = batch_decommitment.layers.iter().map(Vec::len).sum();
+        let n_single_hashes: usize = queries
+            .iter()
+            .map(|query| {
+                let decommitment = tree.generate_decommitment(vec![*query]);
+                decommitment.layers.iter().map(Vec::len).sum::<usize>()
+            })
+            .sum();
+
+        assert!(batch_decommitment.verify(tree.root(), &queries));
+        assert_eq!(n_batch_hashes, 0);
+        assert!(n_batch_hashes < n_single_hashes);
+    }
+
+    #[test]
+    pub fn batch_decommitment_deterministic_test() {
+        let tree = super::MerkleTree::<M31, Blake3Hasher>::commit(vec![init_m31_test_trace(128)]);
+
+        let decommitment = tree.generate_decommitment(vec![3, 70, 20]);
+        let permuted_decommitment = tree.generate_decommitment(vec![70, 3, 20, 3]);
+
+        assert_eq!(decommitment.leaf_blocks, permuted_decommitment.leaf_blocks);
+        assert_eq!(decommitment.layers, permuted_decommitment.layers);
+        assert!(permuted_decommitment.verify(tree.root(), &[70, 3, 20, 3]));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    pub fn parallel_commit_deterministic_test() {
+        // The global pool (configured by `RAYON_NUM_THREADS`) can only be initialized once, so
+        // each thread count gets its own pool.
+        let commit_with_threads = |n_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    super::MerkleTree::<M31, Blake2sHasher>::commit(vec![
+                        init_m31_test_trace(
+                            1 << 12
+                        );
+                        4
+                    ])
+                })
+        };
+
+        let reference_tree = commit_with_threads(1);
+
+        for n_threads in [2, 8] {
+            let tree = commit_with_threads(n_threads);
+            assert_eq!(tree.root(), reference_tree.root());
+            assert_eq!(tree.data, reference_tree.data);
+        }
+    }
+
     #[test]
     pub fn test_decommitment() {
         let trace = vec![init_m31_test_trace(128)];
@@ -225,7 +1655,7 @@ mod tests {
         assert_eq!(decommitment.layers[0].len(), 1);
         assert_eq!(
             decommitment.layers[0][0],
-            Blake3Hasher::hash(<M31 as IntoSlice<u8>>::into_slice(&tree.get_leaf_block(0)))
+            Blake3Hasher::hash_leaf(<M31 as IntoSlice<u8>>::into_slice(&tree.get_leaf_block(0)))
         );
 
         // The queried leaves' parents can be computed by verifer therefore excluded from the proof.
@@ -235,4 +1665,80 @@ mod tests {
         // the proof only needs to contain the right child.
         assert_eq!(decommitment.layers[2].len(), 1);
     }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_spans_test() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        type Spans = Arc<Mutex<Vec<(&'static str, Vec<(&'static str, u64)>)>>>;
+
+        struct SpanRecorder {
+            spans: Spans,
+            next_id: AtomicU64,
+        }
+
+        struct FieldRecorder(Vec<(&'static str, u64)>);
+
+        impl Visit for FieldRecorder {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                self.0.push((field.name(), value));
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        impl Subscriber for SpanRecorder {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut fields = FieldRecorder(Vec::new());
+                span.record(&mut fields);
+                self.spans
+                    .lock()
+                    .unwrap()
+                    .push((span.metadata().name(), fields.0));
+                Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+            }
+
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+            fn event(&self, _event: &Event<'_>) {}
+
+            fn enter(&self, _span: &Id) {}
+
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let spans = Spans::default();
+        let subscriber = SpanRecorder {
+            spans: spans.clone(),
+            next_id: AtomicU64::new(1),
+        };
+        // 64 rows of a single M31 column fill 4 leaf blocks.
+        let trace = vec![init_m31_test_trace(64)];
+
+        tracing::subscriber::with_default(subscriber, || {
+            super::MerkleTree::<M31, Blake3Hasher>::commit(trace).generate_decommitment(vec![0, 5]);
+        });
+
+        assert_eq!(
+            *spans.lock().unwrap(),
+            vec![
+                ("hash_leaves", vec![("size", 4)]),
+                ("hash_layer", vec![("layer", 1), ("size", 2)]),
+                ("hash_layer", vec![("layer", 2), ("size", 1)]),
+                ("generate_decommitment", vec![("n_queries", 2)]),
+            ]
+        );
+    }
 }
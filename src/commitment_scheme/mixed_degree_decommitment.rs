@@ -1,9 +1,10 @@
-use std::iter::Peekable;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Peekable;
 
-use itertools::Itertools;
-use merging_iterator::MergeIter;
+use itertools::{merge, Itertools};
 
-use super::hasher::Hasher;
+use super::hasher::{Hash, Hasher};
 use super::merkle_input::MerkleTreeConfig;
 use super::mixed_degree_merkle_tree::queried_nodes_in_layer;
 use crate::core::fields::{Field, IntoSlice};
@@ -60,7 +61,7 @@ impl<F: Field, H: Hasher> MixedDecommitment<F, H> {
             );
         }
         debug_assert_eq!(next_layer_hashes.len(), 1);
-        next_layer_hashes[0] == root
+        next_layer_hashes[0].ct_eq(&root).into()
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -79,7 +80,7 @@ impl<F: Field, H: Hasher> MixedDecommitment<F, H> {
     {
         let directly_queried_node_indices =
             queried_nodes_in_layer(queries_to_layer.iter(), structure, layer_depth);
-        let mut node_indices = MergeIter::new(
+        let mut node_indices = merge(
             directly_queried_node_indices.iter().copied(),
             previous_layers_indices.clone().map(|q| q / 2),
         )
@@ -1,7 +1,9 @@
-use std::iter::Peekable;
+use alloc::borrow::ToOwned;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Peekable;
 
-use itertools::Itertools;
-use merging_iterator::MergeIter;
+use itertools::{merge, Itertools};
 
 use super::hasher::Hasher;
 use super::merkle_input::{MerkleTreeConfig, MerkleTreeInput};
@@ -209,7 +211,7 @@ where
                 directly_queried_node_indices.iter().copied(),
             ))
             .peekable();
-        let mut node_indices = MergeIter::new(
+        let mut node_indices = merge(
             directly_queried_node_indices.iter().copied(),
             previous_layers_indices.clone().map(|q| q / 2),
         )
@@ -526,8 +528,8 @@ mod tests {
 
         assert_eq!(translated_queries[0], expeted_queries_at_depth_4);
         assert_eq!(translated_queries[1], expeted_queries_at_depth_3);
-        assert_eq!(translated_queries[2], vec![]);
-        assert_eq!(translated_queries[3], vec![]);
+        assert_eq!(translated_queries[2], Vec::<usize>::new());
+        assert_eq!(translated_queries[3], Vec::<usize>::new());
     }
 
     #[test]
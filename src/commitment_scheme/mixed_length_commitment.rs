@@ -0,0 +1,160 @@
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display};
+
+use super::error::Error;
+use super::hasher::Hasher;
+use super::merkle_decommitment::MerkleDecommitment;
+use super::merkle_tree::MerkleTree;
+use crate::core::fields::{Field, IntoSlice};
+
+/// A commitment on columns of different lengths, in a single [MerkleTree]. Columns are padded with
+/// zero field elements up to the power of two at or above the length of the longest column, which
+/// keeps every row openable, padding rows included, without the tree duplicating any of them. The
+/// original lengths are recorded, so that the verifier knows which values are padding, see
+/// [MixedLengthCommitment::check_padding].
+///
+/// Unlike [super::mixed_degree_merkle_tree::MixedDegreeMerkleTree], which injects shorter columns
+/// at higher layers, the padding is committed to like any other value.
+pub struct MixedLengthCommitment<T: Field + Sized + Debug + Display, H: Hasher> {
+    pub tree: MerkleTree<T, H>,
+    pub column_lengths: Vec<usize>,
+}
+
+impl<T: Field + Sized + Copy + Debug + Display, H: Hasher> MixedLengthCommitment<T, H>
+where
+    T: IntoSlice<H::NativeType>,
+    H::NativeType: Copy + From<u8>,
+{
+    /// Commits on `columns`, padded with zeros to the power of two at or above the length of the
+    /// longest one.
+    pub fn commit(columns: &[&[T]]) -> Result<Self, Error> {
+        let column_lengths = columns
+            .iter()
+            .map(|column| column.len())
+            .collect::<Vec<_>>();
+        let n_rows = column_lengths
+            .iter()
+            .max()
+            .copied()
+            .ok_or(Error::NoColumns)?
+            .next_power_of_two();
+        if column_lengths.contains(&0) {
+            return Err(Error::EmptyColumns);
+        }
+        let padded_columns = columns
+            .iter()
+            .map(|column| {
+                let mut padded_column = column.to_vec();
+                padded_column.resize(n_rows, T::zero());
+                padded_column
+            })
+            .collect();
+        Ok(Self {
+            tree: MerkleTree::commit(padded_columns),
+            column_lengths,
+        })
+    }
+
+    pub fn root(&self) -> H::Hash {
+        self.tree.root()
+    }
+
+    /// Generates a decommitment of the padded rows at `queries`, see
+    /// [MerkleTree::generate_decommitment].
+    pub fn generate_decommitment(&self, queries: Vec<usize>) -> MerkleDecommitment<T, H> {
+        self.tree.generate_decommitment(queries)
+    }
+
+    /// Returns whether the value of the column at `row` is padding.
+    pub fn is_padding(column_lengths: &[usize], column: usize, row: usize) -> bool {
+        row >= column_lengths[column]
+    }
+
+    /// Checks that the padding values of an opened row, e.g. one of
+    /// [MerkleDecommitment::values], are zero. The decommitment itself is verified as usual.
+    pub fn check_padding(column_lengths: &[usize], row_index: usize, row: &[T]) -> bool {
+        row.len() == column_lengths.len()
+            && row.iter().enumerate().all(|(column, value)| {
+                !Self::is_padding(column_lengths, column, row_index) || value.is_zero()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+
+    use super::MixedLengthCommitment;
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::error::Error;
+    use crate::commitment_scheme::merkle_tree::MerkleTree;
+    use crate::core::fields::m31::M31;
+    use crate::m31;
+
+    type Commitment = MixedLengthCommitment<M31, Blake2sHasher>;
+
+    #[test]
+    fn open_padding_row_test() {
+        let long_column = [m31!(1), m31!(2), m31!(3), m31!(4)];
+        let short_column = [m31!(5), m31!(6)];
+
+        let commitment = Commitment::commit(&[&long_column, &short_column]).unwrap();
+        let decommitment = commitment.generate_decommitment(vec![3]);
+        let row = decommitment.values().next().unwrap();
+
+        assert_eq!(commitment.column_lengths, vec![4, 2]);
+        assert_eq!(
+            commitment.root(),
+            MerkleTree::<M31, Blake2sHasher>::commit(vec![
+                long_column.to_vec(),
+                vec![m31!(5), m31!(6), M31::zero(), M31::zero()]
+            ])
+            .root()
+        );
+        assert!(decommitment.verify(commitment.root(), &[3]));
+        assert_eq!(row, vec![m31!(4), M31::zero()]);
+        assert!(Commitment::is_padding(&commitment.column_lengths, 1, 3));
+        assert!(Commitment::check_padding(
+            &commitment.column_lengths,
+            3,
+            &row
+        ));
+        assert!(!Commitment::check_padding(
+            &commitment.column_lengths,
+            3,
+            &[m31!(4), m31!(1)]
+        ));
+    }
+
+    #[test]
+    fn open_non_power_of_two_padding_row_test() {
+        let long_column = [m31!(1), m31!(2), m31!(3)];
+        let short_column = [m31!(5), m31!(6)];
+
+        let commitment = Commitment::commit(&[&long_column, &short_column]).unwrap();
+        let decommitment = commitment.generate_decommitment(vec![2, 3]);
+        let rows = decommitment.values().collect::<Vec<_>>();
+
+        assert!(decommitment.verify(commitment.root(), &[2, 3]));
+        assert_eq!(rows, vec![vec![m31!(3), M31::zero()], vec![M31::zero(); 2]]);
+        assert!(Commitment::check_padding(
+            &commitment.column_lengths,
+            2,
+            &rows[0]
+        ));
+        assert!(Commitment::check_padding(
+            &commitment.column_lengths,
+            3,
+            &rows[1]
+        ));
+    }
+
+    #[test]
+    fn invalid_columns_test() {
+        assert!(matches!(Commitment::commit(&[]), Err(Error::NoColumns)));
+        assert!(matches!(
+            Commitment::commit(&[&[m31!(1)], &[]]),
+            Err(Error::EmptyColumns)
+        ));
+    }
+}
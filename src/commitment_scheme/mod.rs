@@ -1,10 +1,30 @@
+#[cfg(feature = "tokio")]
+pub mod async_hasher;
+pub mod batch_verifier;
 pub mod blake2_hash;
+pub mod blake2s_simd_hash;
 pub mod blake3_hash;
+#[cfg(feature = "digest-compat")]
+pub mod digest_adapter;
+pub mod error;
+pub mod field_merkle_tree;
+#[cfg(feature = "gpu")]
+pub mod gpu_blake2s_hash;
 pub mod hasher;
+#[cfg(feature = "std")]
+pub mod hasher_pool;
+pub mod incremental_merkle_tree;
+pub mod keccak_hash;
 pub mod merkle_decommitment;
 pub mod merkle_input;
 pub mod merkle_multilayer;
 pub mod merkle_tree;
 pub mod mixed_degree_decommitment;
 pub mod mixed_degree_merkle_tree;
+pub mod mixed_length_commitment;
+pub mod poseidon_hash;
+#[cfg(feature = "std")]
+pub mod record_merkle_tree;
+pub mod serialize;
+pub mod sha256_hash;
 pub mod utils;
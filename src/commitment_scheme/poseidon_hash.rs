@@ -0,0 +1,516 @@
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt;
+
+use num_traits::{One, Zero};
+
+use super::error::Error;
+use super::hasher::{self, write_output, HashConversionError, Hasher, Name};
+use crate::core::fields::m31::{BaseField, P};
+
+pub const POSEIDON_WIDTH: usize = 24; // in BaseField elements.
+pub const POSEIDON_CAPACITY: usize = 8; // in BaseField elements.
+pub const POSEIDON_RATE: usize = POSEIDON_WIDTH - POSEIDON_CAPACITY; // in BaseField elements.
+
+const fn m31(value: u32) -> BaseField {
+    BaseField::from_u32_unchecked(value)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Eq)]
+pub struct PoseidonHash([BaseField; POSEIDON_CAPACITY]);
+
+impl Name for PoseidonHash {
+    const NAME: alloc::borrow::Cow<'static, str> = alloc::borrow::Cow::Borrowed("Poseidon");
+}
+
+impl IntoIterator for PoseidonHash {
+    type Item = BaseField;
+    type IntoIter = core::array::IntoIter<BaseField, POSEIDON_CAPACITY>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<PoseidonHash> for [BaseField; POSEIDON_WIDTH] {
+    fn from(value: PoseidonHash) -> Self {
+        let mut res = [BaseField::zero(); POSEIDON_WIDTH];
+        res[..POSEIDON_CAPACITY].copy_from_slice(&value.0);
+        res
+    }
+}
+
+impl From<[BaseField; POSEIDON_WIDTH]> for PoseidonHash {
+    fn from(value: [BaseField; POSEIDON_WIDTH]) -> Self {
+        Self(value[..POSEIDON_CAPACITY].try_into().unwrap())
+    }
+}
+
+impl fmt::Display for PoseidonHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for x in self.0 {
+            f.write_str(&format!("{}, ", x))?;
+        }
+        Ok(())
+    }
+}
+
+impl From<PoseidonHash> for Vec<BaseField> {
+    fn from(value: PoseidonHash) -> Self {
+        Self::from(value.0)
+    }
+}
+
+impl TryFrom<Vec<BaseField>> for PoseidonHash {
+    type Error = Error;
+
+    fn try_from(value: Vec<BaseField>) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_slice())
+    }
+}
+
+impl TryFrom<&[BaseField]> for PoseidonHash {
+    type Error = Error;
+
+    fn try_from(value: &[BaseField]) -> Result<Self, Self::Error> {
+        Ok(Self(value.try_into().map_err(|_| HashConversionError {
+            expected: POSEIDON_CAPACITY,
+            actual: value.len(),
+        })?))
+    }
+}
+
+impl AsRef<[BaseField]> for PoseidonHash {
+    fn as_ref(&self) -> &[BaseField] {
+        &self.0
+    }
+}
+
+impl From<PoseidonHash> for [BaseField; POSEIDON_CAPACITY] {
+    fn from(value: PoseidonHash) -> Self {
+        value.0
+    }
+}
+
+impl hasher::Hash<BaseField> for PoseidonHash {}
+
+/// A sponge over the Poseidon2 permutation of width 24 over M31, see
+/// https://eprint.iacr.org/2023/323.pdf. The parameters are the ones of the `Poseidon2Mersenne31`
+/// instance of Plonky3, for 128 bits of security, and exposed as associated constants for
+/// auditing.
+///
+/// Input elements are added into the rate part of the state (the elements following the capacity
+/// prefix), and the state is permuted whenever the rate is full. On finalization, the input is
+/// padded with a one element (followed by implicit zeros), so inputs of different lengths never
+/// collide. The hash is the capacity prefix of the state after a final permutation.
+pub struct PoseidonHasher {
+    state: [BaseField; POSEIDON_WIDTH],
+    // Number of elements absorbed into the rate part of the state since the last permutation.
+    n_absorbed: usize,
+}
+
+impl PoseidonHasher {
+    /// Degree of the S-box `x -> x^5`, the smallest degree coprime with P - 1.
+    pub const SBOX_DEGREE: u32 = 5;
+    /// Number of full rounds, half before and half after the partial rounds.
+    pub const N_FULL_ROUNDS: usize = 8;
+    pub const N_PARTIAL_ROUNDS: usize = 22;
+    /// The 4x4 MDS matrix M4. The linear layer of the full rounds multiplies the state by the
+    /// block circulant matrix `circ(2 * M4, M4, ..., M4)`.
+    pub const EXTERNAL_MDS: [[u32; 4]; 4] =
+        [[2, 3, 1, 1], [1, 2, 3, 1], [1, 1, 2, 3], [3, 1, 1, 2]];
+    /// The linear layer of the partial rounds multiplies the state by `1 + diag(D)`, where 1 is
+    /// the all-ones matrix and D is this diagonal, `[-2, 2^0, 2^1, ..., 2^22]`.
+    pub const INTERNAL_DIAGONAL: [BaseField; POSEIDON_WIDTH] = {
+        let mut diagonal = [m31(0); POSEIDON_WIDTH];
+        diagonal[0] = m31(P - 2);
+        let mut i = 1;
+        while i < POSEIDON_WIDTH {
+            diagonal[i] = m31(1 << (i - 1));
+            i += 1;
+        }
+        diagonal
+    };
+    /// Round constants of the full rounds, in order.
+    pub const EXTERNAL_ROUND_CONSTANTS: [[u32; POSEIDON_WIDTH]; Self::N_FULL_ROUNDS] = [
+        [
+            670752198, 2052960689, 867595173, 1121120522, 1732216065, 1777538858, 974826695,
+            857651441, 1509218160, 933669702, 308743513, 1606546523, 1395707998, 1248974626,
+            733565087, 1614794869, 1457687568, 311580733, 2055660101, 1735187654, 1563765150,
+            358422393, 615368408, 1022914986,
+        ],
+        [
+            1745808542, 1451694789, 1010294888, 478426997, 974777474, 836569592, 553962986,
+            354722588, 1099724285, 957403621, 1171073730, 1314307614, 1575313895, 511348931,
+            1777322674, 743793854, 821769216, 365270850, 2100202195, 1610545562, 1781773041,
+            1642480066, 968153742, 107763776,
+        ],
+        [
+            304102504, 1048805912, 670079580, 1825005418, 699322108, 372969254, 1347088819,
+            1017368981, 695522824, 1491107118, 1656304581, 934311777, 1538050768, 1121275927,
+            1281424936, 1609172128, 302658704, 2055094098, 16103019, 802016690, 359041126,
+            1491417545, 151742200, 122792040,
+        ],
+        [
+            802809388, 2143547951, 2020259742, 437172020, 1610027373, 1217130568, 1833171446,
+            2135403312, 60728125, 173288461, 1580136315, 2058149815, 1766051075, 458819359,
+            1495214374, 696367131, 367271168, 4549961, 718747682, 1943893587, 1536582683,
+            1574838747, 1735444335, 848039704,
+        ],
+        [
+            1689611743, 173154748, 427470023, 1004172913, 2077368442, 782638163, 1744615017,
+            1082619536, 297763826, 1160504957, 618979668, 1687696498, 37211066, 2117379525,
+            1790329919, 1183379851, 545339302, 1229207547, 723170958, 1927785244, 1080767281,
+            1903150401, 1929310598, 95801870,
+        ],
+        [
+            637696247, 1214340530, 1722126248, 1823128363, 926128391, 210718841, 1667233644,
+            688337540, 129024239, 1282387121, 2004475442, 535738304, 1985680653, 895998816,
+            1108547306, 776893336, 1108245527, 574331301, 1825109420, 1194870642, 1497066195,
+            1664793266, 1178412180, 1275811987,
+        ],
+        [
+            764620473, 668520154, 1778964353, 869842094, 2128984066, 747581376, 1519350472,
+            1866059985, 531517395, 561851254, 53392822, 1068331465, 662747074, 823854009,
+            971717700, 320181227, 294934895, 1396650714, 2107450457, 1986427909, 571226895,
+            767463300, 1220071598, 743068663,
+        ],
+        [
+            354088663, 1962447810, 854180576, 179823057, 1437366680, 1198008653, 808278119,
+            467076622, 208939440, 1563930563, 477344603, 1551675578, 2035783893, 947866129,
+            1111734935, 722747324, 1639305445, 807667052, 297389100, 1284478252, 1462279873,
+            1174926203, 447426492, 784678506,
+        ],
+    ];
+    /// Round constants of the partial rounds, added to the first element of the state.
+    pub const INTERNAL_ROUND_CONSTANTS: [u32; Self::N_PARTIAL_ROUNDS] = [
+        1736603273, 747169869, 851352604, 1949058635, 851933251, 8234834, 1526792059, 444086295,
+        1810392070, 590388790, 1311817928, 1791442933, 311399195, 695319905, 459311373, 1367378466,
+        347018669, 1486142932, 1274171468, 1075156138, 400874409, 1085352924,
+    ];
+
+    pub fn from_hash(initial_hash: PoseidonHash) -> Self {
+        // TODO(ShaharS): change to another default state.
+        Self {
+            state: initial_hash.into(),
+            n_absorbed: 0,
+        }
+    }
+
+    fn absorb(&mut self, value: BaseField) {
+        if self.n_absorbed == POSEIDON_RATE {
+            Self::permute(&mut self.state);
+            self.n_absorbed = 0;
+        }
+        self.state[POSEIDON_CAPACITY + self.n_absorbed] += value;
+        self.n_absorbed += 1;
+    }
+
+    /// Pads the absorbed input and applies the final permutation.
+    fn pad_and_permute(&mut self) {
+        self.absorb(BaseField::one());
+        Self::permute(&mut self.state);
+    }
+
+    fn sbox(x: BaseField) -> BaseField {
+        let x2 = x * x;
+        x2 * x2 * x
+    }
+
+    /// Multiplies the state by the linear layer of the full rounds, see [Self::EXTERNAL_MDS].
+    fn external_linear_layer(state: &mut [BaseField; POSEIDON_WIDTH]) {
+        for chunk in state.chunks_exact_mut(4) {
+            let input: [BaseField; 4] = chunk.try_into().unwrap();
+            for (dst, row) in chunk.iter_mut().zip(Self::EXTERNAL_MDS) {
+                *dst = input
+                    .iter()
+                    .zip(row)
+                    .map(|(x, coefficient)| *x * m31(coefficient))
+                    .sum();
+            }
+        }
+        let sums: [BaseField; 4] =
+            core::array::from_fn(|i| state.iter().skip(i).step_by(4).copied().sum());
+        state
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x += sums[i % 4]);
+    }
+
+    /// Multiplies the state by the linear layer of the partial rounds, see
+    /// [Self::INTERNAL_DIAGONAL].
+    fn internal_linear_layer(state: &mut [BaseField; POSEIDON_WIDTH]) {
+        let sum: BaseField = state.iter().copied().sum();
+        state
+            .iter_mut()
+            .zip(Self::INTERNAL_DIAGONAL)
+            .for_each(|(x, diagonal)| *x = sum + *x * diagonal);
+    }
+
+    fn full_round(state: &mut [BaseField; POSEIDON_WIDTH], round_constants: &[u32]) {
+        state
+            .iter_mut()
+            .zip(round_constants)
+            .for_each(|(x, constant)| *x = Self::sbox(*x + m31(*constant)));
+        Self::external_linear_layer(state);
+    }
+
+    /// Applies the Poseidon2 permutation to `state`, in place.
+    pub fn permute(state: &mut [BaseField; POSEIDON_WIDTH]) {
+        let (first_full_rounds, last_full_rounds) =
+            Self::EXTERNAL_ROUND_CONSTANTS.split_at(Self::N_FULL_ROUNDS / 2);
+        Self::external_linear_layer(state);
+        for round_constants in first_full_rounds {
+            Self::full_round(state, round_constants);
+        }
+        for round_constant in Self::INTERNAL_ROUND_CONSTANTS {
+            state[0] = Self::sbox(state[0] + m31(round_constant));
+            Self::internal_linear_layer(state);
+        }
+        for round_constants in last_full_rounds {
+            Self::full_round(state, round_constants);
+        }
+    }
+}
+
+impl Hasher for PoseidonHasher {
+    type Hash = PoseidonHash;
+    const BLOCK_SIZE: usize = POSEIDON_RATE;
+    const OUTPUT_SIZE: usize = POSEIDON_CAPACITY;
+    type NativeType = BaseField;
+    type Digest = [BaseField; POSEIDON_CAPACITY];
+
+    fn new() -> Self {
+        Self::from_hash(PoseidonHash::default())
+    }
+
+    fn reset(&mut self) {
+        self.state = PoseidonHash::default().into();
+        self.n_absorbed = 0;
+    }
+
+    fn update(&mut self, data: &[BaseField]) {
+        data.iter().for_each(|x| self.absorb(*x));
+    }
+
+    fn finalize(mut self) -> PoseidonHash {
+        self.pad_and_permute();
+        self.state.into()
+    }
+
+    fn finalize_reset(&mut self) -> PoseidonHash {
+        self.pad_and_permute();
+        let res = self.state.into();
+        self.reset();
+        res
+    }
+
+    /// Hash many inputs of the same length, reusing a single hasher for all of them, without
+    /// allocating.
+    /// Note: `single_input_length_bytes` is measured in [BaseField] elements.
+    unsafe fn hash_many_in_place(
+        data: &[*const BaseField],
+        single_input_length_bytes: usize,
+        dst: &[*mut BaseField],
+    ) {
+        let mut hasher = Self::new();
+        data.iter()
+            .map(|p| core::slice::from_raw_parts(*p, single_input_length_bytes))
+            .zip(dst)
+            .for_each(|(input, out)| {
+                hasher.update(input);
+                write_output(hasher.finalize_reset().as_ref(), *out);
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+
+    use super::{PoseidonHash, PoseidonHasher, POSEIDON_CAPACITY, POSEIDON_RATE, POSEIDON_WIDTH};
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
+    use crate::commitment_scheme::hasher::Hasher;
+    use crate::core::fields::m31::BaseField;
+    use crate::m31;
+
+    // Permutation test vector of `Poseidon2Mersenne31::<24>` in Plonky3, generated by the
+    // reference Sage implementation, see https://github.com/0xPolygonZero/hash-constants.
+    const PERMUTATION_INPUT: [u32; POSEIDON_WIDTH] = [
+        886409618, 1327899896, 1902407911, 591953491, 648428576, 1844789031, 1198336108, 355597330,
+        1799586834, 59617783, 790334801, 1968791836, 559272107, 31054313, 1042221543, 474748436,
+        135686258, 263665994, 1962340735, 1741539604, 2026927696, 449439011, 1131357108, 50869465,
+    ];
+    const PERMUTATION_OUTPUT: [u32; POSEIDON_WIDTH] = [
+        87189408, 212775836, 954807335, 1424761838, 1222521810, 1264950009, 1891204592, 710452896,
+        957091834, 1776630156, 1091081383, 786687731, 1101902149, 1281649821, 436070674, 313565599,
+        1961711763, 2002894460, 2040173120, 854107426, 25198245, 1967213543, 604802266, 2086190331,
+    ];
+
+    // Hashes of the sponge over the Plonky3 permutation.
+    const ZERO_HASH_RESULT: [u32; POSEIDON_CAPACITY] = [
+        1014072352, 1076695317, 1861991640, 1415097339, 2117930867, 1458030132, 1848581682,
+        2090896572,
+    ];
+    const CONCAT_HASH_RESULT: [u32; POSEIDON_CAPACITY] = [
+        920876223, 616556969, 739108399, 2132631479, 795051445, 267369983, 1056981048, 639013288,
+    ];
+
+    fn to_m31_array<const N: usize>(values: [u32; N]) -> [BaseField; N] {
+        values.map(|x| m31!(x))
+    }
+
+    #[test]
+    fn hash_debug_test() {
+        let values = (0..POSEIDON_CAPACITY as u32)
+            .map(|x| m31!(x))
+            .collect::<Vec<BaseField>>();
+        let poseidon_state = PoseidonHash::try_from(values).unwrap();
+
+        println!("Poseidon State: {:?}", poseidon_state);
+    }
+
+    #[test]
+    fn hash_iter_test() {
+        let values = (0..POSEIDON_CAPACITY as u32)
+            .map(|x| m31!(x))
+            .collect::<Vec<BaseField>>();
+        let poseidon_state = PoseidonHash::try_from(values).unwrap();
+
+        for (i, x) in poseidon_state.into_iter().enumerate() {
+            assert_eq!(x, m31!(i as u32));
+        }
+    }
+
+    #[test]
+    fn hasher_set_state_test() {
+        let values = (0..POSEIDON_CAPACITY as u32)
+            .map(|x| m31!(x))
+            .collect::<Vec<BaseField>>();
+        let mut hasher = PoseidonHasher::from_hash(PoseidonHash::try_from(values).unwrap());
+
+        hasher.state[0] = m31!(100);
+        let poseidon_hash: PoseidonHash = hasher.state.into();
+
+        for (i, x) in poseidon_hash.into_iter().enumerate() {
+            if i == 0 {
+                assert_eq!(x, m31!(100));
+            } else {
+                assert_eq!(x, m31!(i as u32));
+            }
+        }
+    }
+
+    #[test]
+    fn hash_update_test() {
+        let values = (0..POSEIDON_RATE as u32 + 3)
+            .map(|x| m31!(x))
+            .collect::<Vec<BaseField>>();
+        let mut hasher = PoseidonHasher::new();
+
+        hasher.update(&values[..5]);
+        hasher.update(&values[5..]);
+
+        assert_eq!(hasher.finalize(), PoseidonHasher::hash(&values));
+        assert_ne!(
+            PoseidonHasher::hash(&values),
+            PoseidonHasher::hash(&values[..POSEIDON_RATE])
+        );
+    }
+
+    #[test]
+    fn finalize_into_digest_test() {
+        let values = [m31!(1), m31!(2), m31!(3)];
+        let mut hasher = PoseidonHasher::new();
+        hasher.update(&values);
+
+        let digest = hasher.finalize_into_digest();
+
+        assert_eq!(digest.len(), PoseidonHasher::OUTPUT_SIZE);
+        assert_eq!(&digest[..], PoseidonHasher::hash(&values).as_ref());
+    }
+
+    #[test]
+    fn hash_felts_test() {
+        let values = [m31!(1), m31!(2), m31!(3)];
+
+        assert_eq!(
+            PoseidonHasher::hash_felts(&values),
+            PoseidonHasher::hash(&values)
+        );
+    }
+
+    #[test]
+    fn hash_many_test() {
+        let inputs = (0..3)
+            .map(|i| {
+                (0..POSEIDON_CAPACITY as u32 * 2)
+                    .map(|x| m31!(x + i))
+                    .collect::<Vec<BaseField>>()
+            })
+            .collect::<Vec<_>>();
+        let input_ptrs = inputs.iter().map(|v| v.as_ptr()).collect::<Vec<_>>();
+        let mut out = [BaseField::zero(); POSEIDON_CAPACITY * 3];
+        let out_ptrs = (0..3)
+            .map(|i| unsafe { out.as_mut_ptr().add(i * POSEIDON_CAPACITY) })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            PoseidonHasher::hash_many_in_place(&input_ptrs, POSEIDON_CAPACITY * 2, &out_ptrs)
+        };
+
+        for (input, out) in inputs.iter().zip(out.chunks(POSEIDON_CAPACITY)) {
+            assert_eq!(
+                PoseidonHash::try_from(out).unwrap(),
+                PoseidonHasher::hash(input)
+            );
+        }
+    }
+
+    #[test]
+    fn padding_test() {
+        assert_ne!(
+            PoseidonHasher::hash(&[m31!(7)]),
+            PoseidonHasher::hash(&[m31!(7), m31!(0)])
+        );
+        assert_ne!(PoseidonHasher::hash(&[]), PoseidonHasher::hash(&[m31!(0)]));
+        assert_ne!(
+            PoseidonHasher::hash(&[m31!(0); POSEIDON_RATE]),
+            PoseidonHasher::hash(&[m31!(0); POSEIDON_RATE - 1])
+        );
+    }
+
+    #[test]
+    fn hasher_regression_test() {
+        let mut hasher = PoseidonHasher::new();
+
+        let res = hasher.finalize_reset();
+
+        assert_eq!(res, PoseidonHash(to_m31_array(ZERO_HASH_RESULT)));
+        assert_eq!(hasher.state, PoseidonHasher::new().state);
+    }
+
+    #[test]
+    fn concat_and_hash_regression_test() {
+        let a = PoseidonHash::try_from((0..8).map(|x| m31!(x)).collect::<Vec<_>>()).unwrap();
+        let b = PoseidonHash::try_from((8..16).map(|x| m31!(x)).collect::<Vec<_>>()).unwrap();
+
+        let res = PoseidonHasher::concat_and_hash(&a, &b);
+
+        assert_eq!(res, PoseidonHash(to_m31_array(CONCAT_HASH_RESULT)));
+    }
+
+    #[test]
+    fn permutation_test_vector() {
+        let mut state = to_m31_array(PERMUTATION_INPUT);
+
+        PoseidonHasher::permute(&mut state);
+
+        assert_eq!(state, to_m31_array(PERMUTATION_OUTPUT));
+    }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<PoseidonHasher>(&(0..64).map(BaseField::from).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::hasher::Hasher;
+use super::utils::{hash_node_layer, hash_root, layer_hashes};
+use crate::math::utils::usize_div_ceil;
+
+/// Number of records read from the reader at once.
+const RECORDS_PER_READ: usize = 1 << 10;
+
+/// A merkle tree over fixed-size records streamed from a reader, e.g. a file of trace rows that
+/// doesn't fit in memory. Only the layers of hashes are kept in memory.
+///
+/// Records are hashed with [Hasher::hash_leaf] and the root binds the number of records, so the
+/// root equals the root of a [super::merkle_tree::MerkleTree] whose leaf blocks are the records,
+/// one row each. If the number of records is
+/// not a power of two, the leaf layer is padded by duplicating the hash of the last record, as in
+/// [super::incremental_merkle_tree::IncrementalMerkleTree].
+pub struct RecordMerkleTree<H: Hasher<NativeType = u8>, const ARITY: usize = 2> {
+    /// Layers of hashes, from the leaf hashes up to the top node.
+    layers: Vec<Vec<u8>>,
+    n_records: usize,
+    phantom: std::marker::PhantomData<H>,
+}
+
+impl<H: Hasher<NativeType = u8>, const ARITY: usize> RecordMerkleTree<H, ARITY> {
+    /// Builds the tree from the records between the current position of `reader` and its end,
+    /// `record_size` bytes each.
+    ///
+    /// Fails if reading fails, or if the data is empty or not a whole number of records.
+    pub fn build_from_reader<R: Read + Seek>(
+        reader: &mut R,
+        record_size: usize,
+    ) -> io::Result<Self> {
+        assert!(
+            ARITY >= 2 && ARITY.is_power_of_two(),
+            "Invalid arity {ARITY}."
+        );
+        assert_ne!(record_size, 0, "Records must not be empty!");
+        let start = reader.stream_position()?;
+        let n_bytes = reader.seek(SeekFrom::End(0))? - start;
+        reader.seek(SeekFrom::Start(start))?;
+        if n_bytes == 0 || n_bytes % record_size as u64 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{n_bytes} bytes are not a whole, non-zero, number of records."),
+            ));
+        }
+        let n_records = usize::try_from(n_bytes / record_size as u64)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Too many records."))?;
+
+        let mut leaf_layer = vec![0; n_records.next_power_of_two() * H::OUTPUT_SIZE];
+        let (record_hashes, padding) = leaf_layer.split_at_mut(n_records * H::OUTPUT_SIZE);
+        let mut buffer = vec![0; RECORDS_PER_READ * record_size];
+        for dst in record_hashes.chunks_mut(RECORDS_PER_READ * H::OUTPUT_SIZE) {
+            let records = &mut buffer[..dst.len() / H::OUTPUT_SIZE * record_size];
+            reader.read_exact(records)?;
+            records
+                .chunks_exact(record_size)
+                .zip(dst.chunks_exact_mut(H::OUTPUT_SIZE))
+                .for_each(|(record, dst)| dst.copy_from_slice(H::hash_leaf(record).as_ref()));
+        }
+        let last_hash = record_hashes[record_hashes.len() - H::OUTPUT_SIZE..].to_vec();
+        padding
+            .chunks_exact_mut(H::OUTPUT_SIZE)
+            .for_each(|dst| dst.copy_from_slice(&last_hash));
+
+        let mut layers = vec![leaf_layer];
+        while layers.last().unwrap().len() > H::OUTPUT_SIZE {
+            let layer = layers.last().unwrap();
+            let mut parent_layer =
+                vec![0; usize_div_ceil(layer.len() / H::OUTPUT_SIZE, ARITY) * H::OUTPUT_SIZE];
+            hash_node_layer::<H>(layer, ARITY, &mut parent_layer);
+            layers.push(parent_layer);
+        }
+
+        Ok(Self {
+            layers,
+            n_records,
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// The root of the tree, its top node hashed with the number of records, see [hash_root].
+    pub fn root(&self) -> H::Hash {
+        hash_root::<H>(
+            &layer_hashes::<H>(self.layers.last().unwrap())[0],
+            self.n_records,
+        )
+    }
+
+    /// Returns the hashes of the `i`-th layer of the tree, where layer 0 holds the leaf hashes,
+    /// including padding, and the last layer holds the top node.
+    pub fn layer(&self, i: usize) -> Vec<H::Hash> {
+        layer_hashes::<H>(&self.layers[i])
+    }
+
+    /// Returns the number of records, excluding padding.
+    pub fn n_records(&self) -> usize {
+        self.n_records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Seek, SeekFrom, Write};
+
+    use super::RecordMerkleTree;
+    use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+    use crate::commitment_scheme::blake3_hash::Blake3Hasher;
+    use crate::commitment_scheme::incremental_merkle_tree::IncrementalMerkleTree;
+    use crate::commitment_scheme::merkle_tree::MerkleTree;
+    use crate::core::fields::m31::M31;
+    use crate::core::fields::IntoSlice;
+
+    #[test]
+    fn build_from_file_test() {
+        // 16 columns of M31 fill 64 byte leaf blocks of a single row, i.e. 2^11 records.
+        let trace = vec![(0..1 << 11).map(M31::from_u32_unchecked).collect(); 16];
+        let tree = MerkleTree::<M31, Blake2sHasher>::commit(trace);
+        let record_size = tree.bottom_layer_block_size * std::mem::size_of::<M31>();
+        let path = std::env::temp_dir().join(format!("stwo_records_{}", std::process::id()));
+        let mut file = std::fs::File::options()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(IntoSlice::<u8>::into_slice(&tree.bottom_layer))
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let record_tree =
+            RecordMerkleTree::<Blake2sHasher>::build_from_reader(&mut file, record_size);
+        std::fs::remove_file(&path).unwrap();
+        let record_tree = record_tree.unwrap();
+
+        assert_eq!(record_tree.n_records(), 1 << 11);
+        assert_eq!(record_tree.root(), tree.root());
+        assert_eq!(record_tree.layer(0), tree.layer(0));
+    }
+
+    #[test]
+    fn non_power_of_two_records_test() {
+        let records = (0..5_u8).map(|i| [i; 24]).collect::<Vec<_>>();
+        let mut incremental_tree = IncrementalMerkleTree::<Blake3Hasher>::new();
+        records
+            .iter()
+            .for_each(|record| incremental_tree.push_leaf(record));
+
+        let record_tree = RecordMerkleTree::<Blake3Hasher>::build_from_reader(
+            &mut Cursor::new(records.concat()),
+            24,
+        )
+        .unwrap();
+
+        assert_eq!(record_tree.n_records(), 5);
+        assert_eq!(Some(record_tree.root()), incremental_tree.root());
+    }
+
+    #[test]
+    fn partial_record_test() {
+        let mut reader = Cursor::new(vec![0; 100]);
+
+        let result = RecordMerkleTree::<Blake2sHasher>::build_from_reader(&mut reader, 64);
+
+        assert_eq!(
+            result.err().map(|err| err.kind()),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+    }
+}
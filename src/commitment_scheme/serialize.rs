@@ -0,0 +1,306 @@
+//! The canonical byte encoding of field elements, shared by hashing and channel mixing.
+//!
+//! Every M31 element is encoded as its canonical value, in [N_BYTES_FELT] little-endian bytes. A
+//! QM31 element is encoded as its 4 M31 coordinates, in the order of
+//! [QM31::to_m31_array](crate::core::fields::qm31::QM31::to_m31_array). On little-endian targets,
+//! this is the memory layout [IntoSlice] exposes, which [Endianness::encode_felts] borrows instead
+//! of copying it. Hashers and the channel encode field elements through this module.
+//!
+//! Big-endian words can be chosen with [Endianness::Big], for interoperability with verifiers that
+//! expect them, e.g. on-chain.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::error;
+use crate::core::fields::m31::{BaseField, N_BYTES_FELT, P};
+use crate::core::fields::qm31::{SecureField, SECURE_FIELD_EXTENSION_DEGREE};
+use crate::core::fields::IntoSlice;
+
+/// Number of bytes of an encoded QM31 element.
+pub const N_BYTES_SECURE_FELT: usize = N_BYTES_FELT * SECURE_FIELD_EXTENSION_DEGREE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeltDecodingError {
+    InvalidLength { len: usize, width: usize },
+    NonCanonical { index: usize },
+}
+
+impl fmt::Display for FeltDecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength { len, width } => write!(
+                f,
+                "{len} bytes are not a whole number of {width} byte field elements."
+            ),
+            Self::NonCanonical { index } => {
+                write!(f, "The M31 word at index {index} is not canonical.")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FeltDecodingError {}
+
+/// The byte order of the encoded M31 words. The prover and the verifier must agree on it, as it
+/// changes every commitment and every mixed transcript, see
+/// [MerkleTreeConfig](super::merkle_tree::MerkleTreeConfig) and
+/// [ChannelConfig](crate::core::channel::ChannelConfig).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// The byte order of M31 words in memory on the target.
+    const TARGET: Self = match cfg!(target_endian = "little") {
+        true => Self::Little,
+        false => Self::Big,
+    };
+
+    /// Encodes field elements, e.g. M31 or QM31, as the native elements of a hasher in this
+    /// endianness: the encoding of this module for byte natives, and the elements themselves for
+    /// natives holding whole words, e.g. the [BaseField] input of an algebraic hash. The memory
+    /// layout of the elements, see [IntoSlice], is borrowed when it is the encoding.
+    pub fn encode_felts<T: IntoSlice<N>, N: Copy>(self, felts: &[T]) -> Cow<'_, [N]> {
+        self.reorder_words(T::into_slice(felts))
+    }
+
+    pub fn felts_to_bytes(self, felts: &[BaseField]) -> Vec<u8> {
+        felts
+            .iter()
+            .flat_map(|felt| self.encode_word(felt.0))
+            .collect()
+    }
+
+    /// Inverse of [Self::felts_to_bytes]. Fails if `bytes` is not a whole number of elements, or
+    /// holds a word that is not reduced modulo P.
+    pub fn bytes_to_felts(self, bytes: &[u8]) -> Result<Vec<BaseField>, error::Error> {
+        if bytes.len() % N_BYTES_FELT != 0 {
+            return Err(FeltDecodingError::InvalidLength {
+                len: bytes.len(),
+                width: N_BYTES_FELT,
+            }
+            .into());
+        }
+        Ok(bytes
+            .chunks_exact(N_BYTES_FELT)
+            .enumerate()
+            .map(|(index, word)| {
+                let value = self.decode_word(word.try_into().unwrap());
+                if value >= P {
+                    return Err(FeltDecodingError::NonCanonical { index });
+                }
+                Ok(BaseField::from_u32_unchecked(value))
+            })
+            .collect::<Result<_, FeltDecodingError>>()?)
+    }
+
+    pub fn secure_felts_to_bytes(self, felts: &[SecureField]) -> Vec<u8> {
+        felts
+            .iter()
+            .flat_map(|felt| felt.to_m31_array())
+            .flat_map(|felt| self.encode_word(felt.0))
+            .collect()
+    }
+
+    /// Inverse of [Self::secure_felts_to_bytes]. Fails if `bytes` is not a whole number of
+    /// elements, or holds a word that is not reduced modulo P.
+    pub fn bytes_to_secure_felts(self, bytes: &[u8]) -> Result<Vec<SecureField>, error::Error> {
+        if bytes.len() % N_BYTES_SECURE_FELT != 0 {
+            return Err(FeltDecodingError::InvalidLength {
+                len: bytes.len(),
+                width: N_BYTES_SECURE_FELT,
+            }
+            .into());
+        }
+        Ok(self
+            .bytes_to_felts(bytes)?
+            .chunks_exact(SECURE_FIELD_EXTENSION_DEGREE)
+            .map(|coordinates| SecureField::from_m31_array(coordinates.try_into().unwrap()))
+            .collect())
+    }
+
+    /// Reorders the memory layout of field elements, as exposed by [IntoSlice], into this
+    /// endianness, e.g. before hashing it, see [Self::encode_felts]. Only byte-sized native
+    /// elements are reordered: natives holding whole words, e.g. the [BaseField] input of an
+    /// algebraic hash, are left as is.
+    pub fn reorder_words<N: Copy>(self, native: &[N]) -> Cow<'_, [N]> {
+        let natives_per_word = N_BYTES_FELT / core::mem::size_of::<N>();
+        if self == Self::TARGET || natives_per_word <= 1 {
+            return Cow::Borrowed(native);
+        }
+        assert_eq!(native.len() % natives_per_word, 0, "Partial M31 word!");
+        Cow::Owned(
+            native
+                .chunks_exact(natives_per_word)
+                .flat_map(|word| word.iter().rev().copied())
+                .collect(),
+        )
+    }
+
+    fn encode_word(self, word: u32) -> [u8; N_BYTES_FELT] {
+        match self {
+            Self::Little => word.to_le_bytes(),
+            Self::Big => word.to_be_bytes(),
+        }
+    }
+
+    fn decode_word(self, bytes: [u8; N_BYTES_FELT]) -> u32 {
+        match self {
+            Self::Little => u32::from_le_bytes(bytes),
+            Self::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+pub fn felts_to_bytes(felts: &[BaseField]) -> Vec<u8> {
+    Endianness::Little.felts_to_bytes(felts)
+}
+
+/// Inverse of [felts_to_bytes]. Fails if `bytes` is not a whole number of elements, or holds a
+/// word that is not reduced modulo P.
+pub fn bytes_to_felts(bytes: &[u8]) -> Result<Vec<BaseField>, error::Error> {
+    Endianness::Little.bytes_to_felts(bytes)
+}
+
+pub fn secure_felts_to_bytes(felts: &[SecureField]) -> Vec<u8> {
+    Endianness::Little.secure_felts_to_bytes(felts)
+}
+
+/// Inverse of [secure_felts_to_bytes]. Fails if `bytes` is not a whole number of elements, or
+/// holds a word that is not reduced modulo P.
+pub fn bytes_to_secure_felts(bytes: &[u8]) -> Result<Vec<SecureField>, error::Error> {
+    Endianness::Little.bytes_to_secure_felts(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bytes_to_felts, bytes_to_secure_felts, felts_to_bytes, secure_felts_to_bytes, Endianness,
+        FeltDecodingError,
+    };
+    use crate::commitment_scheme::error::Error;
+    use crate::core::fields::m31::{BaseField, P};
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::fields::IntoSlice;
+    use crate::qm31;
+
+    #[test]
+    fn encode_felts_test() {
+        let felts = [0, 1, 1 << 20, P - 1].map(BaseField::from_u32_unchecked);
+        let secure_felts = [qm31!(1, 2, 3, 4), qm31!(P - 1, 0, 5, 1 << 30)];
+
+        assert_eq!(
+            Endianness::Little.encode_felts::<_, u8>(&felts),
+            felts_to_bytes(&felts)
+        );
+        assert_eq!(
+            Endianness::Big.encode_felts::<_, u8>(&secure_felts),
+            Endianness::Big.secure_felts_to_bytes(&secure_felts)
+        );
+        assert_eq!(
+            Endianness::Big.encode_felts::<_, u8>(&felts)[4..8],
+            [0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn felts_round_trip_test() {
+        let felts = [0, 1, 1 << 20, P - 1].map(BaseField::from_u32_unchecked);
+
+        let bytes = felts_to_bytes(&felts);
+
+        assert_eq!(bytes[4..8], [1, 0, 0, 0]);
+        assert_eq!(bytes, IntoSlice::<u8>::into_slice(&felts));
+        assert_eq!(bytes_to_felts(&bytes), Ok(felts.to_vec()));
+    }
+
+    #[test]
+    fn secure_felts_round_trip_test() {
+        let felts = [qm31!(1, 2, 3, 4), qm31!(P - 1, 0, 5, 1 << 30)];
+
+        let bytes = secure_felts_to_bytes(&felts);
+
+        assert_eq!(bytes[4..8], [2, 0, 0, 0]);
+        assert_eq!(bytes, IntoSlice::<u8>::into_slice(&felts));
+        assert_eq!(bytes_to_secure_felts(&bytes), Ok(felts.to_vec()));
+    }
+
+    #[test]
+    fn big_endian_round_trip_test() {
+        let felts = [0, 1, 1 << 20, P - 1].map(BaseField::from_u32_unchecked);
+        let secure_felts = [qm31!(1, 2, 3, 4), qm31!(P - 1, 0, 5, 1 << 30)];
+
+        let bytes = Endianness::Big.felts_to_bytes(&felts);
+        let secure_bytes = Endianness::Big.secure_felts_to_bytes(&secure_felts);
+
+        assert_eq!(bytes[4..8], [0, 0, 0, 1]);
+        assert_ne!(bytes, felts_to_bytes(&felts));
+        assert_eq!(Endianness::Big.bytes_to_felts(&bytes), Ok(felts.to_vec()));
+        assert_eq!(secure_bytes[4..8], [0, 0, 0, 2]);
+        assert_eq!(
+            Endianness::Big.bytes_to_secure_felts(&secure_bytes),
+            Ok(secure_felts.to_vec())
+        );
+        assert_eq!(
+            Endianness::Big.reorder_words(IntoSlice::<u8>::into_slice(&secure_felts)),
+            secure_bytes
+        );
+        assert_eq!(
+            Endianness::Big.reorder_words(&felts),
+            &felts[..],
+            "Word natives are not reordered."
+        );
+        assert_eq!(
+            Endianness::Big.bytes_to_felts(&[0x7f, 0xff, 0xff, 0xff]),
+            Err(Error::InvalidFelts(FeltDecodingError::NonCanonical {
+                index: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn wrong_length_test() {
+        assert_eq!(
+            bytes_to_felts(&[0; 7]),
+            Err(Error::InvalidFelts(FeltDecodingError::InvalidLength {
+                len: 7,
+                width: 4
+            }))
+        );
+        assert_eq!(
+            bytes_to_secure_felts(&[0; 20]),
+            Err(Error::InvalidFelts(FeltDecodingError::InvalidLength {
+                len: 20,
+                width: 16
+            }))
+        );
+    }
+
+    #[test]
+    fn non_canonical_test() {
+        let bytes = [0, 0, 0, 0, 0xff, 0xff, 0xff, 0x7f];
+
+        assert_eq!(
+            bytes_to_felts(&bytes),
+            Err(Error::InvalidFelts(FeltDecodingError::NonCanonical {
+                index: 1
+            }))
+        );
+        assert_eq!(
+            bytes_to_secure_felts(&[bytes, bytes].concat()),
+            Err(Error::InvalidFelts(FeltDecodingError::NonCanonical {
+                index: 1
+            }))
+        );
+        assert_eq!(
+            bytes_to_secure_felts(&[0; 16]),
+            Ok(vec![SecureField::default()])
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use sha2::{Digest, Sha256};
+
+use super::error::Error;
+use super::hasher::{write_output, HashConversionError};
+
+// Wrapper for the sha256 hash type.
+#[derive(Clone, Copy, PartialEq, Default, Eq)]
+pub struct Sha256Hash([u8; 32]);
+
+impl From<Sha256Hash> for Vec<u8> {
+    fn from(value: Sha256Hash) -> Self {
+        Vec::from(value.0)
+    }
+}
+
+impl TryFrom<&[u8]> for Sha256Hash {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(value.try_into().map_err(|_| HashConversionError {
+            expected: 32,
+            actual: value.len(),
+        })?))
+    }
+}
+
+impl TryFrom<Vec<u8>> for Sha256Hash {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
+    }
+}
+
+impl AsRef<[u8]> for Sha256Hash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Sha256Hash> for [u8; 32] {
+    fn from(val: Sha256Hash) -> Self {
+        val.0
+    }
+}
+
+impl fmt::Display for Sha256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for Sha256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Sha256Hash as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl super::hasher::Name for Sha256Hash {
+    const NAME: alloc::borrow::Cow<'static, str> = alloc::borrow::Cow::Borrowed("SHA256");
+}
+
+impl super::hasher::Hash<u8> for Sha256Hash {}
+
+// Wrapper for the sha256 Hashing functionalities.
+#[derive(Clone, Debug)]
+pub struct Sha256Hasher {
+    state: Sha256,
+}
+
+impl super::hasher::Hasher for Sha256Hasher {
+    type Hash = Sha256Hash;
+    const BLOCK_SIZE: usize = 64;
+    const OUTPUT_SIZE: usize = 32;
+    type NativeType = u8;
+    type Digest = [u8; 32];
+
+    fn new() -> Self {
+        Self {
+            state: Sha256::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        Digest::reset(&mut self.state);
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.state, data);
+    }
+
+    fn finalize(self) -> Sha256Hash {
+        Sha256Hash(self.state.finalize().into())
+    }
+
+    fn finalize_reset(&mut self) -> Sha256Hash {
+        Sha256Hash(self.state.finalize_reset().into())
+    }
+
+    unsafe fn hash_many_in_place(
+        data: &[*const u8],
+        single_input_length_bytes: usize,
+        dst: &[*mut u8],
+    ) {
+        data.iter()
+            .map(|p| core::slice::from_raw_parts(*p, single_input_length_bytes))
+            .zip(dst)
+            .for_each(|(input, out)| {
+                let mut hasher = Sha256::new();
+                Digest::update(&mut hasher, input);
+                write_output(&hasher.finalize(), *out);
+            })
+    }
+}
+
+/// Allows using the hasher as an [io::Write] sink, equivalent to calling `update` on the written
+/// data.
+#[cfg(feature = "std")]
+impl io::Write for Sha256Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        super::hasher::Hasher::update(self, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sha256Hasher;
+    use crate::commitment_scheme::hasher::tests::hasher_conformance;
+    use crate::commitment_scheme::hasher::Hasher;
+
+    #[test]
+    fn single_hash_test() {
+        let hash_abc = Sha256Hasher::hash(b"abc");
+        assert_eq!(
+            hash_abc.to_string(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn hash_many_xof_test() {
+        let input1 = "a";
+        let input2 = "b";
+        let input_arr = [input1.as_ptr(), input2.as_ptr()];
+
+        let mut out = [0_u8; 96];
+        let out_ptrs = [out.as_mut_ptr(), unsafe { out.as_mut_ptr().add(42) }];
+        unsafe { Sha256Hasher::hash_many_in_place(&input_arr, 1, &out_ptrs) };
+
+        assert_eq!(&out[..32], Sha256Hasher::hash(b"a").as_ref());
+        assert_eq!(&out[32..42], &[0; 10]);
+        assert_eq!(&out[42..74], Sha256Hasher::hash(b"b").as_ref());
+        assert_eq!(&out[74..], &[0; 22]);
+    }
+
+    #[test]
+    fn hash_state_test() {
+        let mut state = Sha256Hasher::new();
+        state.update(b"a");
+        state.update(b"b");
+        let hash = state.finalize_reset();
+        let hash_empty = state.finalize();
+
+        assert_eq!(hash.to_string(), Sha256Hasher::hash(b"ab").to_string());
+        assert_eq!(
+            hash_empty.to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn conformance_test() {
+        hasher_conformance::<Sha256Hasher>(&(0..64).collect::<Vec<u8>>());
+    }
+}
@@ -1,7 +1,13 @@
-use std::collections::BTreeMap;
-use std::slice::Iter;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::slice::Iter;
 
-use super::hasher::Hasher;
+use subtle::{Choice, ConditionallySelectable};
+
+use super::hasher::{
+    Hash, Hasher, TryFromNatives, LEAF_DOMAIN_TAG, NODE_DOMAIN_TAG, ROOT_DOMAIN_TAG,
+};
 use crate::core::fields::{Field, IntoSlice};
 use crate::math::utils::{log2_ceil, usize_safe_div};
 
@@ -10,11 +16,42 @@ pub type ColumnLengthMap<T> = BTreeMap<usize, ColumnArray<T>>;
 pub type TreeLayer<T> = Box<[T]>;
 pub type TreeData<T> = Box<[TreeLayer<T>]>;
 
+/// Wraps a slice of raw pointers so it can be shared between threads.
+///
+/// # Safety
+///
+/// Sound as long as every thread accesses memory that is disjoint from the memory accessed by the
+/// other threads, e.g. the distinct destinations of [Hasher::hash_many_in_place].
+#[cfg(feature = "parallel")]
+pub(crate) struct SyncPointers<'a, P: Copy>(pub &'a [P]);
+
+#[cfg(feature = "parallel")]
+unsafe impl<P: Copy> Sync for SyncPointers<'_, P> {}
+
+#[cfg(feature = "parallel")]
+impl<P: Copy> SyncPointers<'_, P> {
+    pub fn get(&self, i: usize) -> P {
+        self.0[i]
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
 pub fn allocate_layer<T: Sized>(n_bytes: usize) -> TreeLayer<T> {
     // Safe bacuase 0 is a valid u8 value.
     unsafe { Box::<[T]>::new_zeroed_slice(n_bytes).assume_init() }
 }
 
+/// Splits a layer of native elements, as written by the hashing functions below, into its hashes.
+pub fn layer_hashes<H: Hasher>(layer: &[H::NativeType]) -> Vec<H::Hash> {
+    layer
+        .chunks_exact(H::OUTPUT_SIZE)
+        .map(|hash| H::Hash::try_from_natives(hash).expect("Hashes are of OUTPUT_SIZE elements."))
+        .collect()
+}
+
 pub fn allocate_balanced_tree<T: Sized>(
     bottom_layer_length: usize,
     size_of_node_bytes: usize,
@@ -37,14 +74,55 @@ pub fn allocate_balanced_tree<T: Sized>(
 
 /// Performes a 2-to-1 hash on a layer of a merkle tree.
 pub fn hash_layer<H: Hasher>(layer: &[H::NativeType], node_size: usize, dst: &mut [H::NativeType]) {
+    assert!(usize_safe_div(layer.len(), node_size).is_power_of_two());
+    hash_every_node::<H>(layer, node_size, dst);
+}
+
+/// Number of nodes of a layer hashed by a single task with the `parallel` feature.
+#[cfg(feature = "parallel")]
+const N_NODES_PER_TASK: usize = 1 << 8;
+
+/// Hashes every `node_size` chunk of `layer` into `dst`, for layers of any number of nodes.
+fn hash_every_node<H: Hasher>(
+    layer: &[H::NativeType],
+    node_size: usize,
+    dst: &mut [H::NativeType],
+) {
     let n_nodes_in_layer = usize_safe_div(layer.len(), node_size);
-    assert!(n_nodes_in_layer.is_power_of_two());
     assert!(n_nodes_in_layer <= dst.len() / H::OUTPUT_SIZE);
+    hash_node_runs::<H>(layer, node_size, dst, hash_node_run::<H>);
+}
+
+/// Splits `layer` into runs of consecutive `node_size` nodes and calls `hash_run` with every run
+/// and the destination of its hashes in `dst`. With the `parallel` feature, the runs of a layer
+/// are hashed in parallel.
+fn hash_node_runs<H: Hasher>(
+    layer: &[H::NativeType],
+    node_size: usize,
+    dst: &mut [H::NativeType],
+    hash_run: impl Fn(&[H::NativeType], usize, &mut [H::NativeType]) + Sync,
+) {
+    #[cfg(not(feature = "parallel"))]
+    hash_run(layer, node_size, dst);
 
-    let src_ptrs: Vec<*const H::NativeType> = (0..n_nodes_in_layer)
-        .map(|i| unsafe { layer.as_ptr().add(node_size * i) })
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        layer
+            .par_chunks(node_size * N_NODES_PER_TASK)
+            .zip(dst.par_chunks_mut(H::OUTPUT_SIZE * N_NODES_PER_TASK))
+            .for_each(|(run, dst)| hash_run(run, node_size, dst));
+    }
+}
+
+/// Hashes every `node_size` chunk of `run` into `dst` with [Hasher::hash_many_in_place].
+fn hash_node_run<H: Hasher>(run: &[H::NativeType], node_size: usize, dst: &mut [H::NativeType]) {
+    let n_nodes_in_run = run.len() / node_size;
+    let src_ptrs: Vec<*const H::NativeType> = (0..n_nodes_in_run)
+        .map(|i| unsafe { run.as_ptr().add(node_size * i) })
         .collect();
-    let dst_ptrs: Vec<*mut H::NativeType> = (0..n_nodes_in_layer)
+    let dst_ptrs: Vec<*mut H::NativeType> = (0..n_nodes_in_run)
         .map(|i| unsafe { dst.as_mut_ptr().add(H::OUTPUT_SIZE * i) })
         .collect();
 
@@ -54,6 +132,176 @@ pub fn hash_layer<H: Hasher>(layer: &[H::NativeType], node_size: usize, dst: &mu
     }
 }
 
+/// Like [hash_layer], but prefixes every node with `tag` before hashing it, see
+/// [Hasher::hash_leaf] and [Hasher::hash_nodes].
+pub fn hash_tagged_layer<H: Hasher>(
+    layer: &[H::NativeType],
+    node_size: usize,
+    tag: u8,
+    dst: &mut [H::NativeType],
+) where
+    H::NativeType: Copy + From<u8>,
+{
+    let n_nodes_in_layer = usize_safe_div(layer.len(), node_size);
+    assert!(n_nodes_in_layer <= dst.len() / H::OUTPUT_SIZE);
+    // The tag is fed to the hasher ahead of every node, instead of copying the layer with tags.
+    hash_node_runs::<H>(layer, node_size, dst, |run, node_size, dst| {
+        let mut hasher = H::new();
+        run.chunks_exact(node_size)
+            .zip(dst.chunks_exact_mut(H::OUTPUT_SIZE))
+            .for_each(|(node, dst)| {
+                hasher.update(&[tag.into()]);
+                hasher.update(node);
+                dst.copy_from_slice(hasher.finalize_reset().as_ref());
+            });
+    });
+}
+
+/// Hashes a layer of internal nodes into their parents, `arity` children per parent, prefixing
+/// every parent's input with [NODE_DOMAIN_TAG], see [hash_tagged_nodes].
+/// If the number of nodes is not a multiple of `arity`, the last group is padded with default (i.e.
+/// all-zero) hashes.
+pub fn hash_node_layer<H: Hasher>(layer: &[H::NativeType], arity: usize, dst: &mut [H::NativeType])
+where
+    H::NativeType: Copy + From<u8>,
+{
+    hash_padded_node_layer::<H>(layer, arity, H::Hash::default(), dst);
+}
+
+/// Like [hash_node_layer], but pads the last group with `padding_hash`.
+pub fn hash_padded_node_layer<H: Hasher>(
+    layer: &[H::NativeType],
+    arity: usize,
+    padding_hash: H::Hash,
+    dst: &mut [H::NativeType],
+) where
+    H::NativeType: Copy + From<u8>,
+{
+    let group_size = arity * H::OUTPUT_SIZE;
+    let n_full_groups = layer.len() / group_size;
+    let (full_groups, last_group) = layer.split_at(n_full_groups * group_size);
+    let (full_groups_dst, last_group_dst) = dst.split_at_mut(n_full_groups * H::OUTPUT_SIZE);
+    hash_tagged_layer::<H>(full_groups, group_size, NODE_DOMAIN_TAG, full_groups_dst);
+    if last_group.is_empty() {
+        return;
+    }
+
+    // The last group is hashed on its own, to pad it without copying the layer.
+    let mut hasher = H::new();
+    hasher.update(&[NODE_DOMAIN_TAG.into()]);
+    hasher.update(last_group);
+    let n_padding_nodes = (group_size - last_group.len()) / H::OUTPUT_SIZE;
+    (0..n_padding_nodes).for_each(|_| hasher.update(padding_hash.as_ref()));
+    last_group_dst[..H::OUTPUT_SIZE].copy_from_slice(hasher.finalize().as_ref());
+}
+
+/// Hashes a Merkle leaf followed by its `salt`, prefixed with [LEAF_DOMAIN_TAG]. For an empty salt,
+/// this is equal to [Hasher::hash_leaf].
+pub fn hash_salted_leaf<H: Hasher>(data: &[H::NativeType], salt: &[H::NativeType]) -> H::Hash
+where
+    H::NativeType: From<u8>,
+{
+    let mut hasher = H::new();
+    hasher.update(&[LEAF_DOMAIN_TAG.into()]);
+    hasher.update(data);
+    hasher.update(salt);
+    hasher.finalize()
+}
+
+/// Hashes the children of an internal node into their parent, consistently with
+/// [hash_node_layer]. For two children, this is equal to [Hasher::hash_nodes].
+pub fn hash_tagged_nodes<H: Hasher>(children: &[H::Hash]) -> H::Hash
+where
+    H::NativeType: From<u8>,
+{
+    let mut hasher = H::new();
+    hasher.update(&[NODE_DOMAIN_TAG.into()]);
+    children
+        .iter()
+        .for_each(|child| hasher.update(child.as_ref()));
+    hasher.finalize()
+}
+
+/// Hashes the top node of a [super::merkle_tree::MerkleTree] into its root, together with the
+/// number of rows the tree commits to, as a little-endian u64 of one native element per byte. The
+/// input is prefixed with [ROOT_DOMAIN_TAG], so trees of different shapes, e.g. a trace and the
+/// same trace padded, never share a root.
+pub fn hash_root<H: Hasher>(top_node: &H::Hash, n_rows: usize) -> H::Hash
+where
+    H::NativeType: From<u8>,
+{
+    let mut hasher = H::new();
+    hasher.update(&[ROOT_DOMAIN_TAG.into()]);
+    hasher.update(top_node.as_ref());
+    hasher.update(&(n_rows as u64).to_le_bytes().map(H::NativeType::from));
+    hasher.finalize()
+}
+
+/// Verifies the path from `leaf_hash` to `root` of a tree whose nodes are hashed with
+/// [Hasher::hash_pair_sorted], given the siblings along the path, bottom first. Since children are
+/// sorted, no direction bits are needed, but the position of the leaf is not authenticated.
+pub fn verify_sorted_path<H: Hasher>(
+    leaf_hash: &H::Hash,
+    siblings: &[H::Hash],
+    root: &H::Hash,
+) -> bool
+where
+    H::NativeType: From<u8> + Ord,
+{
+    let recomputed_root = siblings.iter().fold(*leaf_hash, |node, sibling| {
+        H::hash_pair_sorted(&node, sibling)
+    });
+    bool::from(recomputed_root.ct_eq(root))
+}
+
+/// Verifies the path from the leaf at `index` to `root` of a binary tree whose nodes are hashed
+/// with [Hasher::hash_nodes], given the siblings along the path, bottom first.
+///
+/// The children of every node are ordered with a conditional swap instead of a branch on the bits
+/// of `index`, so the reconstruction doesn't leak which path was opened. This is only needed by
+/// zero-knowledge (hiding) verifiers, where the queried index is secret.
+///
+/// Fails if `index` doesn't fit in a tree of `siblings.len()` layers.
+pub fn verify_path_constant_time<H: Hasher>(
+    leaf_hash: &H::Hash,
+    index: usize,
+    siblings: &[H::Hash],
+    root: &H::Hash,
+) -> bool
+where
+    H::NativeType: From<u8>,
+    H::Hash: ConditionallySelectable,
+{
+    let recomputed_root = siblings
+        .iter()
+        .enumerate()
+        .fold(*leaf_hash, |node, (i, sibling)| {
+            let (mut left, mut right) = (node, *sibling);
+            let bit = u32::try_from(i).map_or(0, |i| index.checked_shr(i).unwrap_or(0) & 1);
+            H::Hash::conditional_swap(&mut left, &mut right, Choice::from(bit as u8));
+            H::hash_nodes(&left, &right)
+        });
+    let in_range = u32::try_from(siblings.len())
+        .ok()
+        .and_then(|n_layers| index.checked_shr(n_layers))
+        .map_or(true, |high_bits| high_bits == 0);
+    bool::from(recomputed_root.ct_eq(root) & Choice::from(in_range as u8))
+}
+
+/// Counts the leading zero bits of `bytes`, read as a big-endian integer: bits are counted from the
+/// most significant bit of `bytes[0]` onwards, and all-zero bytes have `8 * bytes.len()`. This is
+/// the difficulty of a digest for grinding, see [crate::core::proof_of_work::ProofOfWork].
+pub fn leading_zeros(bytes: &[u8]) -> u32 {
+    let mut n_bits = 0;
+    for byte in bytes {
+        n_bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    n_bits
+}
+
 // Given a data of a tree, hashes the entire tree.
 pub fn hash_merkle_tree<H: Hasher>(data: &mut [&mut [H::NativeType]]) {
     (0..data.len() - 1).for_each(|i| {
@@ -111,12 +359,12 @@ pub unsafe fn transpose_to_bytes<T: Sized>(column_array: &ColumnArray<T>, dst: &
         unsafe {
             let mut dst_ptr = *ptr;
             for c in column_array {
-                std::ptr::copy_nonoverlapping(
+                core::ptr::copy_nonoverlapping(
                     c.as_ptr().add(i) as *mut u8,
                     dst_ptr,
-                    std::mem::size_of::<T>(),
+                    core::mem::size_of::<T>(),
                 );
-                dst_ptr = dst_ptr.add(std::mem::size_of::<T>());
+                dst_ptr = dst_ptr.add(core::mem::size_of::<T>());
             }
         }
     }
@@ -143,7 +391,7 @@ pub unsafe fn inject<T: Sized>(
     n_rows_in_node: usize,
     gap_offset: usize,
 ) {
-    let ptr_offset = column_array.len() * n_rows_in_node * std::mem::size_of::<T>() + gap_offset;
+    let ptr_offset = column_array.len() * n_rows_in_node * core::mem::size_of::<T>() + gap_offset;
     let offseted_pointers: Vec<*mut u8> = (gap_offset..dst.len())
         .step_by(ptr_offset)
         .map(|i| unsafe { dst.as_mut_ptr().add(i) })
@@ -166,9 +414,9 @@ pub fn column_to_row_major<T>(mut mat: ColumnArray<T>) -> Vec<T> {
     // Inject(transpose).
     // Safe because enough memory is allocated.
     unsafe {
-        let row_major_matrix_byte_slice = std::slice::from_raw_parts_mut(
+        let row_major_matrix_byte_slice = core::slice::from_raw_parts_mut(
             row_major_matrix_vec.as_mut_ptr() as *mut u8,
-            vec_length * std::mem::size_of::<T>(),
+            vec_length * core::mem::size_of::<T>(),
         );
         inject(&mat, row_major_matrix_byte_slice, 1, 0);
         row_major_matrix_vec.set_len(vec_length);
@@ -324,8 +572,9 @@ pub mod tests {
     use crate::commitment_scheme::hasher::Hasher;
     use crate::commitment_scheme::merkle_input::MerkleTreeInput;
     use crate::commitment_scheme::utils::{
-        allocate_layer, hash_layer, hash_merkle_tree, hash_merkle_tree_from_bottom_layer, inject,
-        inject_hash_in_pairs, transpose_to_bytes, tree_data_as_mut_ref,
+        allocate_layer, hash_layer, hash_merkle_tree, hash_merkle_tree_from_bottom_layer,
+        hash_padded_node_layer, hash_tagged_nodes, inject, inject_hash_in_pairs,
+        transpose_to_bytes, tree_data_as_mut_ref,
     };
     use crate::core::fields::m31::M31;
     use crate::math::utils::log2_ceil;
@@ -466,6 +715,36 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn hash_padded_node_layer_test() {
+        let nodes: Vec<_> = (0..5u8).map(|i| Blake3Hasher::hash(&[i])).collect();
+        let padding_hash = Blake3Hasher::hash(b"padding");
+        let layer: Vec<u8> = nodes
+            .iter()
+            .flat_map(|node| node.as_ref().to_vec())
+            .collect();
+        let mut res_layer = allocate_layer(2 * Blake3Hasher::OUTPUT_SIZE);
+
+        hash_padded_node_layer::<Blake3Hasher>(&layer, 4, padding_hash, &mut res_layer);
+
+        let expected_parents = [
+            hash_tagged_nodes::<Blake3Hasher>(&nodes[..4]),
+            hash_tagged_nodes::<Blake3Hasher>(&[
+                nodes[4],
+                padding_hash,
+                padding_hash,
+                padding_hash,
+            ]),
+        ];
+        assert_eq!(
+            res_layer[..],
+            expected_parents
+                .iter()
+                .flat_map(|parent| parent.as_ref().to_vec())
+                .collect::<Vec<_>>()[..]
+        );
+    }
+
     #[test]
     fn hash_tree_test() {
         let mut tree_data =
@@ -1,14 +1,34 @@
+use std::collections::BTreeSet;
 use std::iter;
+use std::marker::PhantomData;
+
+use thiserror::Error;
 
 use super::fields::m31::{BaseField, N_BYTES_FELT, P};
 use super::fields::qm31::{SecureField, SECURE_FIELD_EXTENSION_DEGREE};
-use super::fields::IntoSlice;
-use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+use crate::commitment_scheme::blake2_hash::Blake2sHasher;
 use crate::commitment_scheme::hasher::Hasher;
+use crate::commitment_scheme::serialize::Endianness;
+use crate::core::proof_of_work::{
+    ProofOfWork, ProofOfWorkError, ProofOfWorkProof, ProofOfWorkVerificationError,
+};
+use crate::math::utils::usize_div_ceil;
 
 pub const BLAKE_BYTES_PER_HASH: usize = 32;
-pub const FELTS_PER_HASH: usize = 8;
+pub const FELTS_PER_HASH: usize = BLAKE_BYTES_PER_HASH / N_BYTES_FELT;
 pub const EXTENSION_FELTS_PER_HASH: usize = 2;
+/// Domain separation tag of [Channel::mix_digest]. Every mix of a [HashChannel] hashes the digest,
+/// then the tag of its kind, then its input, so mixes of different kinds never collide, even on
+/// inputs of equal bytes.
+pub const DIGEST_DOMAIN_TAG: u8 = 0x01;
+/// Domain separation tag of [Channel::mix_felts], see [DIGEST_DOMAIN_TAG].
+pub const FELTS_DOMAIN_TAG: u8 = 0x02;
+/// Domain separation tag of [Channel::mix_bytes_framed], see [DIGEST_DOMAIN_TAG].
+pub const FRAMED_DOMAIN_TAG: u8 = 0x03;
+/// Domain separation tag of [Channel::mix_nonce], see [DIGEST_DOMAIN_TAG].
+pub const NONCE_DOMAIN_TAG: u8 = 0x04;
+/// Domain separation tag of [Channel::mix_seed], see [DIGEST_DOMAIN_TAG].
+pub const SEED_DOMAIN_TAG: u8 = 0xff;
 
 #[derive(Default)]
 pub struct ChannelTime {
@@ -33,123 +53,383 @@ pub trait Channel {
     const BYTES_PER_HASH: usize;
 
     fn new(digest: Self::Digest) -> Self;
+    /// Starts a channel from the default digest, with `seed` mixed in by [Channel::mix_seed], e.g.
+    /// for tests and reproducible benchmarks. Provers should start from the initial state of
+    /// their protocol instead.
+    fn from_seed(seed: &[u8]) -> Self
+    where
+        Self: Sized,
+        Self::Digest: Default,
+    {
+        let mut channel = Self::new(Self::Digest::default());
+        channel.mix_seed(seed);
+        channel
+    }
     fn get_digest(&self) -> Self::Digest;
+    /// Total number of bytes mixed into the channel, including framing, but not the digest every
+    /// mix is chained with. A debugging aid for comparing transcripts across implementations.
+    fn absorbed_bytes(&self) -> u64;
 
     // Mix functions.
     fn mix_digest(&mut self, digest: Self::Digest);
+    /// Mixes field elements into the channel. The elements are absorbed without a length prefix,
+    /// as their number is fixed by the protocol at every call site, so the framing is implicit.
+    /// Like every mix of a [HashChannel], they are prefixed with the tag of their kind,
+    /// [FELTS_DOMAIN_TAG].
     fn mix_felts(&mut self, felts: &[SecureField]);
+    /// Mixes variable-length bytes into the channel, prefixed with [FRAMED_DOMAIN_TAG] and their
+    /// length as 8 big-endian bytes, so that different sequences of mixed data can't collide.
+    fn mix_bytes_framed(&mut self, data: &[u8]);
+    /// Mixes externally agreed randomness, e.g. a randomness beacon, into the channel. The seed is
+    /// prefixed with [SEED_DOMAIN_TAG] and its length, so it can't be confused with other mixes.
+    /// The prover and the verifier must mix the same seed at the same position of the transcript.
+    fn mix_seed(&mut self, seed: &[u8]);
     fn mix_nonce(&mut self, nonce: u64);
 
     // Draw functions.
+    /// Draws a uniform random element of the extension field QM31 with the [SampleStrategy] of the
+    /// channel. The default, [RejectionSampling], splits the random bytes into 4-byte limbs and
+    /// resamples until every limb is below 2P, so that their reductions are uniform M31 elements.
+    /// Verifier challenges must be drawn in QM31, e.g. with [HashChannel::draw_qm31], rather than
+    /// by packing base field elements from [Channel::draw_random_bytes], as soundness relies on
+    /// the size of the extension field.
     fn draw_felt(&mut self) -> SecureField;
-    /// Generates a uniform random vector of SecureField elements.
+    /// Generates a uniform random vector of SecureField elements, see [Channel::draw_felt].
     fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField>;
     /// Returns a vector of random bytes of length `BYTES_PER_HASH`.
     fn draw_random_bytes(&mut self) -> Vec<u8>;
+    /// Returns the outputs of `n_hashes` consecutive calls to [Channel::draw_random_bytes],
+    /// concatenated. Channels may squeeze them in a single pass.
+    fn draw_random_bytes_batched(&mut self, n_hashes: usize) -> Vec<u8> {
+        (0..n_hashes)
+            .flat_map(|_| self.draw_random_bytes())
+            .collect()
+    }
+
+    /// Draws `n_queries` distinct query indices uniformly over [0, 2^`log_domain_size`).
+    ///
+    /// Samples that collide with an already drawn index are rejected. The indices are returned
+    /// in ascending order.
+    fn draw_queries(
+        &mut self,
+        n_queries: usize,
+        log_domain_size: u32,
+    ) -> Result<Vec<usize>, ChannelError> {
+        let mask = query_mask(n_queries, log_domain_size)?;
+        let mut queries = BTreeSet::new();
+        while queries.len() < n_queries {
+            let random_bytes = self.draw_random_bytes();
+            for chunk in random_bytes.chunks_exact(N_BYTES_FELT) {
+                let query = u32::from_le_bytes(chunk.try_into().unwrap()) & mask;
+                queries.insert(query as usize);
+                if queries.len() == n_queries {
+                    break;
+                }
+            }
+        }
+        Ok(queries.into_iter().collect())
+    }
+
+    /// Like [Channel::draw_queries], squeezing the random bytes of many queries at once with
+    /// [Channel::draw_random_bytes_batched]. Only the samples that collide with an already drawn
+    /// index are redrawn, in another batch. Both the queries and the resulting state of the
+    /// channel are identical to the ones of [Channel::draw_queries].
+    fn draw_queries_batched(
+        &mut self,
+        n_queries: usize,
+        log_domain_size: u32,
+    ) -> Result<Vec<usize>, ChannelError> {
+        let mask = query_mask(n_queries, log_domain_size)?;
+        let samples_per_hash = Self::BYTES_PER_HASH / N_BYTES_FELT;
+        let mut queries = BTreeSet::new();
+        while queries.len() < n_queries {
+            // Every hash yields at most `samples_per_hash` new queries, so no more hashes are
+            // squeezed than by `draw_queries`.
+            let n_hashes = usize_div_ceil(n_queries - queries.len(), samples_per_hash);
+            let random_bytes = self.draw_random_bytes_batched(n_hashes);
+            for chunk in random_bytes
+                .chunks_exact(Self::BYTES_PER_HASH)
+                .flat_map(|hash| hash.chunks_exact(N_BYTES_FELT))
+            {
+                let query = u32::from_le_bytes(chunk.try_into().unwrap()) & mask;
+                queries.insert(query as usize);
+                if queries.len() == n_queries {
+                    break;
+                }
+            }
+        }
+        Ok(queries.into_iter().collect())
+    }
 }
 
-/// A channel that can be used to draw random elements from a [Blake2sHash] digest.
-pub struct Blake2sChannel {
-    digest: Blake2sHash,
-    channel_time: ChannelTime,
+/// Returns the mask reducing a random word to a query index over [0, 2^`log_domain_size`), after
+/// checking that queries are drawn from a single word and that the domain holds `n_queries`
+/// distinct queries.
+fn query_mask(n_queries: usize, log_domain_size: u32) -> Result<u32, ChannelError> {
+    if log_domain_size > u32::BITS {
+        return Err(ChannelError::DomainTooLarge { log_domain_size });
+    }
+    let domain_size = 1u64 << log_domain_size;
+    if n_queries as u64 > domain_size {
+        return Err(ChannelError::TooManyQueries {
+            n_queries,
+            domain_size,
+        });
+    }
+    Ok((domain_size - 1) as u32)
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ChannelError {
+    #[error("Cannot draw {n_queries} distinct queries from a domain of size {domain_size}.")]
+    TooManyQueries { n_queries: usize, domain_size: u64 },
+    #[error("Cannot draw queries from a domain of size 2^{log_domain_size}, at most 2^32.")]
+    DomainTooLarge { log_domain_size: u32 },
 }
 
-impl Blake2sChannel {
-    /// Generates a uniform random vector of BaseField elements.
-    fn draw_base_felts(&mut self) -> [BaseField; FELTS_PER_HASH] {
+/// How a [HashChannel] turns the random bytes it squeezes into field elements.
+///
+/// Part of the transcript: the prover and the verifier must use the same strategy.
+pub trait SampleStrategy {
+    /// Samples an element, calling `random_bytes` for fresh bytes of the channel as needed.
+    fn next_felt(random_bytes: &mut dyn FnMut() -> Vec<u8>) -> SecureField;
+
+    /// Samples `n_felts` elements. Strategies may override this to share random bytes between
+    /// elements.
+    fn next_felts(random_bytes: &mut dyn FnMut() -> Vec<u8>, n_felts: usize) -> Vec<SecureField> {
+        (0..n_felts)
+            .map(|_| Self::next_felt(random_bytes))
+            .collect()
+    }
+}
+
+/// The default [SampleStrategy]. Random bytes are split into 4-byte words, and resampled until
+/// all of them are below 2P, so that their reductions are uniform M31 elements. Consecutive groups
+/// of 4 such elements form the sampled QM31 elements.
+pub struct RejectionSampling;
+
+impl RejectionSampling {
+    fn next_base_felts(random_bytes: &mut dyn FnMut() -> Vec<u8>) -> Vec<BaseField> {
         // Repeats hashing with an increasing counter until getting a good result.
         // Retry probability for each round is ~ 2^(-28).
         loop {
-            let random_bytes: [u32; FELTS_PER_HASH] = self
-                .draw_random_bytes()
+            let random_bytes: Vec<u32> = random_bytes()
                 .chunks_exact(N_BYTES_FELT) // 4 bytes per u32.
                 .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap();
+                .collect();
 
             // Retry if not all the u32 are in the range [0, 2P).
             if random_bytes.iter().all(|x| *x < 2 * P) {
                 return random_bytes
                     .into_iter()
                     .map(|x| BaseField::reduce(x as u64))
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap();
+                    .collect();
             }
         }
     }
 }
 
-impl Channel for Blake2sChannel {
-    type Digest = Blake2sHash;
-    const BYTES_PER_HASH: usize = BLAKE_BYTES_PER_HASH;
+impl SampleStrategy for RejectionSampling {
+    fn next_felt(random_bytes: &mut dyn FnMut() -> Vec<u8>) -> SecureField {
+        let felts = Self::next_base_felts(random_bytes);
+        SecureField::from_m31_array(felts[..SECURE_FIELD_EXTENSION_DEGREE].try_into().unwrap())
+    }
 
-    fn new(digest: Self::Digest) -> Self {
-        Blake2sChannel {
+    fn next_felts(random_bytes: &mut dyn FnMut() -> Vec<u8>, n_felts: usize) -> Vec<SecureField> {
+        let mut felts = iter::from_fn(|| Some(Self::next_base_felts(random_bytes))).flatten();
+        let secure_felts = iter::from_fn(|| {
+            Some(SecureField::from_m31_array([
+                felts.next()?,
+                felts.next()?,
+                felts.next()?,
+                felts.next()?,
+            ]))
+        });
+        secure_felts.take(n_felts).collect()
+    }
+}
+
+/// The configuration of [HashChannel::new_with_config]. The default is the configuration of
+/// [Channel::new].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// The byte order field elements are mixed in, see [Channel::mix_felts]. Random bytes are
+    /// turned into field elements by the [SampleStrategy] regardless.
+    pub endianness: Endianness,
+}
+
+/// A channel that can be used to draw random elements from the digest of a byte [Hasher].
+/// Elements are sampled with `S`, see [SampleStrategy].
+pub struct HashChannel<H: Hasher<NativeType = u8>, S: SampleStrategy = RejectionSampling> {
+    digest: H::Hash,
+    channel_time: ChannelTime,
+    absorbed_bytes: u64,
+    config: ChannelConfig,
+    phantom: PhantomData<S>,
+}
+
+/// A channel that can be used to draw random elements from a [Blake2sHasher] digest.
+pub type Blake2sChannel = HashChannel<Blake2sHasher>;
+
+impl<H: Hasher<NativeType = u8>, S: SampleStrategy> HashChannel<H, S> {
+    /// Like [Channel::new], with the given configuration. The prover and the verifier must use
+    /// the same configuration.
+    pub fn new_with_config(digest: H::Hash, config: ChannelConfig) -> Self {
+        HashChannel {
             digest,
             channel_time: ChannelTime::default(),
+            absorbed_bytes: 0,
+            config,
+            phantom: PhantomData,
         }
     }
 
+    /// Generates a uniform random vector of `H::OUTPUT_SIZE / 4` BaseField elements, see
+    /// [RejectionSampling].
+    pub fn draw_base_felts(&mut self) -> Vec<BaseField> {
+        RejectionSampling::next_base_felts(&mut || self.draw_random_bytes())
+    }
+
+    /// Draws a uniform random QM31 element from 16 random bytes, i.e. four 4-byte limbs, each
+    /// rejected if it is not below 2P and reduced modulo P otherwise. Unlike [Channel::draw_felt],
+    /// it doesn't depend on the [SampleStrategy] of the channel, and only the rejected limbs are
+    /// resampled. Verifier challenges should be drawn this way.
+    pub fn draw_qm31(&mut self) -> SecureField {
+        self.draw_qm31s(1)[0]
+    }
+
+    /// Draws `n_qm31s` uniform random QM31 elements, see [Self::draw_qm31]. Consecutive elements
+    /// share the random bytes of a hash.
+    pub fn draw_qm31s(&mut self, n_qm31s: usize) -> Vec<SecureField> {
+        let mut limbs = iter::repeat_with(|| self.draw_random_bytes())
+            .flat_map(|random_bytes| {
+                random_bytes
+                    .chunks_exact(N_BYTES_FELT)
+                    .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|limb| *limb < 2 * P)
+            .map(|limb| BaseField::reduce(limb as u64));
+        (0..n_qm31s)
+            .map(|_| SecureField::from_m31_array(std::array::from_fn(|_| limbs.next().unwrap())))
+            .collect()
+    }
+
+    /// Checks whether `nonce` is a proof of work of `n_bits` on the current digest, see
+    /// [ProofOfWork], then mixes it into the channel, even if it is invalid.
+    pub fn mix_nonce_and_check(&mut self, nonce: u64, n_bits: u32) -> bool {
+        let valid = ProofOfWork::new(n_bits).check(self, nonce);
+        self.mix_nonce(nonce);
+        valid
+    }
+
+    /// Searches for the smallest nonce for which [Self::mix_nonce_and_check] succeeds, mixes it
+    /// into the channel and returns it, see [ProofOfWork::prove].
+    pub fn grind(&mut self, n_bits: u32) -> Result<u64, ProofOfWorkError> {
+        ProofOfWork::new(n_bits)
+            .prove(self)
+            .map(|proof| proof.nonce)
+    }
+
+    /// Verifier side of [Self::grind], see [ProofOfWork::verify]. Mixes `nonce` into the channel
+    /// only if it is valid.
+    pub fn verify_grind(
+        &mut self,
+        nonce: u64,
+        n_bits: u32,
+    ) -> Result<(), ProofOfWorkVerificationError> {
+        ProofOfWork::new(n_bits).verify(self, &ProofOfWorkProof { nonce })
+    }
+
+    /// Mixes the concatenation of `inputs` into the channel, prefixed with the domain separation
+    /// `tag` of the kind of mix, see [DIGEST_DOMAIN_TAG].
+    fn mix_tagged(&mut self, tag: u8, inputs: &[&[u8]]) {
+        let mut hasher = H::new();
+        hasher.update(self.digest.as_ref());
+        hasher.update(&[tag]);
+        inputs.iter().for_each(|input| hasher.update(input));
+        self.absorbed_bytes += 1 + inputs.iter().map(|input| input.len() as u64).sum::<u64>();
+
+        self.digest = hasher.finalize();
+        self.channel_time.inc_challenges();
+    }
+}
+
+impl<H: Hasher<NativeType = u8>, S: SampleStrategy> Channel for HashChannel<H, S> {
+    type Digest = H::Hash;
+    const BYTES_PER_HASH: usize = H::OUTPUT_SIZE;
+
+    fn new(digest: Self::Digest) -> Self {
+        Self::new_with_config(digest, ChannelConfig::default())
+    }
+
     fn get_digest(&self) -> Self::Digest {
         self.digest
     }
 
+    fn absorbed_bytes(&self) -> u64 {
+        self.absorbed_bytes
+    }
+
     fn mix_digest(&mut self, digest: Self::Digest) {
-        self.digest = Blake2sHasher::concat_and_hash(&self.digest, &digest);
-        self.channel_time.inc_challenges();
+        self.mix_tagged(DIGEST_DOMAIN_TAG, &[digest.as_ref()]);
     }
 
     fn mix_felts(&mut self, felts: &[SecureField]) {
-        let mut hasher = Blake2sHasher::new();
-        hasher.update(self.digest.as_ref());
-        hasher.update(IntoSlice::<u8>::into_slice(felts));
+        let bytes = self.config.endianness.secure_felts_to_bytes(felts);
+        self.mix_tagged(FELTS_DOMAIN_TAG, &[&bytes]);
+    }
 
-        self.digest = hasher.finalize();
-        self.channel_time.inc_challenges();
+    fn mix_bytes_framed(&mut self, data: &[u8]) {
+        self.mix_tagged(
+            FRAMED_DOMAIN_TAG,
+            &[&(data.len() as u64).to_be_bytes(), data],
+        );
     }
 
-    fn mix_nonce(&mut self, nonce: u64) {
-        // Copy the elements from the original array to the new array
-        let mut padded_nonce = vec![0; BLAKE_BYTES_PER_HASH];
-        padded_nonce[..8].copy_from_slice(&nonce.to_le_bytes());
+    fn mix_seed(&mut self, seed: &[u8]) {
+        self.mix_tagged(SEED_DOMAIN_TAG, &[&(seed.len() as u64).to_be_bytes(), seed]);
+    }
 
-        self.digest =
-            Blake2sHasher::concat_and_hash(&self.digest, &Blake2sHash::from(padded_nonce));
-        self.channel_time.inc_challenges();
+    fn mix_nonce(&mut self, nonce: u64) {
+        self.mix_tagged(NONCE_DOMAIN_TAG, &[&nonce.to_le_bytes()]);
     }
 
     fn draw_felt(&mut self) -> SecureField {
-        let felts: [BaseField; FELTS_PER_HASH] = self.draw_base_felts();
-        SecureField::from_m31_array(felts[..SECURE_FIELD_EXTENSION_DEGREE].try_into().unwrap())
+        S::next_felt(&mut || self.draw_random_bytes())
     }
 
     fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
-        let mut felts = iter::from_fn(|| Some(self.draw_base_felts())).flatten();
-        let secure_felts = iter::from_fn(|| {
-            Some(SecureField::from_m31_array([
-                felts.next()?,
-                felts.next()?,
-                felts.next()?,
-                felts.next()?,
-            ]))
-        });
-        secure_felts.take(n_felts).collect()
+        S::next_felts(&mut || self.draw_random_bytes(), n_felts)
     }
 
     fn draw_random_bytes(&mut self) -> Vec<u8> {
         let mut hash_input = self.digest.as_ref().to_vec();
 
-        // Pad the counter to 32 bytes.
-        let mut padded_counter = [0; BLAKE_BYTES_PER_HASH];
+        // Pad the counter to the hash size.
+        let mut padded_counter = vec![0; Self::BYTES_PER_HASH];
         let counter_bytes = self.channel_time.n_sent.to_le_bytes();
         padded_counter[0..counter_bytes.len()].copy_from_slice(&counter_bytes);
 
         hash_input.extend_from_slice(&padded_counter);
 
         self.channel_time.inc_sent();
-        Blake2sHasher::hash(&hash_input).into()
+        H::hash(&hash_input).into()
+    }
+
+    fn draw_random_bytes_batched(&mut self, n_hashes: usize) -> Vec<u8> {
+        // The digest is shared by every hash input, only the padded counter changes.
+        let mut hash_input = self.digest.as_ref().to_vec();
+        hash_input.resize(2 * Self::BYTES_PER_HASH, 0);
+        let mut random_bytes = Vec::with_capacity(n_hashes * Self::BYTES_PER_HASH);
+        for _ in 0..n_hashes {
+            let counter_bytes = self.channel_time.n_sent.to_le_bytes();
+            hash_input[Self::BYTES_PER_HASH..Self::BYTES_PER_HASH + counter_bytes.len()]
+                .copy_from_slice(&counter_bytes);
+            random_bytes.extend_from_slice(H::hash(&hash_input).as_ref());
+            self.channel_time.inc_sent();
+        }
+        random_bytes
     }
 }
 
@@ -157,14 +437,25 @@ impl Channel for Blake2sChannel {
 mod tests {
     use std::collections::BTreeSet;
 
-    use crate::commitment_scheme::blake2_hash::Blake2sHash;
-    use crate::core::channel::{Blake2sChannel, Channel};
-    use crate::core::fields::qm31::SecureField;
+    use itertools::Itertools;
+
+    use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+    use crate::commitment_scheme::blake3_hash::{Blake3Hash, Blake3Hasher};
+    use crate::commitment_scheme::hasher::Hasher;
+    use crate::commitment_scheme::serialize::Endianness;
+    use crate::commitment_scheme::utils::leading_zeros;
+    use crate::core::channel::{
+        Blake2sChannel, Channel, ChannelConfig, ChannelError, HashChannel, SampleStrategy,
+        FELTS_DOMAIN_TAG, FELTS_PER_HASH,
+    };
+    use crate::core::fields::m31::{BaseField, P};
+    use crate::core::fields::qm31::{SecureField, QM31};
+    use crate::core::proof_of_work::{ProofOfWork, ProofOfWorkError};
     use crate::m31;
 
     #[test]
     fn test_initialize_channel() {
-        let initial_digest = Blake2sHash::from(vec![0; 32]);
+        let initial_digest = Blake2sHash::from([0; 32]);
         let channel = Blake2sChannel::new(initial_digest);
 
         // Assert that the channel is initialized correctly.
@@ -175,7 +466,7 @@ mod tests {
 
     #[test]
     fn test_channel_time() {
-        let initial_digest = Blake2sHash::from(vec![0; 32]);
+        let initial_digest = Blake2sHash::from([0; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
 
         assert_eq!(channel.channel_time.n_challenges, 0);
@@ -189,7 +480,7 @@ mod tests {
         assert_eq!(channel.channel_time.n_challenges, 0);
         assert_eq!(channel.channel_time.n_sent, 6);
 
-        channel.mix_digest(Blake2sHash::from(vec![1; 32]));
+        channel.mix_digest(Blake2sHash::from([1; 32]));
         assert_eq!(channel.channel_time.n_challenges, 1);
         assert_eq!(channel.channel_time.n_sent, 0);
 
@@ -201,7 +492,7 @@ mod tests {
 
     #[test]
     fn test_draw_random_bytes() {
-        let initial_digest = Blake2sHash::from(vec![1; 32]);
+        let initial_digest = Blake2sHash::from([1; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
 
         let first_random_bytes = channel.draw_random_bytes();
@@ -212,7 +503,7 @@ mod tests {
 
     #[test]
     pub fn test_draw_felt() {
-        let initial_digest = Blake2sHash::from(vec![2; 32]);
+        let initial_digest = Blake2sHash::from([2; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
 
         let first_random_felt = channel.draw_felt();
@@ -223,7 +514,7 @@ mod tests {
 
     #[test]
     pub fn test_draw_felts() {
-        let initial_digest = Blake2sHash::from(vec![2; 32]);
+        let initial_digest = Blake2sHash::from([2; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
 
         let mut random_felts = channel.draw_felts(5);
@@ -236,9 +527,77 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_draw_felts_deterministic() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::from([2; 32]));
+        let mut other_channel = Blake2sChannel::new(Blake2sHash::from([2; 32]));
+        channel.mix_bytes_framed(b"transcript");
+        other_channel.mix_bytes_framed(b"transcript");
+
+        let felts = channel.draw_felts(100);
+
+        assert_eq!(felts, other_channel.draw_felts(100));
+        assert!(felts
+            .iter()
+            .flat_map(|felt| felt.to_m31_array())
+            .all(|limb| limb.0 < P));
+    }
+
+    #[test]
+    pub fn test_draw_qm31s_deterministic() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::from([2; 32]));
+        let mut other_channel = Blake2sChannel::new(Blake2sHash::from([2; 32]));
+        channel.mix_bytes_framed(b"transcript");
+        other_channel.mix_bytes_framed(b"transcript");
+
+        let qm31s = channel.draw_qm31s(101);
+
+        assert_eq!(qm31s, other_channel.draw_qm31s(101));
+        assert_eq!(channel.draw_qm31(), other_channel.draw_qm31());
+        assert!(qm31s
+            .iter()
+            .flat_map(|qm31| qm31.to_m31_array())
+            .all(|limb| limb.0 < P));
+        assert_eq!(qm31s.iter().unique().count(), qm31s.len());
+    }
+
+    #[test]
+    pub fn test_draw_qm31_limbs() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::from([3; 32]));
+        let random_bytes = Blake2sChannel::new(Blake2sHash::from([3; 32])).draw_random_bytes();
+
+        let qm31 = channel.draw_qm31();
+
+        // Limbs are either below 2P and kept, or rejected, with probability ~2^-31 each.
+        let limbs = random_bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .filter(|limb| *limb < 2 * P)
+            .take(4)
+            .map(|limb| BaseField::reduce(limb as u64))
+            .collect_vec();
+        assert_eq!(qm31.to_m31_array().to_vec(), limbs);
+    }
+
+    #[test]
+    pub fn test_from_seed() {
+        let mut channel = Blake2sChannel::from_seed(b"test");
+        let mut other_channel = Blake2sChannel::from_seed(b"test");
+
+        assert_eq!(channel.draw_felts(3), other_channel.draw_felts(3));
+        assert_ne!(
+            Blake2sChannel::from_seed(b"test").draw_felt(),
+            Blake2sChannel::new(Blake2sHash::default()).draw_felt()
+        );
+        assert_ne!(
+            Blake2sChannel::from_seed(b"test").get_digest(),
+            Blake2sChannel::from_seed(b"other").get_digest()
+        );
+    }
+
     #[test]
     pub fn test_mix_digest() {
-        let initial_digest = Blake2sHash::from(vec![0; 32]);
+        let initial_digest = Blake2sHash::from([0; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
 
         for _ in 0..10 {
@@ -247,13 +606,209 @@ mod tests {
         }
 
         // Reseed channel and check the digest was changed.
-        channel.mix_digest(Blake2sHash::from(vec![1; 32]));
+        channel.mix_digest(Blake2sHash::from([1; 32]));
         assert_ne!(initial_digest, channel.digest);
     }
 
+    #[test]
+    pub fn test_deterministic_channel() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut other_channel = Blake2sChannel::new(Blake2sHash::default());
+        let felts = [SecureField::from(m31!(5)), SecureField::from(m31!(6))];
+
+        for channel in [&mut channel, &mut other_channel] {
+            channel.mix_felts(&felts);
+            channel.mix_digest(Blake2sHash::from([1; 32]));
+        }
+
+        assert_eq!(channel.draw_felts(3), other_channel.draw_felts(3));
+        assert_eq!(channel.draw_base_felts(), other_channel.draw_base_felts());
+        assert_eq!(channel.get_digest(), other_channel.get_digest());
+    }
+
+    #[test]
+    pub fn test_blake3_channel() {
+        let mut channel = HashChannel::<Blake3Hasher>::new(Blake3Hash::default());
+
+        let first_random_felt = channel.draw_felt();
+
+        assert_eq!(channel.draw_base_felts().len(), FELTS_PER_HASH);
+        assert_ne!(first_random_felt, channel.draw_felt());
+    }
+
+    #[test]
+    pub fn test_draw_queries() {
+        let log_domain_size = 10;
+        let mut channel = Blake2sChannel::new(Blake2sHash::from([3; 32]));
+        let mut other_channel = Blake2sChannel::new(Blake2sHash::from([3; 32]));
+
+        let queries = channel.draw_queries(100, log_domain_size).unwrap();
+
+        assert_eq!(
+            queries,
+            other_channel.draw_queries(100, log_domain_size).unwrap()
+        );
+        assert_eq!(queries.len(), 100);
+        assert!(queries.windows(2).all(|w| w[0] < w[1]));
+        assert!(queries.iter().all(|q| *q < 1 << log_domain_size));
+    }
+
+    #[test]
+    pub fn test_draw_queries_batched() {
+        for (n_queries, log_domain_size) in [(0, 0), (1, 0), (100, 10), (500, 20), (256, 8)] {
+            let mut channel = Blake2sChannel::new(Blake2sHash::from([3; 32]));
+            let mut batched_channel = Blake2sChannel::new(Blake2sHash::from([3; 32]));
+
+            let queries = channel.draw_queries(n_queries, log_domain_size).unwrap();
+
+            assert_eq!(
+                batched_channel
+                    .draw_queries_batched(n_queries, log_domain_size)
+                    .unwrap(),
+                queries
+            );
+            assert_eq!(
+                batched_channel.draw_random_bytes(),
+                channel.draw_random_bytes()
+            );
+        }
+        assert_eq!(
+            Blake2sChannel::new(Blake2sHash::default()).draw_queries_batched(17, 4),
+            Err(ChannelError::TooManyQueries {
+                n_queries: 17,
+                domain_size: 16
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_draw_random_bytes_batched() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut batched_channel = Blake2sChannel::new(Blake2sHash::default());
+
+        let random_bytes = (0..5)
+            .flat_map(|_| channel.draw_random_bytes())
+            .collect_vec();
+
+        assert_eq!(batched_channel.draw_random_bytes_batched(5), random_bytes);
+        assert_eq!(batched_channel.channel_time.n_sent, 5);
+    }
+
+    #[test]
+    pub fn test_draw_queries_full_domain() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+
+        let queries = channel.draw_queries(16, 4).unwrap();
+
+        assert_eq!(queries, (0..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn test_draw_too_many_queries() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+
+        assert_eq!(
+            channel.draw_queries(17, 4),
+            Err(ChannelError::TooManyQueries {
+                n_queries: 17,
+                domain_size: 16
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_draw_queries_domain_too_large() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+
+        assert_eq!(channel.draw_queries(1, 32).unwrap().len(), 1);
+        assert_eq!(
+            channel.draw_queries(1, 33),
+            Err(ChannelError::DomainTooLarge {
+                log_domain_size: 33
+            })
+        );
+        assert_eq!(
+            channel.draw_queries_batched(1, 33),
+            Err(ChannelError::DomainTooLarge {
+                log_domain_size: 33
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_grind() {
+        // A digest with a small 20 bit nonce, to keep the test fast without optimizations.
+        for n_bits in [0, 8, 20] {
+            let mut prover_channel = Blake2sChannel::new(Blake2sHash::from([2; 32]));
+            let mut verifier_channel = Blake2sChannel::new(Blake2sHash::from([2; 32]));
+
+            let nonce = prover_channel.grind(n_bits).unwrap();
+
+            assert!(ProofOfWork::new(n_bits).check(&verifier_channel, nonce));
+            verifier_channel.verify_grind(nonce, n_bits).unwrap();
+            assert_eq!(prover_channel.get_digest(), verifier_channel.get_digest());
+        }
+    }
+
+    #[test]
+    pub fn test_grind_matches_proof_of_work() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut proof_of_work_channel = Blake2sChannel::new(Blake2sHash::default());
+
+        let nonce = channel.grind(11).unwrap();
+        let proof = ProofOfWork::new(11)
+            .prove(&mut proof_of_work_channel)
+            .unwrap();
+
+        assert_eq!(nonce, proof.nonce);
+        assert_eq!(channel.get_digest(), proof_of_work_channel.get_digest());
+    }
+
+    #[test]
+    pub fn test_grind_zero_bits() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+
+        assert_eq!(channel.grind(0), Ok(0));
+    }
+
+    #[test]
+    pub fn test_grind_too_many_bits() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+
+        assert_eq!(
+            channel.grind(257),
+            Err(ProofOfWorkError::DifficultyTooHigh {
+                n_bits: 257,
+                max_bits: 256
+            })
+        );
+        assert!(channel.verify_grind(0, 257).is_err());
+    }
+
+    #[test]
+    pub fn test_verify_grind_fail() {
+        let n_bits = 8;
+        let mut prover_channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut verifier_channel = Blake2sChannel::new(Blake2sHash::default());
+
+        let nonce = prover_channel.grind(n_bits).unwrap();
+
+        verifier_channel
+            .verify_grind(nonce + 1, n_bits)
+            .unwrap_err();
+    }
+
+    #[test]
+    pub fn test_leading_zeros_big_endian() {
+        assert_eq!(leading_zeros(&[0, 0x0f, 0]), 12);
+        assert_eq!(leading_zeros(&[0x80, 0]), 0);
+        assert_eq!(leading_zeros(&[0, 0]), 16);
+        assert_eq!(leading_zeros(Blake2sHash::default().as_ref()), 256);
+    }
+
     #[test]
     pub fn test_mix_felts() {
-        let initial_digest = Blake2sHash::from(vec![0; 32]);
+        let initial_digest = Blake2sHash::from([0; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
         let felts: Vec<SecureField> = (0..2)
             .map(|i| SecureField::from(m31!(i + 1923782)))
@@ -263,4 +818,137 @@ mod tests {
 
         assert_ne!(initial_digest, channel.digest);
     }
+
+    #[test]
+    pub fn test_mix_felts_big_endian() {
+        let felts = [SecureField::from(m31!(1)), SecureField::from(m31!(1 << 20))];
+        let config = ChannelConfig {
+            endianness: Endianness::Big,
+        };
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut big_endian_channel =
+            Blake2sChannel::new_with_config(Blake2sHash::default(), config);
+        let mut other_big_endian_channel =
+            Blake2sChannel::new_with_config(Blake2sHash::default(), config);
+
+        channel.mix_felts(&felts);
+        big_endian_channel.mix_felts(&felts);
+        other_big_endian_channel.mix_felts(&felts);
+
+        assert_ne!(channel.get_digest(), big_endian_channel.get_digest());
+        assert_eq!(
+            big_endian_channel.get_digest(),
+            other_big_endian_channel.get_digest()
+        );
+        assert_eq!(
+            big_endian_channel.get_digest(),
+            Blake2sHasher::hash(
+                &[
+                    Blake2sHash::default().as_ref(),
+                    &[FELTS_DOMAIN_TAG],
+                    &Endianness::Big.secure_felts_to_bytes(&felts)
+                ]
+                .concat()
+            )
+        );
+    }
+
+    #[test]
+    pub fn test_mix_bytes_framed() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut other_channel = Blake2sChannel::new(Blake2sHash::default());
+
+        channel.mix_bytes_framed(b"ab");
+        other_channel.mix_bytes_framed(b"a");
+        other_channel.mix_bytes_framed(b"b");
+
+        assert_ne!(channel.get_digest(), other_channel.get_digest());
+        assert_eq!(channel.channel_time.n_challenges, 1);
+    }
+
+    #[test]
+    pub fn test_mix_seed() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut other_channel = Blake2sChannel::new(Blake2sHash::default());
+        let mut framed_channel = Blake2sChannel::new(Blake2sHash::default());
+
+        channel.mix_seed(b"beacon");
+        framed_channel.mix_bytes_framed(b"beacon");
+
+        assert_ne!(channel.draw_felt(), other_channel.draw_felt());
+        assert_ne!(channel.get_digest(), framed_channel.get_digest());
+    }
+
+    #[test]
+    pub fn test_sample_strategy() {
+        /// Reduces the first 16 random bytes, without rejection.
+        struct Biased;
+
+        impl SampleStrategy for Biased {
+            fn next_felt(random_bytes: &mut dyn FnMut() -> Vec<u8>) -> SecureField {
+                let random_bytes = random_bytes();
+                SecureField::from_m31_array(std::array::from_fn(|i| {
+                    let word =
+                        u32::from_le_bytes(random_bytes[4 * i..4 * (i + 1)].try_into().unwrap());
+                    BaseField::reduce(word as u64)
+                }))
+            }
+        }
+
+        let digest = Blake2sHash::from([3; 32]);
+        let mut channel = Blake2sChannel::new(digest);
+        let mut other_channel = Blake2sChannel::new(digest);
+        let mut biased_channel = HashChannel::<Blake2sHasher, Biased>::new(digest);
+
+        assert_eq!(channel.draw_felts(3), other_channel.draw_felts(3));
+        let random_bytes = Blake2sChannel::new(digest).draw_random_bytes();
+        assert_eq!(
+            biased_channel.draw_felt(),
+            Blake2sHash::try_from(&random_bytes[..]).unwrap().to_qm31()
+        );
+        assert_eq!(biased_channel.draw_felts(2).len(), 2);
+        assert_eq!(biased_channel.channel_time.n_sent, 3);
+    }
+
+    #[test]
+    pub fn test_absorbed_bytes() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+        let felts = [SecureField::from(m31!(1)), SecureField::from(m31!(2))];
+
+        channel.mix_felts(&felts);
+        // A tag and two QM31 elements of 4 M31 limbs, of 4 bytes each.
+        assert_eq!(channel.absorbed_bytes(), 1 + 32);
+
+        channel.draw_felt();
+        channel.mix_bytes_framed(b"ab");
+        channel.mix_nonce(7);
+        assert_eq!(channel.absorbed_bytes(), (1 + 32) + (1 + 8 + 2) + (1 + 8));
+    }
+
+    #[test]
+    pub fn test_mix_domain_separation() {
+        let felts = [
+            QM31::from_u32_unchecked(1, 2, 3, 4),
+            QM31::from_u32_unchecked(5, 6, 7, 8),
+        ];
+        let felt_bytes = Endianness::Little.secure_felts_to_bytes(&felts);
+        let mix = |mix: &dyn Fn(&mut Blake2sChannel)| {
+            let mut channel = Blake2sChannel::new(Blake2sHash::default());
+            mix(&mut channel);
+            channel.get_digest()
+        };
+
+        // Every kind of mix of the same bytes results in a different state.
+        let digests = [
+            mix(&|channel| channel.mix_digest(Blake2sHash::try_from(&felt_bytes[..]).unwrap())),
+            mix(&|channel| channel.mix_felts(&felts)),
+            mix(&|channel| channel.mix_bytes_framed(&felt_bytes)),
+            mix(&|channel| channel.mix_seed(&felt_bytes)),
+            mix(&|channel| {
+                channel.mix_nonce(u64::from_le_bytes(felt_bytes[..8].try_into().unwrap()))
+            }),
+            mix(&|channel| channel.mix_bytes_framed(&felt_bytes[..8])),
+        ];
+        assert!(digests.iter().all_unique());
+    }
 }
@@ -521,7 +521,7 @@ mod tests {
 
     #[test]
     pub fn test_get_random_circle_point() {
-        let initial_digest = Blake2sHash::from(vec![2; 32]);
+        let initial_digest = Blake2sHash::from([2; 32]);
         let mut channel = Blake2sChannel::new(initial_digest);
 
         let first_random_circle_point = CirclePoint::get_random_point(&mut channel);
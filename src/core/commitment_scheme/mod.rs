@@ -5,13 +5,13 @@ use std::ops::Deref;
 use itertools::Itertools;
 
 use super::backend::cpu::{CPUCircleEvaluation, CPUCirclePoly};
-use super::channel::Blake2sChannel;
 use super::fields::m31::BaseField;
 use super::poly::circle::CanonicCoset;
 use super::poly::BitReversedOrder;
 use super::queries::SparseSubCircleDomain;
 use super::ColumnVec;
-use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+use crate::commitment_scheme::blake2_hash::Blake2sHasher;
+use crate::commitment_scheme::hasher::Hasher;
 use crate::commitment_scheme::merkle_decommitment::MerkleDecommitment;
 use crate::commitment_scheme::merkle_tree::MerkleTree;
 use crate::core::channel::Channel;
@@ -32,22 +32,24 @@ impl Deref for OpenedValues {
 }
 
 #[derive(Debug)]
-pub struct Decommitments(Vec<MerkleDecommitment<BaseField, Blake2sHasher>>);
+pub struct Decommitments<H: Hasher = Blake2sHasher>(Vec<MerkleDecommitment<BaseField, H>>);
 
-impl Deref for Decommitments {
-    type Target = Vec<MerkleDecommitment<BaseField, Blake2sHasher>>;
+impl<H: Hasher> Deref for Decommitments<H> {
+    type Target = Vec<MerkleDecommitment<BaseField, H>>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-pub struct CommitmentSchemeProver {
-    pub trees: Vec<CommitmentTreeProver>,
+/// Commits to columns of polynomials, one Merkle tree per [Self::commit] call, mixing each root
+/// into the channel, and keeps the trees to later open them at the queried positions.
+pub struct CommitmentSchemeProver<H: Hasher<NativeType = u8> = Blake2sHasher> {
+    pub trees: Vec<CommitmentTreeProver<H>>,
     pub log_blowup_factor: u32,
 }
 
-impl CommitmentSchemeProver {
+impl<H: Hasher<NativeType = u8>> CommitmentSchemeProver<H> {
     pub fn new(log_blowup_factor: u32) -> Self {
         CommitmentSchemeProver {
             trees: Vec::new(),
@@ -55,19 +57,27 @@ impl CommitmentSchemeProver {
         }
     }
 
-    pub fn commit(&mut self, polynomials: ColumnVec<CPUCirclePoly>, channel: &mut Blake2sChannel) {
+    /// Commits to the evaluations of `polynomials` on their blowup domains, and returns the root
+    /// of the resulting tree, which is also mixed into `channel`.
+    pub fn commit(
+        &mut self,
+        polynomials: ColumnVec<CPUCirclePoly>,
+        channel: &mut impl Channel<Digest = H::Hash>,
+    ) -> H::Hash {
         let tree = CommitmentTreeProver::new(polynomials, self.log_blowup_factor, channel);
+        let root = tree.root();
         self.trees.push(tree);
+        root
     }
 
-    pub fn roots(&self) -> Vec<Blake2sHash> {
+    pub fn roots(&self) -> Vec<H::Hash> {
         self.trees.iter().map(|tree| tree.root()).collect()
     }
 
     pub fn decommit(
         &self,
         positions: BTreeMap<u32, SparseSubCircleDomain>,
-    ) -> (OpenedValues, Decommitments) {
+    ) -> (OpenedValues, Decommitments<H>) {
         let (values, decommitments) = self
             .trees
             .iter()
@@ -81,18 +91,18 @@ impl CommitmentSchemeProver {
     }
 }
 
-pub struct CommitmentTreeProver {
+pub struct CommitmentTreeProver<H: Hasher<NativeType = u8> = Blake2sHasher> {
     pub polynomials: ColumnVec<CPUCirclePoly>,
     pub evaluations: ColumnVec<CPUCircleEvaluation<BaseField, BitReversedOrder>>,
     // TODO(AlonH): Change to mixed degree merkle and remove values clone.
-    pub commitment: MerkleTree<BaseField, Blake2sHasher>,
+    pub commitment: MerkleTree<BaseField, H>,
 }
 
-impl CommitmentTreeProver {
+impl<H: Hasher<NativeType = u8>> CommitmentTreeProver<H> {
     pub fn new(
         polynomials: Vec<CPUCirclePoly>,
         log_blowup_factor: u32,
-        channel: &mut Blake2sChannel,
+        channel: &mut impl Channel<Digest = H::Hash>,
     ) -> Self {
         let domains = polynomials
             .iter()
@@ -101,7 +111,7 @@ impl CommitmentTreeProver {
         let evaluations = zip(&polynomials, domains)
             .map(|(poly, domain)| poly.evaluate(domain.circle_domain()))
             .collect_vec();
-        let commitment = MerkleTree::<BaseField, Blake2sHasher>::commit(
+        let commitment = MerkleTree::<BaseField, H>::commit(
             evaluations
                 .iter()
                 .map(|eval| eval.values.clone())
@@ -120,10 +130,7 @@ impl CommitmentTreeProver {
     pub fn decommit(
         &self,
         positions: Vec<usize>,
-    ) -> (
-        ColumnVec<Vec<BaseField>>,
-        MerkleDecommitment<BaseField, Blake2sHasher>,
-    ) {
+    ) -> (ColumnVec<Vec<BaseField>>, MerkleDecommitment<BaseField, H>) {
         let values = self
             .evaluations
             .iter()
@@ -134,34 +141,41 @@ impl CommitmentTreeProver {
     }
 }
 
-impl Deref for CommitmentTreeProver {
-    type Target = MerkleTree<BaseField, Blake2sHasher>;
+impl<H: Hasher<NativeType = u8>> Deref for CommitmentTreeProver<H> {
+    type Target = MerkleTree<BaseField, H>;
 
     fn deref(&self) -> &Self::Target {
         &self.commitment
     }
 }
 
-#[derive(Default)]
-pub struct CommitmentSchemeVerifier {
-    pub commitments: Vec<CommitmentTreeVerifier>,
+/// The verifier side of [CommitmentSchemeProver]. Mixes the received roots into the channel in the
+/// same order as the prover, so both derive the same queries, and verifies the openings.
+pub struct CommitmentSchemeVerifier<H: Hasher<NativeType = u8> = Blake2sHasher> {
+    pub commitments: Vec<CommitmentTreeVerifier<H>>,
 }
 
-impl CommitmentSchemeVerifier {
+impl<H: Hasher<NativeType = u8>> Default for CommitmentSchemeVerifier<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Hasher<NativeType = u8>> CommitmentSchemeVerifier<H> {
     pub fn new() -> Self {
         CommitmentSchemeVerifier {
             commitments: Vec::new(),
         }
     }
 
-    pub fn commit(&mut self, commitment: Blake2sHash, channel: &mut Blake2sChannel) {
+    pub fn commit(&mut self, commitment: H::Hash, channel: &mut impl Channel<Digest = H::Hash>) {
         let verifier = CommitmentTreeVerifier::new(commitment, channel);
         self.commitments.push(verifier);
     }
 
     pub fn verify(
         &self,
-        decommitments: &[MerkleDecommitment<BaseField, Blake2sHasher>],
+        decommitments: &[MerkleDecommitment<BaseField, H>],
         positions: &[SparseSubCircleDomain],
     ) -> bool {
         self.commitments
@@ -174,21 +188,67 @@ impl CommitmentSchemeVerifier {
     }
 }
 
-pub struct CommitmentTreeVerifier {
-    pub commitment: Blake2sHash,
+pub struct CommitmentTreeVerifier<H: Hasher<NativeType = u8> = Blake2sHasher> {
+    pub commitment: H::Hash,
 }
 
-impl CommitmentTreeVerifier {
-    pub fn new(commitment: Blake2sHash, channel: &mut Blake2sChannel) -> Self {
+impl<H: Hasher<NativeType = u8>> CommitmentTreeVerifier<H> {
+    pub fn new(commitment: H::Hash, channel: &mut impl Channel<Digest = H::Hash>) -> Self {
         channel.mix_digest(commitment);
         CommitmentTreeVerifier { commitment }
     }
 
     pub fn verify(
         &self,
-        decommitment: &MerkleDecommitment<BaseField, Blake2sHasher>,
+        decommitment: &MerkleDecommitment<BaseField, H>,
         positions: &[usize],
     ) -> bool {
         decommitment.verify(self.commitment, positions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use itertools::Itertools;
+
+    use super::{CommitmentSchemeProver, CommitmentSchemeVerifier};
+    use crate::commitment_scheme::blake3_hash::{Blake3Hash, Blake3Hasher};
+    use crate::core::backend::cpu::CPUCirclePoly;
+    use crate::core::channel::{Channel, HashChannel};
+    use crate::core::fields::m31::BaseField;
+    use crate::core::queries::Queries;
+
+    #[test]
+    fn commit_open_verify_test() {
+        const LOG_BLOWUP_FACTOR: u32 = 1;
+        let log_size = 5;
+        let polynomials = (0..3)
+            .map(|i| {
+                CPUCirclePoly::new(
+                    (0..1 << log_size)
+                        .map(|j| BaseField::from_u32_unchecked(i * j + 1))
+                        .collect(),
+                )
+            })
+            .collect_vec();
+        let mut prover_channel = HashChannel::<Blake3Hasher>::new(Blake3Hash::default());
+        let mut verifier_channel = HashChannel::<Blake3Hasher>::new(Blake3Hash::default());
+        let mut prover = CommitmentSchemeProver::<Blake3Hasher>::new(LOG_BLOWUP_FACTOR);
+        let mut verifier = CommitmentSchemeVerifier::<Blake3Hasher>::new();
+
+        let root = prover.commit(polynomials, &mut prover_channel);
+        verifier.commit(root, &mut verifier_channel);
+        let log_domain_size = log_size + LOG_BLOWUP_FACTOR;
+        let queries = Queries::generate(&mut prover_channel, log_domain_size, 4);
+        let verifier_queries = Queries::generate(&mut verifier_channel, log_domain_size, 4);
+        let positions = queries.opening_positions(1);
+        let (opened_values, decommitments) =
+            prover.decommit(BTreeMap::from([(log_domain_size, positions)]));
+
+        assert_eq!(queries.positions, verifier_queries.positions);
+        assert_eq!(opened_values[0].len(), 3);
+        assert!(verifier.verify(&decommitments, &[verifier_queries.opening_positions(1)]));
+    }
+}
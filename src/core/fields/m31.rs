@@ -1,11 +1,11 @@
-use std::fmt::Display;
-use std::ops::{
+use core::fmt::Display;
+use core::ops::{
     Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
 };
 
 use bytemuck::{Pod, Zeroable};
 
-use super::{ComplexConjugate, FieldExpOps};
+use super::{ComplexConjugate, FieldExpOps, IntoSlice};
 use crate::impl_field;
 
 pub const MODULUS_BITS: u32 = 31;
@@ -40,12 +40,27 @@ impl M31 {
     }
 }
 
+// Safe as `M31` is `repr(transparent)`.
+unsafe impl IntoSlice<M31> for M31 {}
+
 impl Display for M31 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+/// Generates reduced elements only, i.e. in the range [0, `P`).
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for M31 {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(u.int_in_range(0..=P - 1)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <u32 as arbitrary::Arbitrary<'a>>::size_hint(depth)
+    }
+}
+
 impl Add for M31 {
     type Output = Self;
 
@@ -1,16 +1,17 @@
-use std::fmt::{Debug, Display};
-use std::iter::{Product, Sum};
-use std::ops::{Mul, MulAssign, Neg};
+use core::fmt::{Debug, Display};
+use core::iter::{Product, Sum};
+use core::ops::{Mul, MulAssign, Neg};
 
 use num_traits::{NumAssign, NumAssignOps, NumOps, One};
 
+#[cfg(feature = "std")]
 use super::backend::ColumnOps;
 
-#[cfg(target_arch = "x86_64")]
 pub mod cm31;
 pub mod m31;
 pub mod qm31;
 
+#[cfg(feature = "std")]
 pub trait FieldOps<F: Field>: ColumnOps<F> {
     // TODO(Ohad): change to use a mutable slice.
     fn batch_inverse(column: &Self::Column, dst: &mut Self::Column);
@@ -127,9 +128,9 @@ pub trait Field:
 pub unsafe trait IntoSlice<T: Sized>: Sized {
     fn into_slice(sl: &[Self]) -> &[T] {
         unsafe {
-            std::slice::from_raw_parts(
+            core::slice::from_raw_parts(
                 sl.as_ptr() as *const T,
-                std::mem::size_of_val(sl) / std::mem::size_of::<T>(),
+                core::mem::size_of_val(sl) / core::mem::size_of::<T>(),
             )
         }
     }
@@ -165,13 +166,17 @@ impl<F: Field> ExtensionOf<F> for F {
 #[macro_export]
 macro_rules! impl_field {
     ($field_name: ty, $field_size: ident) => {
-        use std::iter::{Product, Sum};
+        use core::iter::{Product, Sum};
 
         use num_traits::{Num, One, Zero};
         use $crate::core::fields::Field;
 
         impl Num for $field_name {
+            #[cfg(feature = "std")]
             type FromStrRadixErr = Box<dyn std::error::Error>;
+            // `from_str_radix` is unimplemented, so there is no error to box without `std`.
+            #[cfg(not(feature = "std"))]
+            type FromStrRadixErr = core::convert::Infallible;
 
             fn from_str_radix(_str: &str, _radix: u32) -> Result<Self, Self::FromStrRadixErr> {
                 unimplemented!(
@@ -206,7 +206,7 @@ impl<B: FriOps, H: Hasher<NativeType = u8>> FriProver<B, H> {
             }
 
             let layer = FriLayerProver::new(layer_evaluation);
-            channel.mix_digest(layer.merkle_tree.root());
+            channel.mix_digest(layer.commitment.root());
             let folding_alpha = channel.draw_felt();
             let folded_layer_evaluation = B::fold_line(&layer.evaluation, folding_alpha);
 
@@ -789,6 +789,91 @@ impl<H: Hasher<NativeType = u8>> FriLayerVerifier<H> {
     }
 }
 
+/// A merkle commitment to the evaluations of a FRI layer, along with the log size of the layer's
+/// domain. Each leaf commits to a single evaluation, in bit-reversed order.
+pub struct FriLayerCommitment<H: Hasher> {
+    pub merkle_tree: MerkleTree<SecureField, H>,
+    pub log_size: u32,
+}
+
+impl<H: Hasher<NativeType = u8>> FriLayerCommitment<H> {
+    pub fn root(&self) -> H::Hash {
+        self.merkle_tree.root()
+    }
+
+    /// Returns the part of the commitment the verifier keeps.
+    pub fn verifier(&self) -> FriLayerCommitmentVerifier<H> {
+        FriLayerCommitmentVerifier {
+            root: self.root(),
+            log_size: self.log_size,
+        }
+    }
+}
+
+/// Commits to the evaluations of a FRI layer.
+///
+/// # Panics
+///
+/// Panics if the number of evaluations is not a power of two.
+pub fn commit_fri_layer<H: Hasher<NativeType = u8>>(
+    evals: &[SecureField],
+) -> FriLayerCommitment<H> {
+    // TODO(spapini): Commit on slice.
+    commit_owned_fri_layer(evals.to_vec())
+}
+
+/// Like [commit_fri_layer], committing to the evaluations without copying them.
+fn commit_owned_fri_layer<H: Hasher<NativeType = u8>>(
+    evals: Vec<SecureField>,
+) -> FriLayerCommitment<H> {
+    assert!(
+        evals.len().is_power_of_two(),
+        "Layer size {} is not a power of two.",
+        evals.len()
+    );
+    let log_size = evals.len().ilog2();
+    FriLayerCommitment {
+        merkle_tree: MerkleTree::commit(vec![evals]),
+        log_size,
+    }
+}
+
+/// The verifier's view of a [FriLayerCommitment].
+#[derive(Debug, Clone, Copy)]
+pub struct FriLayerCommitmentVerifier<H: Hasher> {
+    pub root: H::Hash,
+    pub log_size: u32,
+}
+
+impl<H: Hasher<NativeType = u8>> FriLayerCommitmentVerifier<H> {
+    /// Maps a query in a layer of size `2^query_log_size`, e.g. the first layer, to the position
+    /// it gets folded into in this layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this layer is larger than the query's layer.
+    pub fn fold_query(&self, query: usize, query_log_size: u32) -> usize {
+        assert!(
+            query_log_size >= self.log_size,
+            "Query of a layer of log size {query_log_size} can't be mapped to log size {}.",
+            self.log_size
+        );
+        query >> (query_log_size - self.log_size)
+    }
+
+    /// Verifies a decommitment of the layer's evaluations at `positions`.
+    pub fn verify(
+        &self,
+        decommitment: &MerkleDecommitment<SecureField, H>,
+        positions: &[usize],
+    ) -> bool {
+        positions
+            .iter()
+            .all(|&position| position >> self.log_size == 0)
+            && decommitment.verify(self.root, positions)
+    }
+}
+
 /// A FRI layer comprises of a merkle tree that commits to evaluations of a polynomial.
 ///
 /// The polynomial evaluations are viewed as evaluation of a polynomial on multiple distinct cosets
@@ -796,18 +881,18 @@ impl<H: Hasher<NativeType = u8>> FriLayerVerifier<H> {
 // TODO(andrew): Support different step sizes.
 struct FriLayerProver<B: FriOps, H: Hasher> {
     evaluation: LineEvaluation<B, SecureField, BitReversedOrder>,
-    merkle_tree: MerkleTree<SecureField, H>,
+    commitment: FriLayerCommitment<H>,
 }
 
 impl<B: FriOps, H: Hasher<NativeType = u8>> FriLayerProver<B, H> {
     fn new(evaluation: LineEvaluation<B, SecureField, BitReversedOrder>) -> Self {
-        // TODO(spapini): Commit on slice.
         // TODO(spapini): Merkle tree in backend.
-        let merkle_tree = MerkleTree::commit(vec![evaluation.values.to_vec()]);
+        // The evaluations are copied once, out of the backend column.
+        let commitment = commit_owned_fri_layer(evaluation.values.to_vec());
         #[allow(unreachable_code)]
         FriLayerProver {
             evaluation,
-            merkle_tree,
+            commitment,
         }
     }
 
@@ -838,8 +923,11 @@ impl<B: FriOps, H: Hasher<NativeType = u8>> FriLayerProver<B, H> {
             }
         }
 
-        let commitment = self.merkle_tree.root();
-        let decommitment = self.merkle_tree.generate_decommitment(decommit_positions);
+        let commitment = self.commitment.root();
+        let decommitment = self
+            .commitment
+            .merkle_tree
+            .generate_decommitment(decommit_positions);
 
         FriLayerProof {
             evals_subset,
@@ -912,7 +1000,10 @@ mod tests {
 
     use num_traits::{One, Zero};
 
-    use super::{get_opening_positions, FriVerificationError, SparseCircleEvaluation};
+    use super::{
+        commit_fri_layer, get_opening_positions, FriVerificationError, SparseCircleEvaluation,
+        FOLD_STEP,
+    };
     use crate::commitment_scheme::blake2_hash::Blake2sHasher;
     use crate::core::backend::cpu::{CPUCircleEvaluation, CPUCirclePoly, CPULineEvaluation};
     use crate::core::backend::CPUBackend;
@@ -928,6 +1019,7 @@ mod tests {
     use crate::core::poly::{BitReversedOrder, NaturalOrder};
     use crate::core::queries::{Queries, SparseSubCircleDomain};
     use crate::core::test_utils::test_channel;
+    use crate::core::utils::bit_reverse_index;
 
     /// Default blowup factor used for tests.
     const LOG_BLOWUP_FACTOR: u32 = 2;
@@ -1227,6 +1319,67 @@ mod tests {
         let _ = verifier.decommit_on_queries(&invalid_queries, vec![decommitment_value]);
     }
 
+    #[test]
+    fn fri_layer_commitment_test() {
+        const LOG_SIZE: u32 = 4;
+        let alpha = SecureField::from(BaseField::from_u32_unchecked(7));
+        let domain = LineDomain::new(Coset::half_odds(LOG_SIZE));
+        let evaluation = CPULineEvaluation::<SecureField, BitReversedOrder>::new(
+            domain,
+            (0..1 << LOG_SIZE)
+                .map(|i| BaseField::from_u32_unchecked(i * i + 1).into())
+                .collect(),
+        );
+        let mut layers = vec![evaluation];
+        for _ in 0..2 {
+            let folded = CPUBackend::fold_line(layers.last().unwrap(), alpha);
+            layers.push(folded);
+        }
+        let commitments = layers
+            .iter()
+            .map(|layer| commit_fri_layer::<Blake2sHasher>(&layer.values))
+            .collect::<Vec<_>>();
+        let query = 13;
+
+        for (i, (layer, commitment)) in zip(&layers, &commitments).enumerate() {
+            let verifier = commitment.verifier();
+            let position = verifier.fold_query(query, LOG_SIZE);
+            let subline_start = (position >> FOLD_STEP) << FOLD_STEP;
+            let positions = (subline_start..subline_start + (1 << FOLD_STEP)).collect::<Vec<_>>();
+            let decommitment = commitment
+                .merkle_tree
+                .generate_decommitment(positions.clone());
+
+            assert_eq!(verifier.log_size, LOG_SIZE - i as u32);
+            assert_eq!(position, query >> i);
+            assert!(verifier.verify(&decommitment, &positions));
+            assert!(decommitment
+                .values()
+                .eq(positions.iter().map(|&p| vec![layer.values[p]])));
+
+            // The subline opened in this layer folds into the next layer's evaluation at the next
+            // mapped position.
+            if let Some(next_layer) = layers.get(i + 1) {
+                let subline_domain = LineDomain::new(Coset::new(
+                    layer
+                        .domain()
+                        .coset()
+                        .index_at(bit_reverse_index(subline_start, verifier.log_size)),
+                    FOLD_STEP,
+                ));
+                let subline = CPULineEvaluation::<SecureField, BitReversedOrder>::new(
+                    subline_domain,
+                    layer.values[subline_start..subline_start + (1 << FOLD_STEP)].to_vec(),
+                );
+                let next_position = commitments[i + 1].verifier().fold_query(query, LOG_SIZE);
+                assert_eq!(
+                    CPUBackend::fold_line(&subline, alpha).values[0],
+                    next_layer.values[next_position]
+                );
+            }
+        }
+    }
+
     /// Returns an evaluation of a random polynomial with degree `2^log_degree`.
     ///
     /// The evaluation domain size is `2^(log_degree + log_blowup_factor)`.
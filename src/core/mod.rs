@@ -1,20 +1,34 @@
-use std::ops::{Deref, DerefMut};
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
 
+#[cfg(feature = "std")]
 pub mod air;
+#[cfg(feature = "std")]
 pub mod backend;
+#[cfg(feature = "std")]
 pub mod channel;
+#[cfg(feature = "std")]
 pub mod circle;
+#[cfg(feature = "std")]
 pub mod commitment_scheme;
+#[cfg(feature = "std")]
 pub mod constraints;
+#[cfg(feature = "std")]
 pub mod fft;
 pub mod fields;
+#[cfg(feature = "std")]
 pub mod fri;
+#[cfg(feature = "std")]
 pub mod oods;
+#[cfg(feature = "std")]
 pub mod poly;
+#[cfg(feature = "std")]
 pub mod proof_of_work;
+#[cfg(feature = "std")]
 pub mod prover;
+#[cfg(feature = "std")]
 pub mod queries;
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 pub mod test_utils;
 pub mod utils;
 
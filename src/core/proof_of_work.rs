@@ -1,11 +1,13 @@
 use thiserror::Error;
 
-use super::channel::Blake2sChannel;
-use crate::commitment_scheme::blake2_hash::{Blake2sHash, Blake2sHasher};
+use super::channel::{HashChannel, SampleStrategy};
 use crate::commitment_scheme::hasher::Hasher;
+use crate::commitment_scheme::utils::leading_zeros;
 use crate::core::channel::Channel;
 
-// TODO(ShaharS): generalize to more channels and create a from function in the hash traits.
+/// A proof of work on the digest of a [HashChannel]: a nonce such that the hash of the digest
+/// followed by the little-endian nonce has at least `n_bits` leading zero bits, read as a
+/// big-endian integer, see [leading_zeros]. The nonce is mixed into the channel afterwards.
 pub struct ProofOfWork {
     // Proof of work difficulty.
     pub n_bits: u32,
@@ -21,25 +23,39 @@ impl ProofOfWork {
         Self { n_bits }
     }
 
-    pub fn prove(&self, channel: &mut Blake2sChannel) -> ProofOfWorkProof {
-        let seed = channel.get_digest().as_ref().to_vec();
-        let proof = self.grind(seed);
-        channel.mix_nonce(proof.nonce);
-        proof
+    /// Searches for the smallest valid nonce and mixes it into the channel. Fails if `n_bits`
+    /// exceeds the size of the digest, as no nonce would be valid.
+    pub fn prove<H: Hasher<NativeType = u8>, S: SampleStrategy>(
+        &self,
+        channel: &mut HashChannel<H, S>,
+    ) -> Result<ProofOfWorkProof, ProofOfWorkError> {
+        let max_bits = 8 * H::OUTPUT_SIZE as u32;
+        if self.n_bits > max_bits {
+            return Err(ProofOfWorkError::DifficultyTooHigh {
+                n_bits: self.n_bits,
+                max_bits,
+            });
+        }
+        // TODO(ShaharS): naive implementation, should be replaced with a parallel one.
+        let mut hash_input = Self::hash_input(channel, 0);
+        let nonce = (0..)
+            .find(|nonce| {
+                let nonce_start = hash_input.len() - 8;
+                hash_input[nonce_start..].copy_from_slice(&u64::to_le_bytes(*nonce));
+                self.is_valid_hash(H::hash(&hash_input))
+            })
+            .expect("No valid nonce!");
+        channel.mix_nonce(nonce);
+        Ok(ProofOfWorkProof { nonce })
     }
 
-    pub fn verify(
+    /// Checks the proof, then mixes its nonce into the channel.
+    pub fn verify<H: Hasher<NativeType = u8>, S: SampleStrategy>(
         &self,
-        channel: &mut Blake2sChannel,
+        channel: &mut HashChannel<H, S>,
         proof: &ProofOfWorkProof,
     ) -> Result<(), ProofOfWorkVerificationError> {
-        let seed = channel.get_digest().as_ref().to_vec();
-        let verified = check_leading_zeros(
-            self.hash_with_nonce(&seed, proof.nonce).as_ref(),
-            self.n_bits,
-        );
-
-        if !verified {
+        if !self.check(channel, proof.nonce) {
             return Err(ProofOfWorkVerificationError::ProofOfWorkVerificationFailed);
         }
 
@@ -47,41 +63,32 @@ impl ProofOfWork {
         Ok(())
     }
 
-    fn grind(&self, seed: Vec<u8>) -> ProofOfWorkProof {
-        let mut nonce = 0u64;
-        // TODO(ShaharS): naive implementation, should be replaced with a parallel one.
-        loop {
-            let hash = self.hash_with_nonce(&seed, nonce);
-            if check_leading_zeros(hash.as_ref(), self.n_bits) {
-                return ProofOfWorkProof { nonce };
-            }
-            nonce += 1;
-        }
+    /// Returns whether `nonce` is a valid proof of work on the current digest of the channel.
+    pub fn check<H: Hasher<NativeType = u8>, S: SampleStrategy>(
+        &self,
+        channel: &HashChannel<H, S>,
+        nonce: u64,
+    ) -> bool {
+        self.is_valid_hash(H::hash(&Self::hash_input(channel, nonce)))
     }
 
-    fn hash_with_nonce(&self, seed: &[u8], nonce: u64) -> Blake2sHash {
-        let hash_input = seed
-            .iter()
-            .chain(nonce.to_le_bytes().iter())
-            .cloned()
-            .collect::<Vec<_>>();
-        Blake2sHasher::hash(&hash_input)
+    /// The digest of the channel followed by the little-endian nonce.
+    fn hash_input<H: Hasher<NativeType = u8>, S: SampleStrategy>(
+        channel: &HashChannel<H, S>,
+        nonce: u64,
+    ) -> Vec<u8> {
+        [channel.get_digest().as_ref(), &nonce.to_le_bytes()].concat()
     }
-}
 
-/// Check that the prefix leading zeros is greater than `bound_bits`.
-fn check_leading_zeros(bytes: &[u8], bound_bits: u32) -> bool {
-    let mut n_bits = 0;
-    // bytes are in little endian order.
-    for byte in bytes.iter().rev() {
-        if *byte == 0 {
-            n_bits += 8;
-        } else {
-            n_bits += byte.leading_zeros();
-            break;
-        }
+    fn is_valid_hash(&self, hash: impl AsRef<[u8]>) -> bool {
+        leading_zeros(hash.as_ref()) >= self.n_bits
     }
-    n_bits >= bound_bits
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum ProofOfWorkError {
+    #[error("Cannot grind {n_bits} bits on a digest of {max_bits} bits.")]
+    DifficultyTooHigh { n_bits: u32, max_bits: u32 },
 }
 
 #[derive(Clone, Copy, Debug, Error)]
@@ -94,20 +101,20 @@ pub enum ProofOfWorkVerificationError {
 mod tests {
     use crate::commitment_scheme::blake2_hash::Blake2sHash;
     use crate::core::channel::{Blake2sChannel, Channel};
-    use crate::core::proof_of_work::{ProofOfWork, ProofOfWorkProof};
+    use crate::core::proof_of_work::{ProofOfWork, ProofOfWorkError, ProofOfWorkProof};
 
     #[test]
     fn test_verify_proof_of_work_success() {
-        let mut channel = Blake2sChannel::new(Blake2sHash::from(vec![0; 32]));
+        let mut channel = Blake2sChannel::new(Blake2sHash::from([0; 32]));
         let proof_of_work_prover = ProofOfWork { n_bits: 11 };
-        let proof = ProofOfWorkProof { nonce: 133 };
+        let proof = ProofOfWorkProof { nonce: 2374 };
 
         proof_of_work_prover.verify(&mut channel, &proof).unwrap();
     }
 
     #[test]
     fn test_verify_proof_of_work_fail() {
-        let mut channel = Blake2sChannel::new(Blake2sHash::from(vec![0; 32]));
+        let mut channel = Blake2sChannel::new(Blake2sHash::from([0; 32]));
         let proof_of_work_prover = ProofOfWork { n_bits: 1 };
         let invalid_proof = ProofOfWorkProof { nonce: 0 };
 
@@ -124,9 +131,23 @@ mod tests {
         let prover = ProofOfWork::new(n_bits);
         let verifier = ProofOfWork::new(n_bits);
 
-        let proof = prover.prove(&mut prover_channel);
+        let proof = prover.prove(&mut prover_channel).unwrap();
         verifier.verify(&mut verifier_channel, &proof).unwrap();
 
         assert_eq!(prover_channel.get_digest(), verifier_channel.get_digest());
     }
+
+    #[test]
+    fn test_proof_of_work_difficulty_too_high() {
+        let mut channel = Blake2sChannel::new(Blake2sHash::default());
+
+        assert_eq!(
+            ProofOfWork::new(257).prove(&mut channel).unwrap_err(),
+            ProofOfWorkError::DifficultyTooHigh {
+                n_bits: 257,
+                max_bits: 256
+            }
+        );
+        assert_eq!(channel.get_digest(), Blake2sHash::default());
+    }
 }
@@ -5,7 +5,7 @@ use thiserror::Error;
 
 use super::fri::FriVerificationError;
 use super::poly::circle::{CanonicCoset, MAX_CIRCLE_DOMAIN_LOG_SIZE};
-use super::proof_of_work::ProofOfWorkVerificationError;
+use super::proof_of_work::{ProofOfWorkError, ProofOfWorkVerificationError};
 use super::queries::SparseSubCircleDomain;
 use super::ColumnVec;
 use crate::commitment_scheme::blake2_hash::Blake2sHasher;
@@ -138,7 +138,7 @@ pub fn prove(
     let fri_config = FriConfig::new(LOG_LAST_LAYER_DEGREE_BOUND, LOG_BLOWUP_FACTOR, N_QUERIES);
     let fri_prover = FriProver::commit(channel, fri_config, &oods_quotients);
 
-    let proof_of_work = ProofOfWork::new(PROOF_OF_WORK_BITS).prove(channel);
+    let proof_of_work = ProofOfWork::new(PROOF_OF_WORK_BITS).prove(channel)?;
     let (fri_proof, fri_opening_positions) = fri_prover.decommit(channel);
 
     let (opened_values, decommitments) = commitment_scheme.decommit(fri_opening_positions);
@@ -301,6 +301,8 @@ pub enum ProvingError {
     MaxCompositionDegreeExceeded { degree: u32 },
     #[error("Constraints not satisfied.")]
     ConstraintsNotSatisfied,
+    #[error(transparent)]
+    ProofOfWork(#[from] ProofOfWorkError),
 }
 
 #[derive(Clone, Copy, Debug, Error)]
@@ -7,7 +7,7 @@ use crate::commitment_scheme::hasher::Hasher;
 use crate::core::backend::cpu::CPUCircleEvaluation;
 use crate::core::channel::{Blake2sChannel, Channel};
 use crate::core::fields::m31::BaseField;
-use crate::core::fields::{FieldExpOps, IntoSlice};
+use crate::core::fields::FieldExpOps;
 use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
 use crate::core::poly::BitReversedOrder;
 use crate::core::prover::{prove, verify, ProvingError, StarkProof, VerificationError};
@@ -49,18 +49,14 @@ impl Fibonacci {
 
     pub fn prove(&self) -> Result<StarkProof, ProvingError> {
         let trace = self.get_trace();
-        let channel = &mut Blake2sChannel::new(Blake2sHasher::hash(BaseField::into_slice(&[self
-            .air
-            .component
-            .claim])));
+        let channel =
+            &mut Blake2sChannel::new(Blake2sHasher::hash_felts(&[self.air.component.claim]));
         prove(&self.air, channel, vec![trace])
     }
 
     pub fn verify(&self, proof: StarkProof) -> Result<(), VerificationError> {
-        let channel = &mut Blake2sChannel::new(Blake2sHasher::hash(BaseField::into_slice(&[self
-            .air
-            .component
-            .claim])));
+        let channel =
+            &mut Blake2sChannel::new(Blake2sHasher::hash_felts(&[self.air.component.claim]));
         verify(proof, &self.air, channel)
     }
 }
@@ -88,20 +84,20 @@ impl MultiFibonacci {
     }
 
     pub fn prove(&self) -> Result<StarkProof, ProvingError> {
-        let channel = &mut Blake2sChannel::new(Blake2sHasher::hash(BaseField::into_slice(&[self
+        let channel = &mut Blake2sChannel::new(Blake2sHasher::hash_felts(&[self
             .single_fib
             .air
             .component
-            .claim])));
+            .claim]));
         prove(&self.air, channel, self.get_trace())
     }
 
     pub fn verify(&self, proof: StarkProof) -> Result<(), VerificationError> {
-        let channel = &mut Blake2sChannel::new(Blake2sHasher::hash(BaseField::into_slice(&[self
+        let channel = &mut Blake2sChannel::new(Blake2sHasher::hash_felts(&[self
             .single_fib
             .air
             .component
-            .claim])));
+            .claim]));
         verify(proof, &self.air, channel)
     }
 }
@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![feature(
     array_chunks,
     iter_array_chunks,
@@ -9,9 +10,12 @@
     get_many_mut,
     int_roundings
 )]
+extern crate alloc;
+
 pub mod commitment_scheme;
 pub mod core;
+#[cfg(feature = "std")]
 pub mod fibonacci;
-pub mod hash_functions;
 pub mod math;
+#[cfg(feature = "std")]
 pub mod platform;
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::ExtensionOf;
 
@@ -2,7 +2,7 @@
 pub fn log2_ceil(n: usize) -> usize {
     assert_ne!(n, 0, "Attempt log(0)!");
     const NUM_OF_BITS_IN_BYTE: usize = 8;
-    let num_of_bits = std::mem::size_of_val(&n) * NUM_OF_BITS_IN_BYTE;
+    let num_of_bits = core::mem::size_of_val(&n) * NUM_OF_BITS_IN_BYTE;
     num_of_bits - (n - 1).leading_zeros() as usize
 }
 
@@ -10,7 +10,7 @@ pub fn log2_ceil(n: usize) -> usize {
 pub fn log2_floor(n: usize) -> usize {
     assert_ne!(n, 0, "Attempt log(0)!");
     const NUM_OF_BITS_IN_BYTE: usize = 8;
-    let num_of_bits = std::mem::size_of_val(&n) * NUM_OF_BITS_IN_BYTE;
+    let num_of_bits = core::mem::size_of_val(&n) * NUM_OF_BITS_IN_BYTE;
     num_of_bits - n.leading_zeros() as usize - 1
 }
 